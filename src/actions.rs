@@ -1,64 +1,351 @@
+use crate::backup;
+use crate::calendar::{self, Coverage};
+use crate::caption;
+use crate::checksum::{self, Mismatch};
+use crate::cleanup;
 use crate::commands;
-use crate::confirm;
+use crate::config::{PostLayout, Variant};
 use crate::constants::*;
+use crate::dedupe;
+use crate::error::Error;
+use crate::feed::{self, FeedEntry};
 use crate::file;
-use crate::location::Location;
-use crate::random;
+use crate::generation::MakeContext;
+use crate::glossary::Glossary;
+use crate::history;
+use crate::hooks;
+use crate::image_diff;
+use crate::import;
+use crate::jobs::{self, Job};
+use crate::location::{Location, Stage};
+use crate::lock;
+use crate::names;
+use crate::ocr;
+use crate::optimize;
+use crate::output;
+use crate::pipeline;
+use crate::preview;
+use crate::props::{self, Approval, Contribution};
+use crate::publish::{self, PublishTarget};
+use crate::range::{DateRange, FullDateRange, YearMonth, YearOrMonth};
+use crate::ratings;
+use crate::recent;
+use crate::report::{ReportEntry, ReportFormat};
+use crate::schedule::{self, Schedule};
+use crate::sheet;
+use crate::site::{self, SitePost};
+use crate::skips;
+use crate::source_dates;
+use crate::special::{self, Special, SpecialKind};
+use crate::submissions;
+use crate::text_diff;
+use crate::thumbnail::ThumbnailCache;
+use crate::timings::{self, Phase};
+use crate::transcript::{self, Transcript, TranscriptFormat};
+use crate::trash;
+use crate::ui::{self, PromptId};
+use crate::uploads::{self, UploadRecord};
+use crate::vcs;
+use crate::watch;
+use crate::watermarks::WatermarkContext;
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{bail, Context as _, Result};
-use chrono::NaiveDate;
-use rand::Rng as _;
+use chrono::{DateTime, Datelike as _, Local, NaiveDate};
+use image::{DynamicImage, GenericImageView as _};
 
 pub fn show(location: &Location, date: NaiveDate) -> Result<()> {
-    let source_dir = location.source_dir();
+    let path = location.source_file(date);
 
-    let mut path = source_dir.join(date.to_string());
-    path.set_extension(SOURCE_FORMAT);
+    recent::append(location, date).with_context(|| "Appending date to recent dates file")?;
 
-    file::append_date(location.recent_file(), date)
-        .with_context(|| "Appending date to recent dates file")?;
+    let window = location.config().viewer.show_window();
+    location.system().kill_process_name(&window)?;
+    location.system().spawn_image_viewer(
+        &location.config().viewer.program,
+        &[path],
+        &window,
+        true,
+        None,
+    )?;
+
+    Ok(())
+}
+
+pub fn rate(location: &Location, date: NaiveDate, rating: u8) -> Result<()> {
+    if !location.source_file(date).exists() {
+        bail!(Error::usage("Not the date of an existing comic"));
+    }
+    ratings::set(location, date, rating)?;
+    output::success(format!(
+        "Rated {} as {}/{}",
+        date,
+        rating,
+        ratings::MAX_RATING
+    ));
+    Ok(())
+}
+
+pub fn skip(
+    location: &Location,
+    date: NaiveDate,
+    reason: Option<&str>,
+    remove: bool,
+) -> Result<()> {
+    if remove {
+        if !skips::remove(location, date)? {
+            bail!(Error::usage("Comic is not in skip list"));
+        }
+        output::success(format!("Removed {} from skip list", date));
+        return Ok(());
+    }
+    if !location.source_file(date).exists() {
+        bail!(Error::usage("Not the date of an existing comic"));
+    }
+    skips::add(location, date, reason.unwrap_or_default())?;
+    output::success(format!("Added {} to skip list", date));
+    Ok(())
+}
+
+/// Empty answer skips rating
+pub fn ask_rating(location: &Location, date: NaiveDate) -> Result<()> {
+    let prompt = format!("Rating (1-{}, empty to skip):", ratings::MAX_RATING);
+    loop {
+        let answer = location.prompter().ask(&prompt);
+        if answer.is_empty() {
+            return Ok(());
+        }
+        match answer.parse::<u8>() {
+            Ok(rating) if (1..=ratings::MAX_RATING).contains(&rating) => {
+                return rate(location, date, rating);
+            }
+            _ => println!("Invalid rating."),
+        }
+    }
+}
+
+/// Every date is appended to recent dates file, in order of display
+pub fn show_slideshow(location: &Location, dates: &[NaiveDate], delay: u64) -> Result<()> {
+    let paths: Vec<PathBuf> = dates
+        .iter()
+        .map(|date| location.source_file(*date))
+        .collect();
+
+    for date in dates {
+        recent::append(location, *date).with_context(|| "Appending date to recent dates file")?;
+    }
 
-    commands::kill_process_name(window_name::SHOW)?;
-    commands::spawn_image_viewer(&[path], window_name::SHOW, true)?;
+    let window = location.config().viewer.show_window();
+    location.system().kill_process_name(&window)?;
+    location.system().spawn_image_viewer(
+        &location.config().viewer.program,
+        &paths,
+        &window,
+        true,
+        Some(delay),
+    )?;
 
     Ok(())
 }
 
-pub fn make(location: &Location, date: NaiveDate, name: &str, skip_post_check: bool) -> Result<()> {
+/// With `special`, post is marked as special from the start (without a description)
+pub fn make(
+    location: &Location,
+    date: NaiveDate,
+    name: &str,
+    skip_post_check: bool,
+    special: Option<SpecialKind>,
+) -> Result<()> {
+    make_with(
+        &mut MakeContext::new(location)?,
+        date,
+        name,
+        skip_post_check,
+        special,
+    )
+}
+
+/// Same as `make`, but reusing watermarks and icons loaded for other posts of a batch
+pub fn make_with(
+    context: &mut MakeContext,
+    date: NaiveDate,
+    name: &str,
+    skip_post_check: bool,
+    special: Option<SpecialKind>,
+) -> Result<()> {
+    let location = context.location();
+    let original_comic_path = location.source_file(date);
+    if !original_comic_path.exists() {
+        bail!(Error::usage("Not the date of an existing comic"));
+    }
+    make_from_path(
+        context,
+        &original_comic_path,
+        date,
+        name,
+        skip_post_check,
+        special,
+        false,
+    )
+}
+
+/// Generates a completed post again, for `revise`. Watermark of post is kept, unless
+/// `new_watermark`.
+pub fn remake(location: &Location, id: &str, new_watermark: bool) -> Result<()> {
+    remake_with(&mut MakeContext::new(location)?, id, new_watermark)
+}
+
+/// Like `remake`, but shares watermarks and icons with other posts revised in the same batch
+pub fn remake_with(context: &mut MakeContext, id: &str, new_watermark: bool) -> Result<()> {
+    let location = context.location();
+    let date = names::read_date(location, id)
+        .with_context(|| "Reading date from existing post directory")?;
+    let original_comic_path = location.source_file(date);
+    if !original_comic_path.exists() {
+        bail!(Error::usage("Not the date of an existing comic"));
+    }
+    make_from_path(
+        context,
+        &original_comic_path,
+        date,
+        id,
+        true,
+        None,
+        new_watermark,
+    )
+}
+
+/// Makes a post from an image outside of source directory, such as a one-off strip.
+/// With `save_source`, image is first saved (as PNG) to source directory for the given date.
+pub fn make_from_file(
+    location: &Location,
+    path: &Path,
+    date: NaiveDate,
+    name: &str,
+    save_source: bool,
+    special: Option<SpecialKind>,
+) -> Result<()> {
+    if !path.is_file() {
+        bail!(Error::usage("Comic file does not exist"));
+    }
+    let format = image::io::Reader::open(path)
+        .with_context(|| "Opening comic file")?
+        .with_guessed_format()
+        .with_context(|| "Reading comic file")?
+        .format();
+    let Some(format) = format else {
+        bail!(Error::validation(
+            "Comic file is not a recognized image format"
+        ));
+    };
+    let comic = image::open(path)
+        .with_context(|| Error::validation("Comic file could not be decoded as an image"))?;
+    if comic.width() == 0 || comic.height() == 0 {
+        bail!(Error::validation("Comic file is an empty image"));
+    }
+
+    let mut context = MakeContext::new(location)?;
+    if !save_source {
+        return make_from_path(&mut context, path, date, name, false, special, false);
+    }
+
+    let source_path = location.source_file(date);
+    if source_path.exists() {
+        bail!(Error::usage(
+            "There already exists a source comic with that date"
+        ));
+    }
+    if format == image::ImageFormat::Png {
+        fs::copy(path, &source_path).with_context(|| "Copying comic to source directory")?;
+    } else {
+        comic
+            .save_with_format(&source_path, image::ImageFormat::Png)
+            .with_context(|| "Saving comic to source directory")?;
+    }
+    println!("Saved comic to {}", source_path.display());
+    make_from_path(
+        &mut context,
+        &source_path,
+        date,
+        name,
+        false,
+        special,
+        false,
+    )
+}
+
+fn make_from_path(
+    context: &mut MakeContext,
+    original_comic_path: &Path,
+    date: NaiveDate,
+    name: &str,
+    skip_post_check: bool,
+    special: Option<SpecialKind>,
+    new_watermark: bool,
+) -> Result<()> {
+    let location = context.location();
     let generated_dir = location.generated_dir();
 
-    let mut original_comic_path = location.source_dir().join(date.to_string());
-    original_comic_path.set_extension(SOURCE_FORMAT);
     let output_dir = generated_dir.join(name);
     let title_file_path = output_dir.join(post_file::TITLE);
     let date_file_path = output_dir.join(post_file::DATE);
-    let initial_path = output_dir.join(post_file::INITIAL);
-    let duplicate_file_path = output_dir.join(post_file::DUPLICATE);
 
-    let icon = image::open(location.icon_file()).with_context(|| "Opening icon image")?;
+    let icon_path = location.icon_for_date(date);
 
-    let watermark = get_random_watermark(location).with_context(|| "Parsing watermark")?;
-
-    if !original_comic_path.exists() {
-        bail!("Not the date of an existing comic");
-    }
+    // Regenerating a completed post, for `revise`
+    let old_post_path = location.posts_dir().join(name);
+    let is_special = match special {
+        Some(_) => true,
+        None => skip_post_check && special::read(&old_post_path)?.is_some(),
+    };
+    let watermark_context = WatermarkContext {
+        date,
+        special: is_special,
+    };
+    let old_watermark_path = old_post_path.join(post_file::WATERMARK);
+    let watermark = if skip_post_check && !new_watermark && old_watermark_path.is_file() {
+        fs::read_to_string(old_watermark_path).with_context(|| "Reading watermark file")?
+    } else {
+        context
+            .watermarks()
+            .choose_random(watermark_context)
+            .with_context(|| "Parsing watermark")?
+    };
+    let is_reusable = skip_post_check
+        && context
+            .is_reusable(&old_post_path, original_comic_path, &icon_path, &watermark)
+            .with_context(|| "Checking if images of existing post can be reused")?;
 
-    if exists_post_with_date(&generated_dir, date)
-        .with_context(|| "Checking if post already generated")?
-    {
-        bail!("There already exists a generated post with that date");
+    let existing_posts = names::find_posts_by_date(location, date)
+        .with_context(|| "Checking if post already exists")?;
+    for (stage, id) in &existing_posts {
+        match stage {
+            Stage::Completed if !skip_post_check => {
+                bail!(
+                    "There already exists a completed post with that date: {}",
+                    id
+                )
+            }
+            Stage::Old if !skip_post_check => {
+                bail!("There already exists an old post with that date: {}", id)
+            }
+            Stage::Generated | Stage::Completed | Stage::Old => (),
+        }
     }
-    if exists_post_with_date(location.posts_dir(), date)
-        .with_context(|| "Checking if post already exists")?
-        && !skip_post_check
-    {
-        bail!("There already exists a completed post with that date");
+    // Only after other conflicts, so a post is not moved to trash if making would fail anyway
+    for (stage, id) in &existing_posts {
+        if *stage == Stage::Generated {
+            resolve_generated_conflict(location, id)?;
+        }
     }
 
+    hooks::run_pre(location, "make", name, &output_dir)?;
+
     // Parent should already be created
     fs::create_dir(&output_dir).with_context(|| "Creating generated post directory")?;
 
@@ -66,63 +353,377 @@ pub fn make(location: &Location, date: NaiveDate, name: &str, skip_post_check: b
 
     fs::File::create(title_file_path).with_context(|| "Creating title file")?;
 
-    let original_comic = image::open(original_comic_path).with_context(|| "Opening comic image")?;
-    let generated_comic = comic_format::convert_image(original_comic, &icon, &watermark, 0.0);
+    fs::write(output_dir.join(post_file::WATERMARK), &watermark)
+        .with_context(|| "Writing to watermark file")?;
+
+    if is_reusable {
+        reuse_images(location, &old_post_path, &output_dir)
+            .with_context(|| "Copying images of existing post")?;
+        println!("Reused unchanged images of {}", name);
+    } else {
+        let icon = context.icon(&icon_path)?;
+        generate_images(location, original_comic_path, icon, &watermark, &output_dir)?;
+    }
+    let inputs_hash = context
+        .hash_inputs(original_comic_path, &icon_path, &watermark)
+        .with_context(|| "Hashing generation inputs")?;
+    fs::write(output_dir.join(post_file::GENERATION), inputs_hash)
+        .with_context(|| "Writing to generation file")?;
+    if let Some(kind) = special {
+        let special = Special {
+            kind,
+            description: String::new(),
+        };
+        special::write(&output_dir, &special)?;
+    }
+
+    output::success(format!("Created {}", output::id(name)));
+
+    checksum::update(&output_dir).with_context(|| "Updating checksums")?;
+
+    vcs::commit_post(location, "make", name, &output_dir)
+        .with_context(|| "Committing generated post")?;
+    hooks::run_post(location, "make", name, &output_dir);
+
+    Ok(())
+}
+
+/// Existing post is moved to trash if replaced, so it can be restored. Fails if cancelled, or if
+/// there is no answer (eg. from a script).
+fn resolve_generated_conflict(location: &Location, id: &str) -> Result<()> {
+    println!(
+        "There already exists a generated post with that date: {}",
+        output::id(id)
+    );
+    loop {
+        let answer = location
+            .prompter()
+            .ask("[o]pen existing post, [r]eplace it, or [C]ancel:");
+        match answer.to_lowercase().as_str() {
+            "o" | "open" => open(location, id, Some(&location.layout().initial))?,
+            "r" | "replace" => break,
+            "" | "c" | "cancel" => bail!(
+                "There already exists a generated post with that date: {}",
+                id
+            ),
+            _ => println!("Invalid answer."),
+        }
+    }
+    let name = trash::move_to_trash(location, Stage::Generated, id)
+        .with_context(|| "Moving existing post to trash")?;
+    println!("Moved {} to trash as {}", output::id(id), output::id(&name));
+    Ok(())
+}
+
+fn generate_images(
+    location: &Location,
+    original_comic_path: &Path,
+    icon: &DynamicImage,
+    watermark: &str,
+    output_dir: &Path,
+) -> Result<()> {
+    let initial_path = output_dir.join(&location.layout().initial);
+    let duplicate_file_path = output_dir.join(&location.layout().duplicate);
 
-    generated_comic
-        .save(&initial_path)
+    let original_comic = timings::time(Phase::Decode, || image::open(original_comic_path))
+        .with_context(|| "Opening comic image")?;
+    let generated_comic = timings::time(Phase::Convert, || {
+        comic_format::convert_image(original_comic, icon, watermark, 0.0)
+    });
+
+    timings::time(Phase::Encode, || generated_comic.save(&initial_path))
         .with_context(|| "Saving generated image")?;
+    if location.config().make.optimize {
+        timings::time(Phase::Encode, || optimize::optimize_image(&initial_path))
+            .with_context(|| "Optimizing generated image")?;
+    }
+
+    // TODO(feat): Optionally typeset original dialogue (from a script file) into duplicate image.
+    // Blocked on `comic_format` exposing text layout; only `convert_image` is currently public.
+    if location.config().make.link_duplicate {
+        fs::hard_link(&initial_path, &duplicate_file_path)
+            .with_context(|| "Linking duplicate to generated image")?;
+    } else {
+        fs::copy(&initial_path, &duplicate_file_path)
+            .with_context(|| "Duplicating generated image")?;
+    }
+    for variant in location.layout().all_variants().into_iter().skip(1) {
+        fs::copy(&initial_path, output_dir.join(&variant.image))
+            .with_context(|| "Copying generated image for variant")?;
+    }
+    Ok(())
+}
+
+/// Variant images missing from existing post are copied from its initial image
+fn reuse_images(location: &Location, post_path: &Path, output_dir: &Path) -> Result<()> {
+    let layout = location.layout();
+    let initial_path = output_dir.join(&layout.initial);
+    fs::copy(post_path.join(&layout.initial), &initial_path)
+        .with_context(|| "Copying initial image")?;
+    // Duplicate may have been edited since it was generated
+    let is_duplicate_unchanged = file::compare_files(
+        post_path.join(&layout.initial),
+        post_path.join(&layout.duplicate),
+    )
+    .with_context(|| "Comparing initial and duplicate images")?;
+    if location.config().make.link_duplicate && is_duplicate_unchanged {
+        fs::hard_link(&initial_path, output_dir.join(&layout.duplicate))
+            .with_context(|| "Linking duplicate to initial image")?;
+    } else {
+        fs::copy(
+            post_path.join(&layout.duplicate),
+            output_dir.join(&layout.duplicate),
+        )
+        .with_context(|| "Copying duplicate image")?;
+    }
+    for variant in layout.all_variants().into_iter().skip(1) {
+        let old_path = post_path.join(&variant.image);
+        let source_path = if old_path.is_file() {
+            old_path
+        } else {
+            post_path.join(&layout.initial)
+        };
+        fs::copy(source_path, output_dir.join(&variant.image))
+            .with_context(|| "Copying image for variant")?;
+    }
+    Ok(())
+}
+
+/// Generated images are larger than their source comics (which are often optimized), so this
+/// overestimates generously
+fn estimate_make_size(location: &Location, dates: &[NaiveDate]) -> Result<u64> {
+    const SIZE_FACTOR: u64 = 4;
+    let images_per_post = 2 + location.layout().variants.len() as u64;
+    let mut total = 0;
+    for date in dates {
+        let source_size = fs::metadata(location.source_file(*date))
+            .with_context(|| "Reading size of source comic")?
+            .len();
+        total += source_size * SIZE_FACTOR * images_per_post;
+    }
+    Ok(total)
+}
 
-    fs::copy(&initial_path, &duplicate_file_path).with_context(|| "Duplicating generated image")?;
+/// Copies comics from a directory or zip archive into source directory, named by date.
+/// Skips files without a recognized date or image format, and dates already in source directory.
+pub fn import(location: &Location, path: &Path) -> Result<()> {
+    if !path.exists() {
+        bail!(Error::usage("Import directory or archive does not exist"));
+    }
+
+    let mut added = Vec::new();
+    let mut existing_count = 0;
+    let mut unrecognized = Vec::new();
+    import::read_files(path, |file_name, bytes| {
+        let Some(date) = import::parse_file_date(file_name) else {
+            unrecognized.push(file_name.to_string());
+            return Ok(());
+        };
+        let Ok(format) = image::guess_format(bytes) else {
+            unrecognized.push(file_name.to_string());
+            return Ok(());
+        };
+        let source_path = location.source_file(date);
+        if source_path.exists() {
+            existing_count += 1;
+            return Ok(());
+        }
+        if format == image::ImageFormat::Png {
+            fs::write(&source_path, bytes).with_context(|| "Writing source comic")?;
+        } else {
+            image::load_from_memory_with_format(bytes, format)
+                .with_context(|| format!("Decoding `{}`", file_name))?
+                .save_with_format(&source_path, image::ImageFormat::Png)
+                .with_context(|| "Saving source comic")?;
+        }
+        added.push(date);
+        Ok(())
+    })?;
+
+    added.sort();
+    for date in &added {
+        println!("Added {}", date);
+    }
+    for file_name in &unrecognized {
+        println!("Skipped `{}`: unrecognized date or image format", file_name);
+    }
+    println!(
+        "Imported {} comic(s), skipped {} already in source and {} unrecognized.",
+        added.len(),
+        existing_count,
+        unrecognized.len()
+    );
+    Ok(())
+}
+
+/// Moves every comic in source directory into a subdirectory for its year, eg. `source/2024/`
+pub fn migrate_source(location: &Location) -> Result<()> {
+    let mut moved_count = 0;
+    let entries =
+        file::sort_dir_entries(file::read_dir(location.source_dir())?.collect::<Result<_>>()?);
+    for entry in entries {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Ok(Some(date)) = file::get_date_from_path(&path) else {
+            continue;
+        };
+        let year_dir = location.source_year_dir(date.year());
+        fs::create_dir_all(&year_dir).with_context(|| "Creating year directory")?;
+        let new_path = year_dir.join(entry.file_name());
+        if new_path.exists() {
+            println!(
+                "Skipped {}: already exists in year directory",
+                entry.file_name().to_string_lossy()
+            );
+            continue;
+        }
+        file::rename_or_copy(&path, &new_path)
+            .with_context(|| "Moving comic into year directory")?;
+        moved_count += 1;
+    }
+    println!("Moved {} comic(s) into year directories.", moved_count);
+    Ok(())
+}
 
-    println!("Created {}", name);
+/// Makes posts for all comics in range, skipping dates which already have a post
+pub fn make_between(location: &Location, between: FullDateRange) -> Result<()> {
+    let mut dates = Vec::new();
+    let mut skipped_count = 0;
+    for date in names::get_source_dates(location, between)? {
+        if !names::find_posts_by_date(location, date)?.is_empty() {
+            skipped_count += 1;
+        } else {
+            dates.push(date);
+        }
+    }
+    file::require_free_space(
+        location.generated_dir(),
+        estimate_make_size(location, &dates)?,
+    )?;
 
+    let mut context = MakeContext::new(location)?;
+    let mut made_count = 0;
+    for date in dates {
+        let name = names::generate_name(date);
+        make_with(&mut context, date, &name, false, None)
+            .with_context(|| format!("Generating post for {}", date))?;
+        made_count += 1;
+    }
+    println!(
+        "Created {} post(s), skipped {} already existing.",
+        made_count, skipped_count
+    );
     Ok(())
 }
 
-pub fn transcribe(location: &Location, id: &str) -> Result<()> {
+/// Transcribes primary translation if `language` is `None`
+pub fn transcribe(
+    location: &Location,
+    id: &str,
+    draft: bool,
+    language: Option<&str>,
+) -> Result<()> {
+    let variant = location.layout().variant(language)?;
+
     let temp_dir = location.temp_dir();
     if !temp_dir.exists() {
         fs::create_dir_all(&temp_dir)
             .with_context(|| "Creating temp directory for transcript file")?;
     }
 
-    // "{temp_dir}/transcript.{id}"
-    let mut temp_file_path = temp_dir.join("transcript");
-    temp_file_path.set_extension(id);
+    if pipeline::Stage::of(location, id).is_some_and(|stage| stage.is_unfinished()) {
+        output::warning("Post is still in generated directory, and has not been finished");
+    }
+
+    let temp_file_path = match language {
+        Some(language) => location.temp_transcript_file(&format!("{}.{}", id, language)),
+        None => location.temp_transcript_file(id),
+    };
 
     let posts_dir = location.posts_dir().join(id);
+    let transcript_file_path = posts_dir.join(&variant.transcript);
+    let initial_file_path = posts_dir.join(&variant.image);
+    let duplicate_file_path = posts_dir.join(&location.layout().duplicate);
+
+    if draft {
+        if !cfg!(feature = "ocr") {
+            bail!(Error::usage(
+                "Drafting a transcript requires the `ocr` feature, to recognize English text"
+            ));
+        }
+        if transcript_file_path.exists() {
+            bail!(Error::usage(
+                "Post already has a transcript, so a draft cannot be inserted"
+            ));
+        }
+    }
+    hooks::run_pre(location, "transcribe", id, &posts_dir)?;
+
+    let date = names::read_date(location, id)
+        .with_context(|| "Reading date from existing post directory")?;
+    let original_file_path = location.source_file(date);
 
-    let transcript_file_path = posts_dir.join(post_file::TRANSCRIPT);
-    let initial_file_path = posts_dir.join(post_file::INITIAL);
-    let duplicate_file_path = posts_dir.join(post_file::DUPLICATE);
+    let resume = temp_file_path.exists()
+        && (!transcript_file_path.exists()
+            || file::is_newer_than(&temp_file_path, &transcript_file_path)
+                .with_context(|| "Comparing temporary file against transcript file")?)
+        && ui::ask_yes_no(
+            location,
+            PromptId::ResumeTranscript,
+            "Found an unsaved transcript from a previous session. Resume it?",
+        )?;
 
-    commands::kill_process_name(window_name::TRANSCRIBE)?;
+    let window = location.config().viewer.transcribe_window();
+    location.system().kill_process_name(&window)?;
 
-    commands::setup_image_viewer_window(
-        &[initial_file_path, duplicate_file_path],
-        window_name::TRANSCRIBE,
+    let _viewer_guard = location
+        .system()
+        .has_gui()
+        .then(|| cleanup::register(cleanup::Task::KillProcess(window.clone())));
+    location.system().setup_image_viewer_window(
+        &location.config().viewer.program,
+        &[
+            initial_file_path,
+            duplicate_file_path,
+            original_file_path.clone(),
+        ],
+        &window,
     )?;
 
-    let transcript_template = if transcript_file_path.exists() {
+    let has_existing_transcript = transcript_file_path.exists();
+    let transcript_template = if has_existing_transcript {
         println!("(transcript file already exists)");
-        let contents = fs::read_to_string(&transcript_file_path)
+        let contents = file::read_text(&transcript_file_path)
             .with_context(|| "Reading existing transcript file")?;
         Cow::from(contents)
     } else {
-        Cow::from(if is_id_sunday(id)? {
-            "---\n---\n---\n---\n---\n---"
-        } else {
-            "---\n---"
-        })
+        Cow::from(create_transcript_template(
+            location,
+            &original_file_path,
+            is_id_sunday(id)?,
+            draft,
+            language,
+        )?)
     };
 
-    fs::write(&temp_file_path, &*transcript_template)
-        .with_context(|| "Writing template transcript file")?;
+    if !resume {
+        fs::write(&temp_file_path, &*transcript_template)
+            .with_context(|| "Writing template transcript file")?;
+    }
+    let _temp_file_guard = cleanup::register(cleanup::Task::RemoveUnchangedFile {
+        path: temp_file_path.clone(),
+        contents: transcript_template.to_string(),
+    });
 
-    commands::open_editor(&temp_file_path)?;
+    let editing_started = Instant::now();
+    location
+        .system()
+        .open_editor(&location.config().editor.program, &temp_file_path)?;
+    let editing_time = editing_started.elapsed();
 
-    commands::kill_process_name(window_name::TRANSCRIBE)?;
+    location.system().kill_process_name(&window)?;
 
     if file::file_matches_string(&temp_file_path, &transcript_template)
         .with_context(|| "Comparing transcript file against previous version")?
@@ -131,34 +732,143 @@ pub fn transcribe(location: &Location, id: &str) -> Result<()> {
         return Ok(());
     }
 
-    confirm("Save transcript file?");
+    if has_existing_transcript {
+        let new_transcript = fs::read_to_string(&temp_file_path)
+            .with_context(|| "Reading edited transcript file")?;
+        print!(
+            "{}",
+            text_diff::unified_diff(
+                &transcript_template,
+                &new_transcript,
+                "transcript (saved)",
+                "transcript (edited)",
+                2,
+            )
+        );
+    }
+
+    if fs::read_to_string(&temp_file_path)
+        .with_context(|| "Reading edited transcript file")?
+        .contains(transcript::MACHINE_TRANSLATION_MARKER)
+    {
+        output::warning("Transcript still contains machine translation.");
+    }
+
+    if !ui::confirm(location, PromptId::SaveTranscript, "Save transcript file?")? {
+        println!("Transcript not saved. Resume it with `transcribe`.");
+        return Ok(());
+    }
 
-    fs::rename(temp_file_path, &transcript_file_path)
+    let contents =
+        fs::read_to_string(&temp_file_path).with_context(|| "Reading edited transcript file")?;
+    fs::write(
+        &temp_file_path,
+        file::encode_text(&transcript::strip_notes(&contents))?,
+    )
+    .with_context(|| "Removing notes from transcript file")?;
+    history::record_previous(&posts_dir, &variant.transcript)
+        .with_context(|| "Recording previous transcript in history")?;
+    file::rename_or_copy(temp_file_path, &transcript_file_path)
         .with_context(|| "Renaming temporary file as transcript file")?;
+    history::record(&posts_dir, &variant.transcript)
+        .with_context(|| "Recording transcript in history")?;
+    props::add_effort(&posts_dir, editing_time).with_context(|| "Recording editing time")?;
+    if let Some(name) = &location.config().identity.name {
+        props::add_author(&posts_dir, Contribution::Transcript, name)
+            .with_context(|| "Recording author of transcript")?;
+    }
+    let approval = if draft {
+        Approval::Draft
+    } else {
+        Approval::NeedsReview
+    };
+    props::set_approval(&posts_dir, approval, None).with_context(|| "Recording approval")?;
+
+    output::success("Saved transcript file.");
+
+    checksum::update(&posts_dir).with_context(|| "Updating checksums")?;
 
-    println!("Saved transcript file.");
+    vcs::commit_post(location, "transcribe", id, &posts_dir)
+        .with_context(|| "Committing transcript")?;
+    hooks::run_post(location, "transcribe", id, &posts_dir);
 
     Ok(())
 }
 
+/// Removes files in temp directory which were last modified longer than `max_age` ago
+pub fn clean_temp(location: &Location, max_age: Duration) -> Result<()> {
+    let temp_dir = location.temp_dir();
+    if !temp_dir.exists() {
+        println!("No temp directory to clean.");
+        return Ok(());
+    }
+
+    let mut removed_count = 0;
+    for entry in file::read_dir(&temp_dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Reading modification time of {:?}", path))?;
+        // Treat modification times in the future as brand new
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age < max_age {
+            continue;
+        }
+        fs::remove_file(&path).with_context(|| format!("Removing temp file {:?}", path))?;
+        println!("Removed {}", path.to_string_lossy());
+        removed_count += 1;
+    }
+
+    println!("Removed {} stale temp file(s).", removed_count);
+    Ok(())
+}
+
+/// Can be resumed after the post was moved, eg. if committing failed
 pub fn revise(location: &Location, id: &str) -> Result<()> {
     let post_path = location.posts_dir().join(id);
     let generated_path = location.generated_dir().join(id);
+    let old_post_path = location.old_dir().join(id);
+    if !post_path.exists() && old_post_path.exists() && generated_path.exists() {
+        println!("(post was already moved to old directory)");
+        return complete_revise(location, id, &old_post_path, &generated_path);
+    }
+    hooks::run_pre(location, "revise", id, &post_path)?;
 
     let copy_files = [
         (post_file::TITLE, true),
         (post_file::TRANSCRIPT, false),
         (post_file::PROPS, false),
         (post_file::SPECIAL, false),
-        (post_file::SVG, false),
-        // Date and PNG images already created
+        (location.layout().svg.as_str(), false),
+        (post_file::NOTES, false),
+        // Date, watermark, and PNG images already created
     ];
+    let variant_files: Vec<String> = location
+        .layout()
+        .all_variants()
+        .into_iter()
+        .skip(1)
+        .flat_map(|variant| [variant.svg, variant.transcript])
+        .collect();
+    let copy_files = copy_files.into_iter().chain(
+        variant_files
+            .iter()
+            .map(|file_name| (file_name.as_str(), false)),
+    );
     for (file_name, is_required) in copy_files {
         let old_path = post_path.join(file_name);
         let new_path = generated_path.join(file_name);
         if !old_path.exists() {
             if is_required {
-                bail!("Post is missing required `{}` file", file_name);
+                bail!(Error::validation(format!(
+                    "Post is missing required `{}` file",
+                    file_name
+                )));
             }
         } else {
             fs::copy(old_path, new_path)
@@ -166,80 +876,2329 @@ pub fn revise(location: &Location, id: &str) -> Result<()> {
         }
     }
 
-    confirm("Move old post to old directory?");
+    history::copy_history(&post_path, &generated_path).with_context(|| "Copying post history")?;
+    checksum::update(&generated_path).with_context(|| "Updating checksums")?;
+
+    if !ui::confirm(
+        location,
+        PromptId::MoveOldPost,
+        "Move old post to old directory?",
+    )? {
+        bail!(Error::usage(
+            "Old post was not moved. Continue with `resume` once ready."
+        ));
+    }
 
-    let old_post_path = location.old_dir().join(id);
     if old_post_path.exists() {
         // TODO(feat!): Handle post already revised
         bail!("unimplemented: post already revised");
     }
-    fs::rename(&post_path, &old_post_path).with_context(|| "Moving post to `old` directory")?;
-    println!("Moved {} to old directory", id);
+    file::rename_or_copy(&post_path, &old_post_path)
+        .with_context(|| "Moving post to `old` directory")?;
+    output::success(format!("Moved {} to old directory", output::id(id)));
 
-    println!("(waiting until done...)");
-    file::wait_for_file(&post_path);
+    complete_revise(location, id, &old_post_path, &generated_path)
+}
 
+fn complete_revise(
+    location: &Location,
+    id: &str,
+    old_post_path: &Path,
+    generated_path: &Path,
+) -> Result<()> {
+    vcs::commit_post(location, "revise", id, old_post_path)
+        .with_context(|| "Committing revised post")?;
+    hooks::run_post(location, "revise", id, generated_path);
     Ok(())
 }
 
-pub fn upload(location: &Location, id: &str) -> Result<()> {
+/// Waits until generated post has been exported from its SVG file, or was finished (eg. by another
+/// instance), then asks to continue. Use `finish_revised_post` afterwards.
+pub fn wait_for_revised_post(location: &Location, id: &str) -> Result<()> {
+    let layout = location.layout();
     let post_path = location.posts_dir().join(id);
+    let generated_path = location.generated_dir().join(id);
+    println!(
+        "Edit `{}` of generated post {}, and export it to `{}`.",
+        layout.svg, id, layout.initial
+    );
+    println!("Post is finished once every image is exported, or if it is finished elsewhere.");
+    // Editors export over the image in place, which would also replace a duplicate linked to it
+    dedupe::unlink(layout, &generated_path).with_context(|| "Separating linked post images")?;
+
+    let timeout = location
+        .config()
+        .revise
+        .wait_timeout
+        .map(Duration::from_secs);
+    let is_done = || post_path.exists() || is_revised_post_exported(layout, &generated_path);
+    let outcome = file::wait_until(
+        &[location.posts_dir().as_path(), generated_path.as_path()],
+        is_done,
+        timeout,
+    )
+    .with_context(|| "Waiting for revised post")?;
+    match outcome {
+        file::WaitOutcome::Done => (),
+        file::WaitOutcome::TimedOut => bail!("Timed out waiting for revised post"),
+        file::WaitOutcome::Aborted => bail!(Error::usage("Aborted waiting for revised post")),
+    }
+
+    if !ui::confirm(
+        location,
+        PromptId::FinishRevisedPost,
+        "Finish and transcribe now?",
+    )? {
+        bail!(Error::usage(
+            "Revised post was not finished. Continue with `resume` once ready."
+        ));
+    }
+    Ok(())
+}
+
+/// Moves revised post back into posts directory, unless it was already finished elsewhere
+pub fn finish_revised_post(location: &Location, id: &str) -> Result<()> {
+    if location.posts_dir().join(id).exists() {
+        return Ok(());
+    }
+    finish(location, id, Some(id.to_string()))
+}
+
+/// Every image with an SVG file was exported after the SVG was last saved
+fn is_revised_post_exported(layout: &PostLayout, generated_path: &Path) -> bool {
+    let mut has_svg = false;
+    for variant in layout.all_variants() {
+        let svg_path = generated_path.join(&variant.svg);
+        if !svg_path.exists() {
+            continue;
+        }
+        has_svg = true;
+        let image_path = generated_path.join(&variant.image);
+        if !file::is_newer_than(&image_path, &svg_path).unwrap_or(false) {
+            return false;
+        }
+    }
+    has_svg
+}
+
+/// Moves a generated post into posts directory, once all required files exist.
+/// Non-numeric ids are given the next sequential post id, unless `new_id` is given.
+pub fn finish(location: &Location, id: &str, new_id: Option<String>) -> Result<()> {
+    let generated_path = location.generated_dir().join(id);
+    if !generated_path.is_dir() {
+        bail!(Error::missing_post("No generated post exists with that id"));
+    }
+    if let Some(new_id) = &new_id {
+        names::check_new_id(new_id)?;
+    }
+    pipeline::require_transition(location, id, pipeline::Stage::Finished)?;
+    hooks::run_pre(location, "finish", id, &generated_path)?;
+
+    let layout = location.layout();
+    let images = layout
+        .all_variants()
+        .into_iter()
+        .map(|variant| variant.image)
+        .chain([layout.duplicate.clone()]);
+    for file_name in images {
+        if !generated_path.join(&file_name).is_file() {
+            bail!(Error::validation(format!(
+                "Post is missing required `{}` file",
+                file_name
+            )));
+        }
+    }
+
+    let date_file_path = generated_path.join(post_file::DATE);
+    if !date_file_path.is_file() {
+        println!("Post is missing `{}` file.", post_file::DATE);
+        let date = loop {
+            let answer = location.prompter().ask("Date (YYYY-MM-DD):");
+            // Also at end of input, which would otherwise ask forever
+            if answer.is_empty() {
+                bail!(Error::usage("No date given for post"));
+            }
+            match NaiveDate::parse_from_str(&answer, "%Y-%m-%d") {
+                Ok(date) => break date,
+                Err(_) => println!("Invalid date."),
+            }
+        };
+        fs::write(&date_file_path, date.to_string()).with_context(|| "Writing to date file")?;
+    }
 
-    commands::toggle_upload_destination()?;
+    let title_file_path = generated_path.join(post_file::TITLE);
+    let has_title = title_file_path.is_file()
+        && !file::read_text(&title_file_path)
+            .with_context(|| "Reading title file")?
+            .trim()
+            .is_empty();
+    if !has_title {
+        println!("Post is missing a title.");
+        let title = location.prompter().ask("Title:");
+        if title.is_empty() {
+            bail!(Error::usage("No title given for post"));
+        }
+        fs::write(&title_file_path, file::encode_text(&title)?)
+            .with_context(|| "Writing to title file")?;
+        history::record(&generated_path, post_file::TITLE)
+            .with_context(|| "Recording title in history")?;
+    }
+
+    let new_id = match new_id {
+        Some(new_id) => new_id,
+        None if id.parse::<u32>().is_ok() => id.to_string(),
+        None => names::get_next_post_id(location).with_context(|| "Finding next post id")?,
+    };
+    let post_path = location.posts_dir().join(&new_id);
+    if post_path.exists() {
+        bail!("A completed post already exists with id `{}`", new_id);
+    }
 
-    if let Err(error) = upload_files(post_path) {
-        // Close destination if upload failed
-        commands::toggle_upload_destination()?;
-        return Err(error);
+    let has_svg = layout
+        .all_variants()
+        .iter()
+        .any(|variant| generated_path.join(&variant.svg).exists());
+    if dedupe::is_linked(layout, &generated_path)? {
+        // Exported over initial image in place, so duplicate was overwritten too
+        if has_svg {
+            bail!(Error::validation(format!(
+                "Duplicate image `{}` is linked to `{}`, so it was replaced when the SVG was \
+                exported. Restore the duplicate (eg. with `make` again) before finishing.",
+                layout.duplicate, layout.initial
+            )));
+        }
+        dedupe::unlink(layout, &generated_path).with_context(|| "Separating linked post images")?;
     }
+    if let Some(name) = location.config().identity.name.as_ref().filter(|_| has_svg) {
+        props::add_author(&generated_path, Contribution::Svg, name)
+            .with_context(|| "Recording author of SVG")?;
+    }
+
+    // Atomic, as long as both directories are on the same filesystem
+    file::rename_or_copy(&generated_path, &post_path)
+        .with_context(|| "Moving post to posts directory")?;
+    output::success(format!(
+        "Finished {} as {}",
+        output::id(id),
+        output::id(&new_id)
+    ));
+
+    checksum::update(&post_path).with_context(|| "Updating checksums")?;
+
+    vcs::commit_post(location, "finish", &new_id, &post_path)
+        .with_context(|| "Committing finished post")?;
+    hooks::run_post(location, "finish", &new_id, &post_path);
 
     Ok(())
 }
 
-fn upload_files(dir: impl AsRef<Path>) -> Result<()> {
-    commands::upload_file(dir.as_ref().join("english.png"))?;
-    commands::sleep(100);
-    commands::upload_file(dir.as_ref().join("esperanto.png"))?;
-    commands::sleep(100);
-    commands::upload_file(dir.as_ref().join("transcript"))?;
+/// Compares image of old post against its regenerated version
+pub fn diff(location: &Location, id: &str) -> Result<()> {
+    let initial = &location.layout().initial;
+    let old_path = location.old_dir().join(id).join(initial);
+    let new_path = location.generated_dir().join(id).join(initial);
+    if !old_path.exists() {
+        bail!(Error::missing_post("No old post image exists with that id"));
+    }
+    if !new_path.exists() {
+        bail!(Error::missing_post(
+            "No generated post image exists with that id"
+        ));
+    }
+
+    let old_image = image::open(&old_path).with_context(|| "Opening old post image")?;
+    let new_image = image::open(&new_path).with_context(|| "Opening generated post image")?;
+    let diff = image_diff::diff_images(&old_image, &new_image);
+
+    let temp_dir = location.temp_dir();
+    fs::create_dir_all(&temp_dir).with_context(|| "Creating temp directory for diff image")?;
+    let mut diff_path = temp_dir.join("diff");
+    diff_path.set_extension(format!("{}.{}", id, SOURCE_FORMAT));
+    diff.composite
+        .save(&diff_path)
+        .with_context(|| "Saving diff image")?;
+
+    println!("{:.2}% of pixels changed", diff.changed_ratio * 100.0);
+    if old_image.dimensions() != new_image.dimensions() {
+        println!(
+            "Image size changed from {:?} to {:?}",
+            old_image.dimensions(),
+            new_image.dimensions()
+        );
+    }
+
+    let window = location.config().viewer.show_window();
+    location.system().kill_process_name(&window)?;
+    location.system().spawn_image_viewer(
+        &location.config().viewer.program,
+        &[diff_path],
+        &window,
+        true,
+        None,
+    )?;
+
     Ok(())
 }
 
-/// Skips entries with missing or malformed date file
-fn exists_post_with_date(dir: impl AsRef<Path>, date: NaiveDate) -> Result<bool> {
-    let entries = file::read_dir(&dir)?;
+/// Refuses posts which are referred to by id outside of their directory (scheduled, uploaded,
+/// claimed, or with a submitted transcript), since those records would be left behind.
+/// Directories are renamed back if any fails.
+pub fn rename(location: &Location, old_id: &str, new_id: &str) -> Result<()> {
+    names::check_new_id(new_id)?;
 
-    for entry in entries {
-        let entry = entry?;
+    let dirs = [
+        location.generated_dir(),
+        location.posts_dir(),
+        location.old_dir(),
+    ];
 
-        let date_file_path = entry.path().join(post_file::DATE);
-        if !date_file_path.exists() {
-            continue;
+    for dir in &dirs {
+        if dir.join(new_id).exists() {
+            bail!("A post already exists with the new id");
         }
+    }
+    let old_paths: Vec<&PathBuf> = dirs
+        .iter()
+        .filter(|dir| dir.join(old_id).exists())
+        .collect();
+    if old_paths.is_empty() {
+        bail!(Error::missing_post("No post exists with that id"));
+    }
+    if let Some(reason) = find_id_reference(location, old_id)? {
+        bail!(Error::usage(format!(
+            "Post cannot be renamed, since it is {}",
+            reason
+        )));
+    }
 
-        let date_file = fs::read_to_string(date_file_path).with_context(|| "Reading date file")?;
-        let existing_date = NaiveDate::parse_from_str(date_file.trim(), "%Y-%m-%d")
-            .with_context(|| "Parsing date in file")?;
-        if existing_date == date {
-            return Ok(true);
+    let mut renamed = Vec::new();
+    for dir in old_paths {
+        let old_path = dir.join(old_id);
+        let new_path = dir.join(new_id);
+        if let Err(error) = fs::rename(&old_path, &new_path) {
+            for (old_path, new_path) in renamed.iter().rev() {
+                let _ = fs::rename(new_path, old_path);
+            }
+            return Err(error).with_context(|| format!("Renaming post directory {:?}", old_path));
         }
+        println!("Renamed {}", old_path.to_string_lossy());
+        renamed.push((old_path, new_path));
+    }
+
+    let old_temp_path = location.temp_transcript_file(old_id);
+    if old_temp_path.exists() {
+        fs::rename(&old_temp_path, location.temp_transcript_file(new_id))
+            .with_context(|| "Renaming temporary transcript file")?;
+        println!("Renamed {}", old_temp_path.to_string_lossy());
+    }
+
+    Ok(())
+}
+
+/// Record outside of post directories which refers to post by its id
+fn find_id_reference(location: &Location, id: &str) -> Result<Option<&'static str>> {
+    if Schedule::load(location)?.get(id).is_some() {
+        return Ok(Some("scheduled"));
+    }
+    let is_uploaded = location
+        .posts_dir()
+        .join(id)
+        .join(post_file::UPLOADED)
+        .exists()
+        || uploads::load(location)?
+            .iter()
+            .any(|record| record.id == id);
+    if is_uploaded {
+        return Ok(Some("uploaded"));
+    }
+    if submissions::find_claim(location, id)?.is_some() {
+        return Ok(Some("claimed for transcribing"));
+    }
+    if location.submission_file(id).exists() {
+        return Ok(Some("waiting for its submitted transcript to be reviewed"));
+    }
+    Ok(None)
+}
+
+/// Opens notes file of a post in editor. Removes the file if left empty.
+pub fn note(location: &Location, id: &str) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let notes_path = post_path.join(post_file::NOTES);
+
+    location
+        .system()
+        .open_editor(&location.config().editor.program, &notes_path)?;
+
+    if notes_path.exists()
+        && fs::read_to_string(&notes_path)
+            .with_context(|| "Reading notes file")?
+            .trim()
+            .is_empty()
+    {
+        fs::remove_file(&notes_path).with_context(|| "Removing empty notes file")?;
+    }
+    checksum::update(&post_path).with_context(|| "Updating checksums")?;
+    Ok(())
+}
+
+/// Creates special file if it doesn't exist, then opens it in editor.
+/// Type of existing file is replaced with `kind`, if given.
+pub fn special(location: &Location, id: &str, kind: Option<SpecialKind>) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let mut special = special::read(&post_path)?.unwrap_or_default();
+    if let Some(kind) = kind {
+        special.kind = kind;
     }
+    special::write(&post_path, &special)?;
+
+    location.system().open_editor(
+        &location.config().editor.program,
+        &post_path.join(post_file::SPECIAL),
+    )?;
+
+    // Invalid file is kept, so it can be fixed
+    special::read(&post_path)
+        .with_context(|| Error::validation("Edited special file is invalid"))?;
+    checksum::update(&post_path).with_context(|| "Updating checksums")?;
+    Ok(())
+}
+
+/// Opens post directory in file manager, or a single file in a program depending on its type
+pub fn open(location: &Location, id: &str, file: Option<&str>) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let config = location.config();
+
+    let Some(file) = file else {
+        dedupe::unlink(location.layout(), &post_path)
+            .with_context(|| "Separating linked post images")?;
+        return location
+            .system()
+            .spawn_detached(&config.file_manager.program, &post_path);
+    };
 
-    Ok(false)
+    let path = post_path.join(file);
+    if !path.is_file() {
+        bail!(Error::missing_post(format!(
+            "Post has no file named `{}`",
+            file
+        )));
+    }
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => {
+            dedupe::unlink(location.layout(), &post_path)
+                .with_context(|| "Separating linked post images")?;
+            location
+                .system()
+                .spawn_detached(&config.svg_editor.program, &path)
+        }
+        Some("png" | "jpg" | "jpeg" | "gif" | "webp") => {
+            let window = config.viewer.show_window();
+            location.system().kill_process_name(&window)?;
+            location.system().spawn_image_viewer(
+                &config.viewer.program,
+                &[path.clone()],
+                &window,
+                false,
+                None,
+            )
+        }
+        _ => location.system().open_editor(&config.editor.program, &path),
+    }
 }
 
-fn get_random_watermark(location: &Location) -> Result<String> {
-    let contents = fs::read_to_string(location.watermarks_file())
-        .with_context(|| "Reading watermarks file")?;
-    let watermarks: Vec<&str> = contents.lines().collect();
-    let index = random::with_rng(|rng| rng.gen_range(0..watermarks.len()));
-    Ok(watermarks[index].to_string())
+/// Composes a grid of source comics (or completed post images) within range, and displays it
+pub fn sheet(
+    location: &Location,
+    range: Option<DateRange>,
+    month: Option<YearMonth>,
+    posts: bool,
+    columns: u32,
+    limit: usize,
+) -> Result<()> {
+    let in_range = |date: NaiveDate| {
+        range.is_none_or(|range| range.contains(date))
+            && month.is_none_or(|month| month.contains(date))
+    };
+
+    let mut paths: Vec<PathBuf> = if posts {
+        names::get_stage_post_dates(location, Stage::Completed)
+            .with_context(|| "Reading dates of posts")?
+            .into_iter()
+            .filter(|(_, date)| in_range(*date))
+            .map(|(id, _)| {
+                location
+                    .posts_dir()
+                    .join(id)
+                    .join(&location.layout().initial)
+            })
+            .filter(|path| path.exists())
+            .collect()
+    } else {
+        names::get_source_dates(location, FullDateRange::all())
+            .with_context(|| "Reading source dates")?
+            .into_iter()
+            .filter(|date| in_range(*date))
+            .map(|date| location.source_file(date))
+            .collect()
+    };
+    if paths.is_empty() {
+        bail!("No comics found within range");
+    }
+    if paths.len() > limit {
+        println!("Showing only the first {} of {} comics", limit, paths.len());
+        paths.truncate(limit);
+    }
+
+    let image = sheet::compose_sheet(&paths, columns)?;
+
+    let temp_dir = location.temp_dir();
+    fs::create_dir_all(&temp_dir).with_context(|| "Creating temp directory for sheet image")?;
+    let mut sheet_path = temp_dir.join("sheet");
+    sheet_path.set_extension(SOURCE_FORMAT);
+    image
+        .save(&sheet_path)
+        .with_context(|| "Saving sheet image")?;
+
+    let window = location.config().viewer.show_window();
+    location.system().kill_process_name(&window)?;
+    location.system().spawn_image_viewer(
+        &location.config().viewer.program,
+        &[sheet_path],
+        &window,
+        true,
+        None,
+    )?;
+
+    Ok(())
+}
+
+/// Writes transcript with title and date to stdout, or a file
+pub fn transcript_export(
+    location: &Location,
+    id: &str,
+    format: TranscriptFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let transcript_path = post_path.join(post_file::TRANSCRIPT);
+    if !transcript_path.exists() {
+        bail!(Error::validation("Post has no transcript"));
+    }
+    let transcript = Transcript::parse(
+        &file::read_text(transcript_path).with_context(|| "Reading transcript file")?,
+    );
+    let title =
+        file::read_text(post_path.join(post_file::TITLE)).with_context(|| "Reading title file")?;
+    let date = file::read_last_line_as_date(
+        fs::File::open(post_path.join(post_file::DATE)).with_context(|| "Opening date file")?,
+    )
+    .with_context(|| "Reading date file")?;
+
+    let rendered = format.render(title.trim(), date, &transcript);
+    match output {
+        Some(output) => {
+            fs::write(output, rendered).with_context(|| "Writing exported transcript")?
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+/// Exports SVG file of a completed post as its translated image, at the size of its duplicate
+/// image, and checks the result has the same dimensions
+pub fn render(location: &Location, id: &str, language: Option<&str>) -> Result<()> {
+    let variant = location.layout().variant(language)?;
+    let post_path = location.posts_dir().join(id);
+    let svg_path = post_path.join(&variant.svg);
+    if !svg_path.is_file() {
+        bail!(Error::missing_post(format!(
+            "Post has no `{}` file",
+            variant.svg
+        )));
+    }
+    let (width, height) = image::image_dimensions(post_path.join(&location.layout().duplicate))
+        .with_context(|| "Reading dimensions of duplicate image")?;
+
+    // Rendered to temporary file first, so a failed render doesn't replace the existing image
+    let temp_dir = location.temp_dir();
+    fs::create_dir_all(&temp_dir).with_context(|| "Creating temp directory for render")?;
+    let temp_path = temp_dir.join(format!("render.{}.{}", id, SOURCE_FORMAT));
+    commands::render_svg(
+        &location.config().render.program,
+        &svg_path,
+        &temp_path,
+        width,
+    )
+    .with_context(|| "Rendering svg file")?;
+    let rendered_dimensions =
+        image::image_dimensions(&temp_path).with_context(|| "Reading dimensions of render")?;
+    if rendered_dimensions != (width, height) {
+        fs::remove_file(&temp_path).with_context(|| "Removing invalid render")?;
+        bail!(Error::validation(format!(
+            "Rendered image is {}x{}, but duplicate image is {}x{}",
+            rendered_dimensions.0, rendered_dimensions.1, width, height
+        )));
+    }
+    // Image is replaced in place, which would also replace a duplicate linked to it
+    dedupe::unlink(location.layout(), &post_path)
+        .with_context(|| "Separating linked post images")?;
+    // Copied, since temp directory may be on another filesystem
+    fs::copy(&temp_path, post_path.join(&variant.image))
+        .with_context(|| "Replacing translated image with render")?;
+    fs::remove_file(&temp_path).with_context(|| "Removing temporary render")?;
+
+    checksum::update(&post_path).with_context(|| "Updating checksums")?;
+    output::success(format!("Rendered {}/{}", output::id(id), variant.image));
+    Ok(())
+}
+
+/// Renders and validates each post whose SVG file is saved, until interrupted
+pub fn watch(location: &Location) -> Result<()> {
+    println!("Watching {}", location.posts_dir().display());
+    watch::watch_svg_files(location, |id, variant| {
+        let time = Local::now().format("%H:%M:%S");
+        println!("[{}] {}/{} changed", time, id, variant.svg);
+        // Editors may still be writing file
+        commands::sleep(500);
+        let result = lock::acquire(location, true)
+            .and_then(|_lock| render(location, id, variant.language.as_deref()));
+        if let Err(error) = result {
+            println!("[{}] {} {:#}", time, output::error_label(), error);
+        }
+    })
+}
+
+/// Renders transcript text onto blank panels with the configured font, to check it fits
+pub fn preview(location: &Location, id: &str) -> Result<()> {
+    let config = &location.config().preview;
+    let Some(font_path) = &config.font else {
+        bail!(Error::usage(
+            "No font configured (set `preview.font` in config file)"
+        ));
+    };
+    let font =
+        ab_glyph::FontVec::try_from_vec(fs::read(font_path).with_context(|| "Reading font file")?)
+            .with_context(|| "Parsing font file")?;
+
+    let (_, post_path) = names::find_post(location, id)?;
+    let transcript_path = post_path.join(post_file::TRANSCRIPT);
+    let contents = if transcript_path.exists() {
+        file::read_text(&transcript_path).with_context(|| "Reading transcript file")?
+    } else {
+        // Unsaved transcript from `transcribe`
+        let temp_path = location.temp_transcript_file(id);
+        if !temp_path.exists() {
+            bail!(Error::validation("Post has no transcript"));
+        }
+        fs::read_to_string(&temp_path).with_context(|| "Reading temporary transcript file")?
+    };
+    let transcript = Transcript::parse(&contents);
+
+    let preview = preview::render_preview(&transcript, &font, config.font_size);
+    for panel in &preview.overflowing_panels {
+        println!("Panel {} does not fit.", panel + 1);
+    }
+
+    let temp_dir = location.temp_dir();
+    fs::create_dir_all(&temp_dir).with_context(|| "Creating temp directory for preview image")?;
+    let mut preview_path = temp_dir.join("preview");
+    preview_path.set_extension(format!("{}.{}", id, SOURCE_FORMAT));
+    preview
+        .image
+        .save(&preview_path)
+        .with_context(|| "Saving preview image")?;
+
+    let window = location.config().viewer.show_window();
+    location.system().kill_process_name(&window)?;
+    location.system().spawn_image_viewer(
+        &location.config().viewer.program,
+        &[preview_path],
+        &window,
+        false,
+        None,
+    )?;
+
+    Ok(())
+}
+
+/// Opens images of post, and prints its details, to check it before uploading.
+/// Transcript submitted with `serve` is then accepted or rejected with `accept_submission`, and
+/// completed post is approved or rejected with `approve`. Either is asked if `None`.
+pub fn review(
+    location: &Location,
+    id: &str,
+    accept_submission: Option<bool>,
+    approve: Option<bool>,
+    comment: Option<&str>,
+) -> Result<()> {
+    const TRANSCRIPT_LINES: usize = 6;
+
+    let (directory, post_path) = names::find_post(location, id)?;
+    let stage = pipeline::Stage::of(location, id).expect("post should exist");
+
+    let layout = location.layout();
+    let image_paths: Vec<PathBuf> = layout
+        .all_variants()
+        .iter()
+        .map(|variant| variant.image.as_str())
+        .chain([layout.duplicate.as_str()])
+        .map(|file_name| post_path.join(file_name))
+        .filter(|path| path.is_file())
+        .collect();
+    if !image_paths.is_empty() {
+        let window = location.config().viewer.show_window();
+        location.system().kill_process_name(&window)?;
+        location.system().spawn_image_viewer(
+            &location.config().viewer.program,
+            &image_paths,
+            &window,
+            false,
+            None,
+        )?;
+    }
+
+    let or_missing = |value: String| {
+        if value.is_empty() {
+            "(none)".to_string()
+        } else {
+            value
+        }
+    };
+    println!("Id:      {}", output::id(id));
+    println!("Stage:   {}", stage);
+    println!(
+        "Date:    {}",
+        or_missing(file::read_first_line(&post_path.join(post_file::DATE))?)
+    );
+    println!(
+        "Title:   {}",
+        or_missing(file::read_first_line(&post_path.join(post_file::TITLE))?)
+    );
+    if let Some(special) = special::read(&post_path).with_context(|| "Reading special file")? {
+        println!("Special: {}", special.kind);
+    }
+    let props_path = post_path.join(post_file::PROPS);
+    if props_path.exists() {
+        let props = fs::read_to_string(&props_path).with_context(|| "Reading props file")?;
+        for line in props.lines().filter(|line| !line.trim().is_empty()) {
+            println!("Prop:    {}", line.trim());
+        }
+    }
+
+    let transcript_path = post_path.join(post_file::TRANSCRIPT);
+    if transcript_path.exists() {
+        let transcript =
+            file::read_text(&transcript_path).with_context(|| "Reading transcript file")?;
+        println!();
+        let mut lines = transcript.lines();
+        for line in lines.by_ref().take(TRANSCRIPT_LINES) {
+            println!("\t{}", line);
+        }
+        let remaining = lines.count();
+        if remaining > 0 {
+            println!("\t(... {} more line(s))", remaining);
+        }
+    } else {
+        println!("(no transcript)");
+    }
+
+    let pending_path = post_path.join(post_file::PENDING_TRANSCRIPT);
+    if pending_path.exists() {
+        review_submitted_transcript(location, id, &post_path, accept_submission)?;
+    } else if accept_submission.is_some() {
+        bail!(Error::usage("Post has no submitted transcript to review"));
+    }
+
+    if directory != Stage::Completed {
+        if approve.is_some() {
+            bail!(Error::usage("Only completed posts can be approved"));
+        }
+        return Ok(());
+    }
+    // Approves transcript which was saved, not the one waiting for review
+    if approve.is_none() && pending_path.exists() {
+        return Ok(());
+    }
+    review_approval(location, id, &post_path, approve, comment)
+}
+
+/// `None` asks, if post is waiting for approval. Rejected post becomes a draft again.
+fn review_approval(
+    location: &Location,
+    id: &str,
+    post_path: &Path,
+    approve: Option<bool>,
+    comment: Option<&str>,
+) -> Result<()> {
+    let (approve, comment) = match approve {
+        Some(approve) => (approve, comment.map(String::from)),
+        None => {
+            match props::read_approval(post_path)? {
+                Some(Approval::Draft | Approval::NeedsReview) => (),
+                Some(Approval::Approved) | None => return Ok(()),
+            }
+            println!();
+            let approve = loop {
+                let answer = location
+                    .prompter()
+                    .ask("Approve post? [y]es, [r]eject, or [S]kip:");
+                match answer.to_lowercase().as_str() {
+                    "y" | "yes" => break true,
+                    "r" | "reject" => break false,
+                    "" | "s" | "skip" => return Ok(()),
+                    _ => println!("Invalid answer."),
+                }
+            };
+            let comment = match comment {
+                Some(comment) => comment.to_string(),
+                None => location.prompter().ask("Comment (optional):"),
+            };
+            (approve, Some(comment))
+        }
+    };
+
+    let approval = if approve {
+        Approval::Approved
+    } else {
+        Approval::Draft
+    };
+    props::set_approval(post_path, approval, comment.as_deref())
+        .with_context(|| "Recording approval")?;
+    if approve {
+        output::success("Approved post.");
+    } else {
+        println!("Rejected post. It is a draft again.");
+    }
+
+    checksum::update(post_path).with_context(|| "Updating checksums")?;
+    vcs::commit_post(location, "review", id, post_path).with_context(|| "Committing approval")?;
+    Ok(())
+}
+
+/// Prints diff of submitted transcript against saved transcript, then saves it in place of saved
+/// transcript, or removes it. `None` asks whether to accept, and keeps it if declined.
+fn review_submitted_transcript(
+    location: &Location,
+    id: &str,
+    post_path: &Path,
+    accept: Option<bool>,
+) -> Result<()> {
+    let variant = location.layout().variant(None)?;
+    let pending_path = post_path.join(post_file::PENDING_TRANSCRIPT);
+    let transcript_path = post_path.join(&variant.transcript);
+
+    let submitted =
+        fs::read_to_string(&pending_path).with_context(|| "Reading submitted transcript file")?;
+    let saved = if transcript_path.exists() {
+        file::read_text(&transcript_path).with_context(|| "Reading transcript file")?
+    } else {
+        String::new()
+    };
+    println!();
+    println!("Submitted transcript:");
+    print!(
+        "{}",
+        text_diff::unified_diff(
+            &saved,
+            &submitted,
+            "transcript (saved)",
+            "transcript (submitted)",
+            2,
+        )
+    );
+
+    let claim = submissions::find_claim(location, id)?;
+    match accept {
+        Some(false) => {
+            fs::remove_file(&pending_path).with_context(|| "Removing submitted transcript file")?;
+            submissions::remove_claim(location, id)?;
+            println!("Rejected submitted transcript.");
+            return Ok(());
+        }
+        Some(true) => (),
+        None => {
+            if !ui::ask_yes_no(
+                location,
+                PromptId::AcceptTranscript,
+                "Accept submitted transcript?",
+            )? {
+                println!(
+                    "Submitted transcript not accepted. Discard it with `review --reject-submission`."
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    fs::write(
+        &pending_path,
+        file::encode_text(&transcript::strip_notes(&submitted))?,
+    )
+    .with_context(|| "Removing notes from submitted transcript file")?;
+    history::record_previous(post_path, &variant.transcript)
+        .with_context(|| "Recording previous transcript in history")?;
+    file::rename_or_copy(&pending_path, &transcript_path)
+        .with_context(|| "Renaming submitted transcript file as transcript file")?;
+    history::record(post_path, &variant.transcript)
+        .with_context(|| "Recording transcript in history")?;
+    if let Some(claim) = claim {
+        props::add_author(post_path, Contribution::Transcript, &claim.name)
+            .with_context(|| "Recording author of transcript")?;
+        submissions::remove_claim(location, id)?;
+    }
+    props::set_approval(post_path, Approval::NeedsReview, None)
+        .with_context(|| "Recording approval")?;
+    output::success("Accepted submitted transcript.");
+
+    checksum::update(post_path).with_context(|| "Updating checksums")?;
+    vcs::commit_post(location, "review", id, post_path).with_context(|| "Committing transcript")?;
+    Ok(())
+}
+
+/// Moves transcripts submitted with `serve` into their post directories, to be accepted or
+/// rejected with `review`, which also records the claimant as author. Submissions of posts which
+/// are not in posts directory are kept.
+pub fn pull_queue(location: &Location) -> Result<()> {
+    let mut count = 0;
+    for id in submissions::list_submissions(location)? {
+        let post_path = location.posts_dir().join(&id);
+        if !post_path.is_dir() {
+            output::warning(format!(
+                "Submitted transcript of {} was kept, since post is not in posts directory",
+                output::id(&id)
+            ));
+            continue;
+        }
+        let claim = submissions::find_claim(location, &id)?;
+        file::rename_or_copy(
+            location.submission_file(&id),
+            post_path.join(post_file::PENDING_TRANSCRIPT),
+        )
+        .with_context(|| "Moving submitted transcript into post directory")?;
+        match claim {
+            Some(claim) => println!("{}\t(from {})", output::id(&id), claim.name),
+            None => println!("{}", output::id(&id)),
+        }
+        count += 1;
+    }
+    println!(
+        "Pulled {} submitted transcript(s). Review each with `review <id>`.",
+        count
+    );
+    Ok(())
+}
+
+/// Prints every saved version of a post's transcript and title
+pub fn history_show(location: &Location, id: &str) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let versions = history::list_versions(&post_path)?;
+    for version in &versions {
+        println!(
+            "{}\t{}\t{}",
+            version.number,
+            version.file_name,
+            version.saved_at.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    println!("Found {} version(s).", versions.len());
+    Ok(())
+}
+
+/// Prints a unified diff between two versions, as numbered by `history_show`
+pub fn history_diff(
+    location: &Location,
+    id: &str,
+    old_number: usize,
+    new_number: usize,
+) -> Result<()> {
+    let (_, post_path) = names::find_post(location, id)?;
+    let versions = history::list_versions(&post_path)?;
+    let get_version = |number: usize| {
+        versions
+            .iter()
+            .find(|version| version.number == number)
+            .with_context(|| Error::usage(format!("No version {} exists", number)))
+    };
+    let old_version = get_version(old_number)?;
+    let new_version = get_version(new_number)?;
+
+    let old_contents = file::read_text(&old_version.path).with_context(|| "Reading old version")?;
+    let new_contents = file::read_text(&new_version.path).with_context(|| "Reading new version")?;
+    print!(
+        "{}",
+        text_diff::unified_diff(
+            &old_contents,
+            &new_contents,
+            &format!("{} (version {})", old_version.file_name, old_number),
+            &format!("{} (version {})", new_version.file_name, new_number),
+            2,
+        )
+    );
+    Ok(())
+}
+
+/// Prints commits which changed a post, if location is a git repository
+pub fn log(location: &Location, id: &str) -> Result<()> {
+    vcs::print_log(location, id)
+}
+
+/// Prints id, date, title, and first line of notes, of each post.
+/// With `author`, only posts which they contributed to are listed.
+pub fn list(location: &Location, stage: Option<Stage>, author: Option<&str>) -> Result<()> {
+    let stages = match stage {
+        Some(stage) => vec![stage],
+        None => Stage::ALL.to_vec(),
+    };
+    for stage in stages {
+        let entries = file::sort_dir_entries(
+            file::read_dir(location.stage_dir(stage))?
+                .flatten()
+                .collect(),
+        );
+        for entry in entries {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(author) = author {
+                let authors = props::read_authors(&path)?;
+                if !authors.iter().any(|existing| existing.name == author) {
+                    continue;
+                }
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            let date = file::read_first_line(&path.join(post_file::DATE))?;
+            let title = file::read_first_line(&path.join(post_file::TITLE))?;
+            let note = file::read_first_line(&path.join(post_file::NOTES))?;
+            let special = special::read(&path)
+                .with_context(|| format!("Reading special file of {}", id))?
+                .map(|special| special.kind.to_string())
+                .unwrap_or_default();
+            println!(
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                stage.dir_name(),
+                id,
+                date,
+                title,
+                special,
+                note
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints dates which appear in more than one post
+pub fn check_duplicates(location: &Location) -> Result<bool> {
+    let duplicates = names::find_duplicate_dates(location)?;
+    for (date, posts) in &duplicates {
+        println!("{}", date);
+        for (stage, id) in posts {
+            println!("\t{}/{}", stage.dir_name(), id);
+        }
+    }
+    println!("Found {} duplicated date(s).", duplicates.len());
+    Ok(duplicates.is_empty())
+}
+
+/// Recompresses images of one post, or every post, and prints space saved
+pub fn optimize(location: &Location, id: Option<&str>) -> Result<()> {
+    let posts = match id {
+        Some(id) => vec![names::find_post(location, id)?],
+        None => {
+            let mut posts = Vec::new();
+            for stage in Stage::ALL {
+                let entries = file::sort_dir_entries(
+                    file::read_dir(location.stage_dir(stage))?
+                        .flatten()
+                        .collect(),
+                );
+                posts.extend(entries.into_iter().map(|entry| (stage, entry.path())));
+            }
+            posts
+        }
+    };
+
+    let mut total_saved = 0;
+    for (stage, post_path) in posts {
+        let id = post_path
+            .file_name()
+            .expect("post path should have file name")
+            .to_string_lossy()
+            .to_string();
+        let saved = optimize::optimize_post(location.layout(), &post_path)
+            .with_context(|| format!("Optimizing images of `{}`", id))?;
+        if saved > 0 {
+            checksum::update(&post_path).with_context(|| "Updating checksums")?;
+            println!(
+                "{}/{}: saved {:.1} KB",
+                stage.dir_name(),
+                id,
+                saved as f64 / 1000.0
+            );
+        }
+        total_saved += saved;
+    }
+    println!("Saved {:.1} MB.", total_saved as f64 / 1_000_000.0);
+    Ok(())
+}
+
+/// Replaces duplicate images which are still identical to their initial image with hard links.
+/// Generated posts are skipped, since their images are still being edited.
+pub fn dedupe(location: &Location, dry_run: bool) -> Result<()> {
+    let mut linked = 0;
+    let mut saved_bytes = 0;
+    for stage in [Stage::Completed, Stage::Old] {
+        let entries = file::sort_dir_entries(
+            file::read_dir(location.stage_dir(stage))?
+                .flatten()
+                .collect(),
+        );
+        for entry in entries {
+            let post_path = entry.path();
+            if !dedupe::can_link(location.layout(), &post_path)? {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            println!("{}/{}", stage.dir_name(), id);
+            saved_bytes += fs::metadata(post_path.join(&location.layout().duplicate))?.len();
+            if !dry_run {
+                dedupe::link(location.layout(), &post_path)
+                    .with_context(|| format!("Linking images of `{}`", id))?;
+            }
+            linked += 1;
+        }
+    }
+    println!(
+        "{} {} duplicate image(s), saving {:.1} MB.",
+        if dry_run { "Would link" } else { "Linked" },
+        linked,
+        saved_bytes as f64 / 1_000_000.0
+    );
+    Ok(())
+}
+
+/// Prints every file which differs from its post's checksum manifest, and returns whether there
+/// were none. Posts without a manifest are counted, but not treated as a problem.
+pub fn verify(location: &Location) -> Result<bool> {
+    let mut problems = 0;
+    let mut unverified = 0;
+    for stage in Stage::ALL {
+        let entries = file::sort_dir_entries(
+            file::read_dir(location.stage_dir(stage))?
+                .flatten()
+                .collect(),
+        );
+        for entry in entries {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let Some(mismatches) = checksum::verify(&entry.path())
+                .with_context(|| format!("Verifying post `{}`", id))?
+            else {
+                unverified += 1;
+                continue;
+            };
+            for mismatch in &mismatches {
+                let (problem, file_name) = match mismatch {
+                    Mismatch::Changed(file_name) => ("changed", file_name),
+                    Mismatch::Missing(file_name) => ("missing", file_name),
+                    Mismatch::Unrecorded(file_name) => ("unrecorded", file_name),
+                };
+                println!("{}/{}: {} `{}`", stage.dir_name(), id, problem, file_name);
+            }
+            problems += mismatches.len();
+        }
+    }
+    if unverified > 0 {
+        println!("{} post(s) have no checksum manifest.", unverified);
+    }
+    println!("Found {} mismatched file(s).", problems);
+    Ok(problems == 0)
+}
+
+/// Prints every problem found in source directory, and returns whether there were none
+pub fn check_source(location: &Location) -> Result<bool> {
+    let mut problems = 0;
+    let mut extensions_by_date: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+
+    let entries = file::sort_dir_entries(file::read_source_dir(location.source_dir())?);
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        let Ok(Some(date)) = file::get_date_from_path(&path) else {
+            println!("{}: malformed date in file name", name);
+            problems += 1;
+            continue;
+        };
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        extensions_by_date.entry(date).or_default().push(extension);
+
+        let size = entry
+            .metadata()
+            .with_context(|| "Reading file metadata")?
+            .len();
+        if size == 0 {
+            println!("{}: empty file", name);
+            problems += 1;
+        } else if let Err(error) = image::open(&path) {
+            println!("{}: cannot decode image: {}", name, error);
+            problems += 1;
+        }
+    }
+
+    for (date, extensions) in &extensions_by_date {
+        if extensions.len() > 1 {
+            println!(
+                "{}: duplicated with extensions {}",
+                date,
+                extensions.join(", ")
+            );
+            problems += 1;
+        }
+    }
+
+    // Only within each year, since the first and last years may be incomplete
+    let dates: Vec<NaiveDate> = extensions_by_date.into_keys().collect();
+    for pair in dates.windows(2) {
+        let (previous, next) = (pair[0], pair[1]);
+        if previous.year() != next.year() {
+            continue;
+        }
+        let (Some(first), Some(last)) = (previous.succ_opt(), next.pred_opt()) else {
+            continue;
+        };
+        if first > last {
+            continue;
+        }
+        if first == last {
+            println!("{}: missing", first);
+        } else {
+            println!("{}..{}: missing", first, last);
+        }
+        problems += 1;
+    }
+
+    println!("Found {} problem(s).", problems);
+    Ok(problems == 0)
+}
+
+/// Prints runs of consecutive source comics which have no post, longest first
+pub fn gaps(location: &Location, year: Option<i32>) -> Result<()> {
+    let between = match year {
+        Some(year) => FullDateRange::year(year).with_context(|| "Invalid year")?,
+        None => FullDateRange::all(),
+    };
+    let source_dates = names::get_source_dates(location, between)?;
+    // Skipped comics end a run, as if they were posted
+    let excluded_dates = names::get_excluded_dates(location)?;
+
+    // Each run is a slice of `source_dates`, so missing source comics don't end a run
+    let mut runs: Vec<&[NaiveDate]> = source_dates
+        .split(|date| excluded_dates.contains(date))
+        .filter(|run| !run.is_empty())
+        .collect();
+    runs.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+
+    for run in &runs {
+        let (first, last) = (run[0], run[run.len() - 1]);
+        if first == last {
+            println!("{}\t(1 comic)", first);
+        } else {
+            println!("{}..{}\t({} comics)", first, last, run.len());
+        }
+    }
+    println!(
+        "Found {} gap(s), of {} untranslated comic(s).",
+        runs.len(),
+        runs.iter().map(|run| run.len()).sum::<usize>()
+    );
+    Ok(())
+}
+
+/// Prints comics without a post (or skipped), whose anniversary is within the next `days` days.
+/// Round anniversaries (eg. 25th, then 10th, then 5th) are listed first, then soonest.
+pub fn anniversaries(location: &Location, days: u32) -> Result<()> {
+    let excluded_dates = names::get_excluded_dates(location)?;
+    let mut dates_by_day: BTreeMap<(u32, u32), Vec<NaiveDate>> = BTreeMap::new();
+    for date in source_dates::load(location)? {
+        if !excluded_dates.contains(&date) {
+            dates_by_day
+                .entry((date.month(), date.day()))
+                .or_default()
+                .push(date);
+        }
+    }
+
+    let today = Local::now().date_naive();
+    let mut upcoming: Vec<(NaiveDate, NaiveDate, i32)> = Vec::new();
+    for day in today.iter_days().take(days as usize + 1) {
+        let Some(dates) = dates_by_day.get(&(day.month(), day.day())) else {
+            continue;
+        };
+        for date in dates {
+            let years = day.year() - date.year();
+            if years > 0 {
+                upcoming.push((day, *date, years));
+            }
+        }
+    }
+    let roundness = |years: i32| [25, 10, 5].iter().position(|step| years % step == 0);
+    upcoming
+        .sort_by_key(|(day, date, years)| (roundness(*years).unwrap_or(usize::MAX), *day, *date));
+
+    for (day, date, years) in &upcoming {
+        println!(
+            "{}\t{} anniversary on {}\t(in {} day(s))",
+            date,
+            ordinal(*years),
+            day,
+            (*day - today).num_days()
+        );
+    }
+    println!("Found {} upcoming anniversary(s).", upcoming.len());
+    Ok(())
+}
+
+/// Eg. `21st`, `12th`
+fn ordinal(number: i32) -> String {
+    let suffix = match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", number, suffix)
+}
+
+/// Prints a calendar of each month in period (default: current month), marking which comics have
+/// posts, or saves a heatmap of the whole year to `image_path`
+pub fn calendar(
+    location: &Location,
+    period: Option<YearOrMonth>,
+    image_path: Option<&Path>,
+) -> Result<()> {
+    let period =
+        period.unwrap_or_else(|| YearOrMonth::Month(YearMonth::of(Local::now().date_naive())));
+    let source_dates: HashSet<NaiveDate> = source_dates::load(location)?.into_iter().collect();
+    let read_dates = |stage: Stage| -> Result<HashSet<NaiveDate>> {
+        Ok(names::get_stage_post_dates(location, stage)?
+            .into_iter()
+            .map(|(_, date)| date)
+            .collect())
+    };
+    let generated_dates = read_dates(Stage::Generated)?;
+    let completed_dates = read_dates(Stage::Completed)?;
+
+    let coverage = |date: NaiveDate| {
+        if completed_dates.contains(&date) {
+            Coverage::Completed
+        } else if generated_dates.contains(&date) {
+            Coverage::Generated
+        } else if source_dates.contains(&date) {
+            Coverage::SourceOnly
+        } else {
+            Coverage::NoSource
+        }
+    };
+
+    if let Some(image_path) = image_path {
+        calendar::render_heatmap(period.year(), coverage)
+            .save(image_path)
+            .with_context(|| "Saving heatmap image")?;
+        println!("Saved heatmap of {} to {:?}.", period.year(), image_path);
+        return Ok(());
+    }
+
+    let color = output::is_enabled();
+    for (i, month) in period.months().into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        calendar::print_month(month, color, &coverage);
+    }
+    println!();
+    calendar::print_legend(color);
+    Ok(())
+}
+
+/// Prints words of each transcript which are not in the dictionary, or the location's custom
+/// dictionary file, and returns whether there were none
+pub fn check_spelling(location: &Location) -> Result<bool> {
+    let config = &location.config().spelling;
+
+    let dictionary_path = location.dictionary_file();
+    let custom_words: HashSet<String> = if dictionary_path.exists() {
+        fs::read_to_string(&dictionary_path)
+            .with_context(|| "Reading custom dictionary file")?
+            .lines()
+            .map(|word| word.trim().to_string())
+            .filter(|word| !word.is_empty())
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let variants = location.layout().all_variants();
+    let mut problems = 0;
+    let entries = file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect());
+    for (entry, variant) in entries
+        .iter()
+        .flat_map(|entry| variants.iter().map(move |variant| (entry, variant)))
+    {
+        let transcript_path = entry.path().join(&variant.transcript);
+        if !transcript_path.exists() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let label = match &variant.language {
+            Some(language) => format!("{} ({})", id, language),
+            None => id,
+        };
+        let contents =
+            file::read_text(&transcript_path).with_context(|| "Reading transcript file")?;
+
+        // Variant language codes are used as dictionary names
+        let language = variant.language.as_deref().unwrap_or(&config.language);
+        let misspelled: HashSet<String> =
+            commands::list_misspelled_words(&config.program, language, &contents)
+                .with_context(|| "Running spell checker")?
+                .into_iter()
+                .filter(|word| !custom_words.contains(word))
+                .collect();
+        if misspelled.is_empty() {
+            continue;
+        }
+
+        let transcript = Transcript::parse(&contents);
+        for (panel_index, panel) in transcript.panels.iter().enumerate() {
+            for (line_index, line) in panel.iter().enumerate() {
+                for word in line.split(|char: char| !char.is_alphanumeric() && char != '\'') {
+                    if misspelled.contains(word) {
+                        println!(
+                            "{}: panel {}, line {}: {}",
+                            label,
+                            panel_index + 1,
+                            line_index + 1,
+                            word
+                        );
+                        problems += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    println!("Found {} misspelled word(s).", problems);
+    Ok(problems == 0)
+}
+
+/// Prints each use of a non-preferred translation in transcripts, and returns whether there were none.
+/// Only primary transcripts are checked, since glossary terms are in the primary language.
+pub fn check_glossary(location: &Location) -> Result<bool> {
+    let glossary = Glossary::load(location)?;
+    if glossary.is_empty() {
+        println!("Glossary is empty, or does not exist.");
+        return Ok(true);
+    }
+
+    let mut problems = 0;
+    let entries = file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect());
+    for entry in entries {
+        let transcript_path = entry.path().join(post_file::TRANSCRIPT);
+        if !transcript_path.exists() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let transcript = Transcript::parse(
+            &file::read_text(&transcript_path).with_context(|| "Reading transcript file")?,
+        );
+
+        for (panel_index, panel) in transcript.panels.iter().enumerate() {
+            for (line_index, line) in panel.iter().enumerate() {
+                for found in glossary.find_avoided(line) {
+                    println!(
+                        "{}: panel {}, line {}: `{}` for \"{}\", prefer `{}`",
+                        id,
+                        panel_index + 1,
+                        line_index + 1,
+                        found.variant,
+                        found.english,
+                        found.preferred,
+                    );
+                    problems += 1;
+                }
+            }
+        }
+    }
+
+    println!("Found {} inconsistent translation(s).", problems);
+    Ok(problems == 0)
+}
+
+/// Moves a generated or completed post into trash
+pub fn delete(location: &Location, id: &str) -> Result<()> {
+    let stages: Vec<Stage> = [Stage::Generated, Stage::Completed]
+        .into_iter()
+        .filter(|stage| location.stage_dir(*stage).join(id).is_dir())
+        .collect();
+    let stage = match stages.as_slice() {
+        [] => bail!(Error::missing_post(
+            "No generated or completed post exists with that id"
+        )),
+        [stage] => *stage,
+        _ => {
+            bail!("Post exists as both generated and completed; refusing to guess which to delete")
+        }
+    };
+
+    let name = trash::move_to_trash(location, stage, id)?;
+    output::success(format!(
+        "Moved {} to trash as {}",
+        output::id(id),
+        output::id(&name)
+    ));
+    Ok(())
+}
+
+pub fn trash_list(location: &Location) -> Result<()> {
+    let entries = trash::list_entries(location).with_context(|| "Reading trash directory")?;
+    if entries.is_empty() {
+        println!("Trash is empty.");
+    }
+    for entry in entries {
+        println!("{}", entry);
+    }
+    Ok(())
+}
+
+pub fn trash_restore(location: &Location, name: &str) -> Result<()> {
+    let entry = trash::restore(location, name)?;
+    output::success(format!(
+        "Restored {} to {} directory",
+        output::id(&entry.id),
+        entry.stage.dir_name()
+    ));
+    Ok(())
+}
+
+pub fn trash_empty(location: &Location) -> Result<()> {
+    if !ui::ask_yes_no(
+        location,
+        PromptId::EmptyTrash,
+        "Permanently remove all posts in trash?",
+    )? {
+        println!("Trash not emptied.");
+        return Ok(());
+    }
+    let count = trash::empty(location).with_context(|| "Emptying trash")?;
+    output::success(format!("Removed {} post(s) from trash.", count));
+    Ok(())
+}
+
+/// `keep` takes precedence over `backup.keep` in config
+pub fn backup(location: &Location, dir: Option<&Path>, keep: Option<usize>) -> Result<()> {
+    let keep = keep.or(location.config().backup.keep);
+    if keep == Some(0) {
+        bail!(Error::usage("At least one backup must be kept"));
+    }
+    let dir = backup::snapshots_dir(location, dir);
+    let snapshot = backup::create(location, &dir)?;
+    output::success(format!("Backed up location to {:?}", snapshot.path));
+    if let Some(keep) = keep {
+        for removed in backup::prune(&dir, keep).with_context(|| "Removing old backups")? {
+            println!("Removed backup {}", removed.name);
+        }
+    }
+    Ok(())
+}
+
+pub fn backup_list(location: &Location, dir: Option<&Path>) -> Result<()> {
+    let snapshots = backup::list(&backup::snapshots_dir(location, dir))?;
+    if snapshots.is_empty() {
+        println!("No backups.");
+    }
+    for snapshot in snapshots {
+        println!(
+            "{}\t{}",
+            snapshot.name,
+            snapshot.created_at.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+/// Current state is backed up first, so restoring can be undone
+pub fn backup_restore(location: &Location, dir: Option<&Path>, timestamp: &str) -> Result<()> {
+    let dir = backup::snapshots_dir(location, dir);
+    let snapshot = backup::list(&dir)?
+        .into_iter()
+        .find(|snapshot| snapshot.name == timestamp)
+        .with_context(|| Error::usage("No backup with that timestamp"))?;
+    let current = backup::create(location, &dir).with_context(|| "Backing up current state")?;
+    backup::restore(location, &snapshot)?;
+    output::success(format!(
+        "Restored backup {}. Previous state was backed up as {}.",
+        snapshot.name, current.name
+    ));
+    Ok(())
+}
+
+/// Without `target`, pastes files into upload destination window
+/// Uploads primary translation if `language` is `None`
+/// Post must be approved with `review`, unless `force`
+pub fn upload(
+    location: &Location,
+    id: &str,
+    target: Option<PublishTarget>,
+    language: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    let variant = location.layout().variant(language)?;
+    pipeline::require_transition(location, id, pipeline::Stage::Uploaded)?;
+    let post_path = location.posts_dir().join(id);
+    if !post_path.join(&variant.transcript).is_file() {
+        bail!(Error::validation(format!(
+            "Post has no `{}` file",
+            variant.transcript
+        )));
+    }
+    match props::read_approval(&post_path)? {
+        Some(approval) if approval != Approval::Approved && !force => {
+            bail!(Error::validation(format!(
+                "Post is not approved (it is `{}`). Approve it with `review`, or upload anyway \
+                with `--force`.",
+                approval
+            )));
+        }
+        _ => (),
+    }
+
+    let mut schedule = Schedule::load(location)?;
+    warn_if_unscheduled_upload(&schedule, id);
+
+    let target_name = target.map_or(uploads::PASTE_TARGET, PublishTarget::name);
+    let records = uploads::load(location)?;
+    warn_if_reupload(&records, id, target_name, language);
+    warn_if_over_rate(&records, location.config().upload.max_per_day);
+    hooks::run_pre(location, "upload", id, &post_path)?;
+
+    if let Some(target) = target {
+        let caption = caption::render_caption(location, &post_path, &variant)
+            .with_context(|| "Creating caption")?;
+        let config = location.config();
+        publish::publish(
+            &config.publish,
+            &config.layout,
+            &variant,
+            target,
+            &post_path,
+            caption,
+        )
+        .with_context(|| "Publishing to target")?;
+        output::success(format!("Published {}", output::id(id)));
+    } else {
+        location.system().toggle_upload_destination()?;
+
+        if let Err(error) = upload_files(location, &post_path, &variant) {
+            // Close destination if upload failed
+            location.system().toggle_upload_destination()?;
+            return Err(error);
+        }
+    }
+
+    fs::write(
+        post_path.join(post_file::UPLOADED),
+        Local::now().date_naive().to_string(),
+    )
+    .with_context(|| "Writing upload date file")?;
+    checksum::update(&post_path).with_context(|| "Updating checksums")?;
+    uploads::append(location, id, target_name, language).with_context(|| "Recording upload")?;
+
+    if schedule.remove(id) {
+        schedule.save(location)?;
+    }
+
+    hooks::run_post(location, "upload", id, &post_path);
+
+    Ok(())
+}
+
+fn warn_if_reupload(records: &[UploadRecord], id: &str, target_name: &str, language: Option<&str>) {
+    let previous = records.iter().rev().find(|record| {
+        record.id == id && record.target == target_name && record.language.as_deref() == language
+    });
+    if let Some(previous) = previous {
+        let variant = match language {
+            Some(language) => format!(" (variant `{}`)", language),
+            None => String::new(),
+        };
+        output::warning(format!(
+            "Post was already uploaded to {}{} at {}",
+            target_name,
+            variant,
+            previous.time.format("%Y-%m-%d %H:%M")
+        ));
+    }
+}
+
+fn warn_if_over_rate(records: &[UploadRecord], max_per_day: Option<usize>) {
+    let Some(max_per_day) = max_per_day else {
+        return;
+    };
+    let today = Local::now().date_naive();
+    let uploaded_today = records
+        .iter()
+        .filter(|record| record.time.date() == today)
+        .count();
+    if uploaded_today >= max_per_day {
+        output::warning(format!(
+            "{} post(s) already uploaded today, which reaches the limit of {}",
+            uploaded_today, max_per_day
+        ));
+    }
+}
+
+fn warn_if_unscheduled_upload(schedule: &Schedule, id: &str) {
+    let today = Local::now().date_naive();
+    let Some(publish_date) = schedule.get(id) else {
+        return;
+    };
+    if publish_date > today {
+        output::warning(format!(
+            "Post is scheduled for {}, which is after today",
+            publish_date
+        ));
+    }
+    let earlier: Vec<_> = schedule
+        .entries()
+        .into_iter()
+        .filter(|(date, other_id)| *date < publish_date && *other_id != id)
+        .collect();
+    if !earlier.is_empty() {
+        output::warning("Uploading out of order. Posts scheduled earlier:");
+        for (date, other_id) in earlier {
+            println!("\t{}\t{}", date, other_id);
+        }
+    }
+}
+
+pub fn schedule_set(location: &Location, id: &str, date: NaiveDate) -> Result<()> {
+    let exists = [Stage::Generated, Stage::Completed]
+        .into_iter()
+        .any(|stage| location.stage_dir(stage).join(id).is_dir());
+    if !exists {
+        bail!(Error::missing_post(
+            "No generated or completed post exists with that id"
+        ));
+    }
+    let mut schedule = Schedule::load(location)?;
+    schedule.set(id, date);
+    schedule.save(location)?;
+    output::success(format!("Scheduled {} for {}", output::id(id), date));
+    Ok(())
+}
+
+pub fn schedule_list(location: &Location) -> Result<()> {
+    let schedule = Schedule::load(location)?;
+    let entries = schedule.entries();
+    if entries.is_empty() {
+        println!("No posts scheduled.");
+    }
+    for (date, id) in entries {
+        println!("{}\t{}", date, id);
+    }
+    Ok(())
+}
+
+/// Shows posts due today (or overdue)
+pub fn schedule_next(location: &Location) -> Result<()> {
+    let schedule = Schedule::load(location)?;
+    let today = Local::now().date_naive();
+    let due = schedule.due(today);
+    if due.is_empty() {
+        println!("Nothing due today.");
+    }
+    for (date, id) in due {
+        if date < today {
+            println!("{}\t{}\t(overdue)", date, id);
+        } else {
+            println!("{}\t{}", date, id);
+        }
+    }
+    Ok(())
+}
+
+/// Prints averages of transcription time and length, weekly throughput, and an estimate of time to
+/// finish the archive. Weeks are counted by modification time of transcript files.
+pub fn stats_effort(location: &Location) -> Result<()> {
+    const RECENT_WEEKS: usize = 8;
+
+    let mut efforts = Vec::new();
+    let mut word_counts = Vec::new();
+    let mut transcribed_dates = Vec::new();
+    let mut untranscribed = 0;
+    for entry in file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect()) {
+        let post_path = entry.path();
+        let transcript_path = post_path.join(post_file::TRANSCRIPT);
+        if !transcript_path.exists() {
+            untranscribed += 1;
+            continue;
+        }
+        let contents =
+            file::read_text(&transcript_path).with_context(|| "Reading transcript file")?;
+        let transcript = Transcript::parse(&contents);
+        word_counts.push(
+            transcript
+                .panels
+                .iter()
+                .flatten()
+                .map(|line| line.split_whitespace().count())
+                .sum::<usize>(),
+        );
+        if let Some(effort) = props::read_effort(&post_path)? {
+            efforts.push(effort);
+        }
+        let modified = fs::metadata(&transcript_path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| "Reading modification time of transcript file")?;
+        transcribed_dates.push(DateTime::<Local>::from(modified).date_naive());
+    }
+
+    println!(
+        "Transcribed posts: {} ({} completed but untranscribed)",
+        word_counts.len(),
+        untranscribed
+    );
+    if !word_counts.is_empty() {
+        println!(
+            "Average words per post: {:.1}",
+            word_counts.iter().sum::<usize>() as f64 / word_counts.len() as f64
+        );
+    }
+    let average_effort =
+        (!efforts.is_empty()).then(|| efforts.iter().sum::<Duration>() / efforts.len() as u32);
+    match average_effort {
+        Some(average) => println!(
+            "Average transcription time: {} (of {} post(s) with recorded time)",
+            format_duration(average),
+            efforts.len()
+        ),
+        None => println!("Average transcription time: unknown (no recorded times)"),
+    }
+
+    transcribed_dates.sort();
+    let (Some(first), Some(last)) = (transcribed_dates.first(), transcribed_dates.last()) else {
+        return Ok(());
+    };
+    let mut weeks: BTreeMap<(i32, u32), usize> = BTreeMap::new();
+    for date in &transcribed_dates {
+        let week = date.iso_week();
+        *weeks.entry((week.year(), week.week())).or_default() += 1;
+    }
+    println!("Posts transcribed per week (most recent {}):", RECENT_WEEKS);
+    for ((year, week), count) in weeks.iter().rev().take(RECENT_WEEKS).rev() {
+        println!("\t{}-W{:02}\t{}", year, week, count);
+    }
+    let week_span = ((*last - *first).num_days() / 7 + 1) as f64;
+    let per_week = transcribed_dates.len() as f64 / week_span;
+    println!("Average per week: {:.1}", per_week);
+
+    let source_count = names::get_source_dates(location, FullDateRange::all())?.len();
+    let posted_count = names::get_post_dates(location)?.len();
+    let remaining = source_count.saturating_sub(posted_count) + untranscribed;
+    print!(
+        "Remaining comics: {}, about {:.0} week(s)",
+        remaining,
+        remaining as f64 / per_week
+    );
+    match average_effort {
+        Some(average) => println!(
+            " or {} of transcription",
+            format_duration(average * remaining as u32)
+        ),
+        None => println!(),
+    }
+    Ok(())
+}
+
+/// Average time of each phase, for every command logged with `--timings`
+pub fn stats_perf(location: &Location) -> Result<()> {
+    let runs = timings::read_log(location)?;
+    if runs.is_empty() {
+        println!("No runs logged. Run any command with `--timings` to log it.");
+        return Ok(());
+    }
+    let mut commands: BTreeMap<&str, Vec<&timings::Run>> = BTreeMap::new();
+    for run in &runs {
+        commands.entry(&run.command).or_default().push(run);
+    }
+
+    for (command, runs) in commands {
+        let total: Duration = runs.iter().map(|run| run.total).sum();
+        println!(
+            "{} ({} run(s), average {:.1} ms)",
+            command,
+            runs.len(),
+            timings::as_millis(total / runs.len() as u32)
+        );
+        for phase in Phase::ALL {
+            let phase_total: Duration = runs.iter().filter_map(|run| run.phases.get(&phase)).sum();
+            if phase_total.is_zero() {
+                continue;
+            }
+            println!(
+                "\t{:<8}\t{:>9.1} ms\t{:>5.1}%",
+                phase.name(),
+                timings::as_millis(phase_total / runs.len() as u32),
+                timings::percent(phase_total, total)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Number of transcripts and SVGs contributed by each author, to completed posts
+pub fn stats_by_author(location: &Location) -> Result<()> {
+    let mut counts: BTreeMap<String, BTreeMap<Contribution, usize>> = BTreeMap::new();
+    let mut unattributed = 0;
+    for entry in file::read_dir(location.posts_dir())?.flatten() {
+        let authors = props::read_authors(&entry.path())?;
+        if authors.is_empty() {
+            unattributed += 1;
+        }
+        for author in authors {
+            *counts
+                .entry(author.name)
+                .or_default()
+                .entry(author.contribution)
+                .or_default() += 1;
+        }
+    }
+
+    for (name, contributions) in &counts {
+        let summary: Vec<String> = contributions
+            .iter()
+            .map(|(contribution, count)| format!("{} {}(s)", count, contribution))
+            .collect();
+        println!("{}\t{}", name, summary.join(", "));
+    }
+    println!("Posts without an author: {}", unattributed);
+    Ok(())
+}
+
+/// As hours and minutes, or minutes and seconds if less than an hour
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 60 * 60 {
+        format!("{}h {:02}m", seconds / 60 / 60, seconds / 60 % 60)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+/// Prints every recorded upload, oldest first, optionally only of one post
+pub fn uploads_list(location: &Location, id: Option<&str>) -> Result<()> {
+    let records = uploads::load(location)?;
+    let mut count = 0;
+    for record in &records {
+        if id.is_some_and(|id| record.id != id) {
+            continue;
+        }
+        println!(
+            "{}\t{}\t{}\t{}",
+            record.time.format("%Y-%m-%d %H:%M"),
+            record.id,
+            record.target,
+            record.language.as_deref().unwrap_or("-")
+        );
+        count += 1;
+    }
+    println!("Found {} upload(s).", count);
+    Ok(())
+}
+
+/// Prints which post to upload next, according to the configured policy
+pub fn next_upload(location: &Location) -> Result<()> {
+    let policy = location.config().upload.policy;
+    let Some(id) = schedule::next_upload(location, policy)? else {
+        println!("No transcribed posts are waiting to be uploaded.");
+        return Ok(());
+    };
+    let date = names::read_date(location, &id)?;
+    match Schedule::load(location)?.get(&id) {
+        Some(publish_date) => println!("{}\t{}\t(scheduled for {})", id, date, publish_date),
+        None => println!("{}\t{}", id, date),
+    }
+    Ok(())
+}
+
+/// Caption is pasted last, after both images and the transcript
+fn upload_files(location: &Location, post_path: &Path, variant: &Variant) -> Result<()> {
+    let system = location.system();
+    let prompter = location.prompter();
+    let clipboard = location.config().clipboard.backend;
+
+    let caption = caption::render_caption(location, post_path, variant)
+        .with_context(|| "Creating caption")?;
+    let temp_dir = location.temp_dir();
+    fs::create_dir_all(&temp_dir).with_context(|| "Creating temp directory for caption file")?;
+    let caption_path = temp_dir.join("caption");
+    fs::write(&caption_path, caption).with_context(|| "Writing caption file")?;
+    let _caption_guard = cleanup::register(cleanup::Task::RemoveFile(caption_path.clone()));
+
+    system.upload_file(
+        prompter,
+        clipboard,
+        &post_path.join(&location.layout().duplicate),
+    )?;
+    commands::sleep(100);
+    system.upload_file(prompter, clipboard, &post_path.join(&variant.image))?;
+    commands::sleep(100);
+    system.upload_file(prompter, clipboard, &post_path.join(&variant.transcript))?;
+    commands::sleep(100);
+    system.upload_file(prompter, clipboard, &caption_path)?;
+    Ok(())
+}
+
+/// Posts are counted as completed in the month their final image was last modified
+pub fn report(
+    location: &Location,
+    month: YearMonth,
+    format: ReportFormat,
+    output_dir: Option<PathBuf>,
+) -> Result<()> {
+    const IMAGES_DIR: &str = "images";
+    const THUMBNAILS_DIR: &str = "thumbnails";
+
+    let output_dir =
+        output_dir.unwrap_or_else(|| location.temp_dir().join(format!("report.{}", month)));
+    fs::create_dir_all(output_dir.join(IMAGES_DIR))
+        .with_context(|| "Creating report images directory")?;
+    fs::create_dir_all(output_dir.join(THUMBNAILS_DIR))
+        .with_context(|| "Creating report thumbnails directory")?;
+
+    let mut thumbnails = ThumbnailCache::open(location)?;
+
+    let mut entries = Vec::new();
+    for entry in file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect()) {
+        let post_path = entry.path();
+        let image_path = post_path.join(&location.layout().initial);
+        if !image_path.exists() {
+            continue;
+        }
+
+        let modified = fs::metadata(&image_path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| "Reading modification time of post image")?;
+        let completed_date = chrono::DateTime::<chrono::Local>::from(modified).date_naive();
+        if !month.contains(completed_date) {
+            continue;
+        }
+
+        let id = entry.file_name().to_string_lossy().to_string();
+        let title = file::read_text(post_path.join(post_file::TITLE))
+            .with_context(|| format!("Reading title file of post {}", id))?
+            .trim()
+            .to_string();
+        let date = names::read_date(location, &id)
+            .with_context(|| format!("Reading date file of post {}", id))?;
+
+        let image_file_name = format!("{}/{}.png", IMAGES_DIR, id);
+        let thumbnail_file_name = format!("{}/{}.png", THUMBNAILS_DIR, id);
+        fs::copy(&image_path, output_dir.join(&image_file_name))
+            .with_context(|| "Copying post image")?;
+        let thumbnail_path = thumbnails
+            .get(location, &id)
+            .with_context(|| "Generating thumbnail")?;
+        fs::copy(thumbnail_path, output_dir.join(&thumbnail_file_name))
+            .with_context(|| "Copying thumbnail image")?;
+
+        entries.push(ReportEntry {
+            id,
+            title,
+            date,
+            image_path: image_file_name,
+            thumbnail_path: thumbnail_file_name,
+        });
+    }
+
+    let report_path = output_dir.join(format.file_name());
+    fs::write(&report_path, format.render(month, &entries))
+        .with_context(|| "Writing report file")?;
+
+    println!(
+        "Wrote report of {} post(s) to {}",
+        entries.len(),
+        report_path.to_string_lossy()
+    );
+    Ok(())
+}
+
+pub fn jobs_list(location: &Location) -> Result<()> {
+    let jobs = jobs::load(location)?;
+    for job in &jobs {
+        println!("{}", job);
+    }
+    println!("Found {} queued job(s).", jobs.len());
+    Ok(())
+}
+
+/// Stops at first job which fails, leaving it queued
+pub fn jobs_run(location: &Location) -> Result<()> {
+    // Only loaded for the first make job, since watermarks are not needed to transcribe
+    let mut context = None;
+    let mut count = 0;
+    while let Some(job) = jobs::load(location)?.into_iter().next() {
+        println!("Running job: {}", output::id(&job));
+        match &job {
+            Job::Make { date } => {
+                if context.is_none() {
+                    context = Some(MakeContext::new(location)?);
+                }
+                let context = context.as_mut().expect("context should be loaded");
+                make_with(context, *date, &names::generate_name(*date), false, None)
+            }
+            Job::Transcribe { id } => transcribe(location, id, false, None),
+        }
+        .with_context(|| format!("Running job `{}`", job))?;
+        jobs::remove(location, &job)?;
+        count += 1;
+    }
+    output::success(format!("Ran {} job(s).", count));
+    Ok(())
+}
+
+pub fn jobs_clear(location: &Location) -> Result<()> {
+    let count = jobs::load(location)?.len();
+    jobs::clear(location)?;
+    println!("Removed {} queued job(s).", count);
+    Ok(())
+}
+
+/// Prints Atom feed of the most recently uploaded posts, each with its caption
+pub fn feed(location: &Location, limit: usize) -> Result<()> {
+    const FEED_ID: &str = "urn:garfutils:uploads";
+
+    let variant = location.layout().variant(None)?;
+    let image_base_url = location
+        .config()
+        .feed
+        .image_base_url
+        .as_deref()
+        .map(|url| url.trim_end_matches('/'));
+
+    let mut seen_ids = HashSet::new();
+    let mut entries = Vec::new();
+    for record in uploads::load(location)?.into_iter().rev() {
+        if entries.len() >= limit {
+            break;
+        }
+        // Re-uploads only update the most recent entry
+        if !seen_ids.insert(record.id.clone()) {
+            continue;
+        }
+        let post_path = location.posts_dir().join(&record.id);
+        if !post_path.is_dir() {
+            continue;
+        }
+        let caption = caption::render_caption(location, &post_path, &variant)
+            .with_context(|| format!("Rendering caption of post {}", record.id))?;
+        let image_url = image_base_url.map(|url| format!("{}/{}.png", url, record.id));
+        entries.push(FeedEntry {
+            title: file::read_first_line(&post_path.join(post_file::TITLE))?,
+            id: format!("urn:garfutils:post:{}", record.id),
+            link: image_url.clone(),
+            updated: record
+                .time
+                .and_local_timezone(Local)
+                .earliest()
+                .with_context(|| "Upload time does not exist in local timezone")?,
+            summary: caption,
+            image_url,
+        });
+    }
+
+    print!(
+        "{}",
+        feed::render_atom(&location.config().site.title, FEED_ID, None, &entries)
+    );
+    Ok(())
+}
+
+/// Writes a static website of completed posts to `output_dir`, overwriting existing files of the
+/// same names
+pub fn site_build(location: &Location, output_dir: &Path) -> Result<()> {
+    for dir in [site::IMAGES_DIR, site::THUMBNAILS_DIR, site::POSTS_DIR] {
+        fs::create_dir_all(output_dir.join(dir))
+            .with_context(|| format!("Creating `{}` directory of site", dir))?;
+    }
+    let mut thumbnails = ThumbnailCache::open(location)?;
+
+    let mut posts = Vec::new();
+    for entry in file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect()) {
+        let post_path = entry.path();
+        let image_path = post_path.join(&location.layout().initial);
+        if !image_path.exists() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let title = file::read_first_line(&post_path.join(post_file::TITLE))?;
+        let date = names::read_date(location, &id)
+            .with_context(|| format!("Reading date file of post {}", id))?;
+        let transcript_path = post_path.join(post_file::TRANSCRIPT);
+        let alt_text = if transcript_path.exists() {
+            let transcript = Transcript::parse(
+                &file::read_text(&transcript_path)
+                    .with_context(|| format!("Reading transcript of post {}", id))?,
+            );
+            Some(TranscriptFormat::AltText.render(&title, date, &transcript))
+        } else {
+            None
+        };
+        let modified = fs::metadata(&image_path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| "Reading modification time of post image")?;
+
+        fs::copy(
+            &image_path,
+            output_dir
+                .join(site::IMAGES_DIR)
+                .join(format!("{}.png", id)),
+        )
+        .with_context(|| "Copying post image")?;
+        let thumbnail_path = thumbnails
+            .get(location, &id)
+            .with_context(|| "Generating thumbnail")?;
+        fs::copy(
+            thumbnail_path,
+            output_dir
+                .join(site::THUMBNAILS_DIR)
+                .join(format!("{}.png", id)),
+        )
+        .with_context(|| "Copying thumbnail image")?;
+
+        posts.push(SitePost {
+            id,
+            title,
+            date,
+            alt_text,
+            completed: DateTime::<Local>::from(modified),
+        });
+    }
+
+    let config = &location.config().site;
+    for (file_name, contents) in site::render(&config.title, config.base_url.as_deref(), &posts) {
+        let path = output_dir.join(&file_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| "Creating site directory")?;
+        }
+        fs::write(&path, contents).with_context(|| format!("Writing `{}`", file_name))?;
+    }
+    output::success(format!(
+        "Wrote site of {} post(s) to {}",
+        posts.len(),
+        output_dir.display()
+    ));
+    Ok(())
+}
+
+/// With `watch`, runs indefinitely, checking for new or changed posts every `interval`
+pub fn thumbnails(
+    location: &Location,
+    watch: bool,
+    interval: Duration,
+    throttle: Duration,
+) -> Result<()> {
+    let mut thumbnails = ThumbnailCache::open(location)?;
+    loop {
+        let generated_count = thumbnails
+            .refresh_all(location, throttle)
+            .with_context(|| "Refreshing thumbnails")?;
+        if !watch {
+            println!("Generated {} thumbnail(s).", generated_count);
+            return Ok(());
+        }
+        commands::sleep(interval.as_millis() as u64);
+    }
+}
+
+/// Empty panels, or panels with recognized English text as comments (with `ocr` feature).
+/// With `draft`, each panel also gets a machine translation of its English text.
+fn create_transcript_template(
+    location: &Location,
+    original_path: &Path,
+    is_sunday: bool,
+    draft: bool,
+    language: Option<&str>,
+) -> Result<String> {
+    let panel_count = if is_sunday { 7 } else { 3 };
+    // Sunday layouts vary, so don't split into panels
+    let ocr_panel_count = if is_sunday { 1 } else { panel_count };
+
+    let recognized = ocr::recognize_panels(original_path, ocr_panel_count, &location.temp_dir())
+        .unwrap_or_else(|error| {
+            output::warning(format!("Failed to recognize comic text: {:#}", error));
+            Vec::new()
+        });
+
+    let mut lines = Vec::new();
+    for i in 0..panel_count {
+        if i > 0 {
+            lines.push("---".to_string());
+        }
+        let Some(text) = recognized.get(i as usize) else {
+            continue;
+        };
+        lines.extend(
+            text.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| format!("{}{}", transcript::NOTE_MARKER, line.trim())),
+        );
+        if draft && !text.trim().is_empty() {
+            let translation = translate_draft(location, text, language)
+                .with_context(|| format!("Translating panel {}", i + 1))?;
+            lines.extend(
+                translation
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| {
+                        format!("{}{}", transcript::MACHINE_TRANSLATION_MARKER, line.trim())
+                    }),
+            );
+        }
+    }
+    if draft && recognized.is_empty() {
+        println!("No English text to translate.");
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Variants are translated from English to their language code, eg. `en-es`
+fn translate_draft(location: &Location, text: &str, language: Option<&str>) -> Result<String> {
+    let config = &location.config().translation;
+    match &config.http_url {
+        Some(url) => {
+            let mut fields = vec![commands::FormField::text("text", text)];
+            if let Some(language) = language {
+                fields.push(commands::FormField::text("language", language));
+            }
+            let response = commands::http_post_form(url, &[], &fields)?;
+            Ok(String::from_utf8_lossy(&response).to_string())
+        }
+        None => match language {
+            Some(language) => commands::translate_apertium(&format!("en-{}", language), text),
+            None => commands::translate_apertium(&config.apertium_pair, text),
+        },
+    }
 }
 
 fn is_id_sunday(id: &str) -> Result<bool> {
     let id_number = id
         .parse::<u32>()
         .with_context(|| "Post id is not an integer")?;
-    let is_sunday = (id_number + 1) % 7 == 0;
-    Ok(is_sunday)
+    Ok(names::is_post_number_sunday(id_number))
 }
@@ -1,15 +1,21 @@
+use crate::help;
+
 use std::path::PathBuf;
 
-use chrono::NaiveDate;
+use chrono::{Datelike as _, Days, Local, NaiveDate, Weekday};
 use clap::{ArgGroup, Parser, Subcommand};
 
-use garfutils::DateRange;
+use garfutils::output::ColorChoice;
+use garfutils::{
+    DateRange, FullDateRange, PublishTarget, ReportFormat, SpecialKind, Stage, TranscriptFormat,
+    YearMonth, YearOrMonth,
+};
 
 /// GarfUtils
 ///
 /// A set of utilities for translating Garfield comics
 #[derive(Debug, Parser)]
-#[clap(author, version, about)]
+#[clap(author, version, about, after_help = help::examples())]
 pub struct Args {
     #[command(subcommand)]
     pub command: Command,
@@ -20,6 +26,31 @@ pub struct Args {
     /// Expects sub-directories `source`, `generated`, `posts`, each of which may be symlinks
     #[arg(long)]
     pub location: Option<PathBuf>,
+    /// Name of this workflow, so its image viewer windows don't conflict with other instances
+    #[arg(long, global = true)]
+    pub instance: Option<String>,
+    /// Wait for other instances to finish modifying the location, instead of failing
+    #[arg(long, global = true)]
+    pub wait: bool,
+    /// Print paths instead of opening image viewer windows
+    ///
+    /// Implied when neither `$WAYLAND_DISPLAY` nor `$DISPLAY` is set
+    #[arg(long, global = true)]
+    pub no_gui: bool,
+    /// Print how long each phase of the command took, and log it for `stats --perf`
+    #[arg(long, global = true)]
+    pub timings: bool,
+    /// Exit with code 6 if any prompt is answered no, instead of continuing without that step
+    ///
+    /// Declining to move the old post in `revise` always fails, even without this option
+    #[arg(long, global = true)]
+    pub abort_on_no: bool,
+    /// Name to record as author of saved transcripts and SVGs, instead of `identity.name` in config
+    #[arg(long, global = true)]
+    pub author: Option<String>,
+    /// Whether to style output, eg. warnings in yellow
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
 }
 
 #[derive(Debug, Subcommand)]
@@ -32,17 +63,68 @@ pub enum Command {
     )]
     Show {
         /// Date of the comic to display (defaults to a random date)
-        #[arg(group("date_sunday"), group("date_range"))]
+        ///
+        /// Any date may also be `today`, `yesterday`, `last-<weekday>`, or an offset like `-3d`
+        #[arg(group("date_sunday"), group("date_range"), value_parser = parse_date, allow_hyphen_values = true)]
         date: Option<NaiveDate>,
         /// Only show comics within a month+day range (regardless of year)
         #[arg(short, long, group("date_range"), value_parser = clap::value_parser!(DateRange))]
         range: Option<DateRange>,
+        /// Only show comics published on today's month and day, in any year
+        #[arg(short, long, group("date_range"))]
+        today: bool,
+        /// Only show comics published on the month and day of this date, in any year
+        #[arg(short, long, group("date_range"), value_parser = parse_date, allow_hyphen_values = true)]
+        anniversary: Option<NaiveDate>,
+        /// Only show comics within a full date range, eg. `2003-01-01..2005-12-31` or `2003..2005`
+        #[arg(short, long, conflicts_with = "date", value_parser = clap::value_parser!(FullDateRange))]
+        between: Option<FullDateRange>,
         /// Only show 'sunday' comics (for random date)
         #[arg(short, long, group("date_sunday"))]
         sunday: bool,
+        /// Only show comics on these weekdays, comma-separated (for random date)
+        #[arg(short, long, group("date_sunday"), value_delimiter = ',')]
+        weekday: Vec<Weekday>,
         /// Only print the date, don't open image viewer
         #[arg(short, long)]
         just_print: bool,
+        /// Show many random comics as a slideshow, with this many seconds between each
+        #[arg(long, conflicts_with = "date", num_args = 0..=1, default_missing_value = "5")]
+        slideshow: Option<u64>,
+        /// Number of comics in slideshow
+        #[arg(long, requires = "slideshow", default_value_t = 20)]
+        count: usize,
+        /// Only show comics rated at least this, with `rate`
+        #[arg(long, conflicts_with = "date", value_parser = clap::value_parser!(u8).range(1..=5))]
+        min_rating: Option<u8>,
+        /// Ask for a rating of the comic after showing it
+        #[arg(long, conflicts_with = "slideshow")]
+        rate: bool,
+        /// Only show comics which have no post, and are not in skip list
+        #[arg(short, long, conflicts_with = "date")]
+        new: bool,
+    },
+
+    /// Add a comic to skip list, so it is never suggested or shown with `show --new`
+    Skip {
+        /// Date of the comic
+        #[arg(value_parser = parse_date, allow_hyphen_values = true)]
+        date: NaiveDate,
+        /// Why comic should not be posted, eg. `untranslatable pun`
+        reason: Option<String>,
+        /// Remove comic from skip list instead
+        #[arg(long, conflicts_with = "reason")]
+        remove: bool,
+    },
+
+    /// Rate a comic from 1 to 5, for `show --min-rating` and `make --best-unposted`
+    Rate {
+        /// Date of the comic
+        #[arg(value_parser = parse_date, allow_hyphen_values = true)]
+        date: NaiveDate,
+        /// Rating, from 1 to 5
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=5))]
+        rating: u8,
     },
 
     /// Create a new post, given a date
@@ -50,14 +132,50 @@ pub enum Command {
     #[clap(group(ArgGroup::new("date_recent").required(true)))]
     Make {
         /// Date of the comic to create into a post
-        #[arg(group("date_recent"))]
+        #[arg(group("date_recent"), value_parser = parse_date, allow_hyphen_values = true)]
         date: Option<NaiveDate>,
         /// Use most recently displayed comic `show` instead of specifying a date
-        #[arg(short, long, group("date_recent"))]
-        recent: bool,
+        ///
+        /// Optionally, how many comics back to go, eg. `--recent 2` for second-most-recent
+        #[arg(
+            short,
+            long,
+            group("date_recent"),
+            num_args = 0..=1,
+            default_missing_value = "1",
+            value_parser = clap::value_parser!(usize).range(1..)
+        )]
+        recent: Option<usize>,
+        /// Create posts for every comic in a full date range, which doesn't already have a post
+        #[arg(short, long, group("date_recent"), value_parser = clap::value_parser!(FullDateRange))]
+        between: Option<FullDateRange>,
+        /// Use a random comic with the highest rating, which doesn't already have a post
+        #[arg(long, group("date_recent"))]
+        best_unposted: bool,
+        /// Create post from an image file, instead of the source comic for the date
+        ///
+        /// Date must be given, eg. `make 2024-01-01 --from-file strip.jpg`
+        #[arg(long, requires = "date")]
+        from_file: Option<PathBuf>,
+        /// Also save the image file into source directory, as PNG
+        #[arg(long, requires = "from_file")]
+        save_source: bool,
+        /// Mark post as special, eg. for a holiday. Edit details later with `special`
+        #[arg(long, value_enum, conflicts_with = "between")]
+        special: Option<SpecialKind>,
         // TODO(feat): name
     },
 
+    /// Move a generated post into posts directory, after checking required files exist
+    #[clap(alias = "f")]
+    Finish {
+        /// Id of the generated post
+        id: String,
+        /// Id to give the completed post (defaults to next sequential id)
+        #[arg(long)]
+        new_id: Option<String>,
+    },
+
     /// Transcribe an existing post, given an id
     ///
     /// Displays post, and opens editor to input transcription
@@ -65,6 +183,21 @@ pub enum Command {
     Transcribe {
         /// Id of the post to transcribe
         id: Option<String>,
+        /// Insert a machine translation of each panel's English text, for editing
+        ///
+        /// Requires the `ocr` feature, to recognize English text. Fails if the post already has a
+        /// transcript.
+        #[arg(long)]
+        draft: bool,
+        /// Transcribe a variant from `layout.variants`, instead of the primary translation
+        #[arg(long)]
+        lang: Option<String>,
+        /// Without an id, choose from every untranscribed post (default if output is a terminal)
+        #[arg(short, long, overrides_with = "first")]
+        interactive: bool,
+        /// Without an id, use the first untranscribed post instead of choosing
+        #[arg(long)]
+        first: bool,
     },
 
     /// Recreate an existing post, given an id
@@ -72,6 +205,25 @@ pub enum Command {
     Revise {
         /// Id of the post to recreate
         id: Option<String>,
+        /// Revise every post which has not been revised, one after another.
+        /// Images are only regenerated if their source, icon, or watermark changed
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+        /// Generate image with a new random watermark, instead of keeping the watermark of post
+        #[arg(long)]
+        new_watermark: bool,
+        /// Without an id, choose from every unrevised post (default if output is a terminal)
+        #[arg(short, long, overrides_with = "first")]
+        interactive: bool,
+        /// Without an id, use the first unrevised post instead of choosing
+        #[arg(long)]
+        first: bool,
+    },
+
+    /// Continue an unfinished `revise` of a post, skipping steps which were already completed
+    Resume {
+        /// Id of the post
+        id: String,
     },
 
     /// Copy/paste images and transcript to upload destination
@@ -79,5 +231,589 @@ pub enum Command {
     Upload {
         /// Id of the post to upload
         id: String,
+        /// Publish directly to a target set in config, instead of pasting
+        #[arg(long, value_enum)]
+        target: Option<PublishTarget>,
+        /// Upload a variant from `layout.variants`, instead of the primary translation
+        #[arg(long)]
+        lang: Option<String>,
+        /// Upload even if post has not been approved with `review`
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Compare image of an old post against its regenerated version, after `revise`
+    Diff {
+        /// Id of the revised post
+        id: String,
+    },
+
+    /// Change the id of an existing post, in all directories
+    Rename {
+        /// Current id of the post
+        old_id: String,
+        /// New id for the post (must not already exist)
+        new_id: String,
+    },
+
+    /// Convert existing transcripts for publishing
+    Transcript {
+        #[command(subcommand)]
+        command: TranscriptCommand,
+    },
+
+    /// Export the svg file of a completed post as its translated image
+    Render {
+        /// Id of the post
+        id: String,
+        /// Render a variant from `layout.variants`, instead of the primary translation
+        #[arg(long)]
+        lang: Option<String>,
+    },
+
+    /// Render each completed post whenever its svg file is saved, printing a log
+    Watch,
+
+    /// Render transcript onto blank panels with the comic font, to check that text fits
+    Preview {
+        /// Id of the post
+        id: String,
+    },
+
+    /// Open images of a post, and print its date, title, stage, and start of transcript
+    ///
+    /// If someone else submitted a transcript with `serve`, also show its changes, and ask whether
+    /// to accept it. Then, if the post needs review, ask whether to approve it for upload
+    #[clap(group(ArgGroup::new("decision")))]
+    Review {
+        /// Id of the post
+        id: String,
+        /// Accept submitted transcript without asking
+        #[arg(long, conflicts_with = "reject_submission")]
+        accept_submission: bool,
+        /// Discard submitted transcript without asking
+        #[arg(long)]
+        reject_submission: bool,
+        /// Approve post for upload without asking
+        #[arg(long, group("decision"))]
+        approve: bool,
+        /// Reject post without asking, making it a draft again
+        #[arg(long, group("decision"))]
+        reject: bool,
+        /// Reason for approving or rejecting, recorded in props
+        #[arg(long, requires = "decision")]
+        comment: Option<String>,
+    },
+
+    /// Move transcripts submitted with `serve` into their posts, to be reviewed with `review`
+    PullQueue,
+
+    /// Edit translation notes of a post
+    Note {
+        /// Id of the post
+        id: String,
+    },
+
+    /// Mark a post as special, or edit its details
+    Special {
+        /// Id of the post
+        id: String,
+        /// Why post is special (kept from existing file if not given)
+        #[arg(long = "type", value_enum)]
+        kind: Option<SpecialKind>,
+    },
+
+    /// Open post directory, or a single file of a post
+    #[clap(alias = "o")]
+    Open {
+        /// Id of the post
+        id: String,
+        /// Name of file in post directory, eg. `esp.png`
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+
+    /// List posts, with their date, title, and first line of notes
+    #[clap(alias = "l")]
+    List {
+        /// Only list posts in this directory
+        #[arg(long, value_enum)]
+        stage: Option<Stage>,
+        /// Only list posts which this author contributed to
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// Move a generated or completed post into trash
+    Delete {
+        /// Id of the post to delete
+        id: String,
+    },
+
+    /// Losslessly recompress post images, and print space saved
+    Optimize {
+        /// Id of the post
+        #[arg(required_unless_present = "all")]
+        id: Option<String>,
+        /// Optimize every post, in every stage
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+    },
+
+    /// Replace duplicate images of completed and old posts with hard links, if still identical
+    Dedupe {
+        /// Only print posts which would be linked
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+
+    /// Compare every post against its checksum manifest, to detect corrupted or modified files
+    ///
+    /// Manifests are updated by each command which modifies a post
+    Verify,
+
+    /// Show commits which changed a post, if location is a git repository
+    Log {
+        /// Id of the post
+        id: String,
+    },
+
+    /// Saved versions of a post's transcript and title
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+
+    /// Manage deleted posts
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommand,
+    },
+
+    /// Snapshot posts and metadata, to roll back with `backup restore`
+    Backup {
+        #[command(subcommand)]
+        command: Option<BackupCommand>,
+        /// Directory of snapshots, instead of `backup.dir` in config
+        #[arg(long, global = true)]
+        to: Option<PathBuf>,
+        /// Number of snapshots to keep, removing the oldest, instead of `backup.keep` in config
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+
+    /// Plan publishing dates of posts
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommand,
+    },
+
+    /// Log of every upload, with its target and time
+    Uploads {
+        #[command(subcommand)]
+        command: UploadsCommand,
+    },
+
+    /// Suggest what to work on next
+    #[clap(alias = "n")]
+    #[clap(group(ArgGroup::new("next_kind").required(true)))]
+    Next {
+        /// Suggest a comic to make, which fills the next slot in the posting schedule
+        #[arg(long, group("next_kind"))]
+        plan: bool,
+        /// Suggest a completed post to upload, using `upload.policy` from config
+        #[arg(long, group("next_kind"))]
+        upload: bool,
+    },
+
+    /// Display a grid of comics within a range, to choose from at a glance
+    #[clap(group(ArgGroup::new("sheet_range").required(true).multiple(true)))]
+    Sheet {
+        /// Only include comics within a month+day range (regardless of year)
+        #[arg(short, long, group("sheet_range"), value_parser = clap::value_parser!(DateRange))]
+        range: Option<DateRange>,
+        /// Only include comics from a month, in YYYY-MM format
+        #[arg(short, long, group("sheet_range"), value_parser = clap::value_parser!(YearMonth))]
+        month: Option<YearMonth>,
+        /// Use images of completed posts, instead of source comics
+        #[arg(short, long)]
+        posts: bool,
+        /// Number of comics in each row
+        #[arg(short, long, default_value_t = 4)]
+        columns: u32,
+        /// Maximum number of comics, earliest first
+        #[arg(short, long, default_value_t = 60)]
+        limit: usize,
+    },
+
+    /// List runs of consecutive comics which have no post, longest first
+    Gaps {
+        /// Only include comics from this year
+        #[arg(short, long)]
+        year: Option<i32>,
+    },
+
+    /// List comics without a post, whose anniversary of publication is coming up
+    Anniversaries {
+        /// Number of days ahead to include, from today
+        #[arg(short, long, default_value_t = 7)]
+        days: u32,
+    },
+
+    /// Display a calendar of a month, marking which comics have no post, or a generated or
+    /// completed post
+    Calendar {
+        /// Year (YYYY) or month (YYYY-MM) to display
+        ///
+        /// Default: current month
+        #[arg(value_parser = clap::value_parser!(YearOrMonth))]
+        period: Option<YearOrMonth>,
+        /// Save a heatmap of the whole year as a PNG image, instead of printing
+        #[arg(long)]
+        image: Option<PathBuf>,
+    },
+
+    /// Generate a digest of posts completed in a month
+    Report {
+        /// Month to summarize, in YYYY-MM format
+        #[arg(long, value_parser = clap::value_parser!(YearMonth))]
+        month: YearMonth,
+        /// Output format of report file
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+        /// Directory to write report, images, and thumbnails into
+        ///
+        /// Default: `report.<month>` in temp directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Print an Atom feed of the most recently uploaded posts
+    Feed {
+        /// Maximum number of posts
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Generate a static website of completed posts
+    Site {
+        #[command(subcommand)]
+        command: SiteCommand,
+    },
+
+    /// Work queued to run later, eg. from `serve`
+    Jobs {
+        #[command(subcommand)]
+        command: JobsCommand,
+    },
+
+    /// Serve a JSON API over the archive, for browsing posts and queueing jobs remotely
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on. Use `0.0.0.0` to allow other devices (there is no authentication)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+    },
+
+    /// Summarize progress of the archive
+    #[clap(group(ArgGroup::new("summary").required(true).multiple(true)))]
+    Stats {
+        /// Average transcription time, words per post, and posts transcribed per week
+        #[arg(long, group("summary"))]
+        effort: bool,
+        /// Average time of each phase (directory scan, image decode, etc.), for each command run
+        /// with `--timings`
+        #[arg(long, group("summary"))]
+        perf: bool,
+        /// Transcripts and SVGs contributed by each author
+        #[arg(long, group("summary"))]
+        by_author: bool,
+    },
+
+    /// Copy comics from a directory or zip archive into source directory
+    ///
+    /// File names may be dated like `2024-01-31`, `20240131`, or `ga240131`.
+    /// Images in other formats are converted to PNG
+    Import {
+        /// Directory or zip archive of comic images
+        path: PathBuf,
+    },
+
+    /// Move source comics into a subdirectory for each year, eg. `source/2024/2024-01-31.png`
+    ///
+    /// Both layouts are always supported, but nested is faster to read with many comics
+    MigrateSource,
+
+    /// Generate missing or outdated thumbnails of post images
+    ///
+    /// Run with `nice` for minimal impact on other work
+    Thumbnails {
+        /// Keep running in the background, periodically checking for new or changed posts
+        #[arg(long)]
+        watch: bool,
+        /// Seconds between checks, with `--watch`
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Milliseconds to pause after generating each thumbnail
+        #[arg(long, default_value_t = 200)]
+        throttle: u64,
+    },
+
+    /// Report problems with posts or source comics
+    #[clap(group(ArgGroup::new("checks").required(true).multiple(true)))]
+    Check {
+        /// List dates which appear in more than one post
+        #[arg(long, group("checks"))]
+        duplicates: bool,
+        /// Find corrupt or misnamed source images, and missing dates
+        #[arg(long, group("checks"))]
+        source: bool,
+        /// Find misspelled words in transcripts, with `hunspell` or `aspell`
+        ///
+        /// Words in the location's `dictionary` file (one per line) are always accepted
+        #[arg(long, group("checks"))]
+        spelling: bool,
+        /// Find translations of terms which differ from the location's `glossary.toml`
+        #[arg(long, group("checks"))]
+        glossary: bool,
+    },
+
+    /// Interactively create a location, or fill in missing files and settings of an existing one
+    Setup,
+
+    /// Print a walkthrough of the full workflow, from choosing a comic to uploading its post
+    Guide,
+
+    /// Print version of this build
+    Version {
+        /// Also check whether a newer release is available
+        ///
+        /// Requires `self-update` feature
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Install the latest release, replacing this executable
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only print whether a newer release is available, and its release notes
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Remove leftover files from previous sessions
+    #[clap(group(ArgGroup::new("target").required(true).multiple(true)))]
+    Clean {
+        /// Remove stale files from temp directory (eg. unsaved transcripts)
+        #[arg(long, group("target"))]
+        tmp: bool,
+        /// Only remove files older than this many days
+        #[arg(long, default_value_t = 7)]
+        older_than: u64,
+    },
+}
+
+impl Command {
+    /// Whether the command modifies the location, so must not run concurrently with another
+    /// instance.
+    /// `show` only appends to recent dates file, so does not need to wait for long-running commands
+    /// like `transcribe`.
+    pub fn requires_lock(&self) -> bool {
+        match self {
+            Self::Make { .. }
+            | Self::Finish { .. }
+            | Self::Transcribe { .. }
+            | Self::Upload { .. }
+            | Self::Rename { .. }
+            | Self::Delete { .. }
+            | Self::Note { .. }
+            | Self::Special { .. }
+            | Self::Dedupe { .. }
+            | Self::Optimize { .. }
+            | Self::Render { .. }
+            | Self::Import { .. }
+            | Self::MigrateSource
+            | Self::Rate { .. }
+            | Self::Skip { .. }
+            | Self::PullQueue
+            | Self::Clean { .. } => true,
+            // May accept a submitted transcript
+            Self::Review { .. } => true,
+            // Rating is asked after showing
+            Self::Show { rate, .. } => *rate,
+            Self::Trash { command } => !matches!(command, TrashCommand::List),
+            Self::Backup { command, .. } => !matches!(command, Some(BackupCommand::List)),
+            Self::Schedule { command } => matches!(command, ScheduleCommand::Set { .. }),
+            Self::Jobs { command } => !matches!(command, JobsCommand::List),
+            // Locks only some steps, itself
+            Self::Revise { .. } | Self::Resume { .. } => false,
+            // Runs indefinitely, so would block every other command
+            Self::Watch => false,
+            // Also runs indefinitely, and only appends to jobs file
+            #[cfg(feature = "server")]
+            Self::Serve { .. } => false,
+            // Run before location is valid
+            Self::Setup | Self::Version { .. } | Self::Guide => false,
+            #[cfg(feature = "self-update")]
+            Self::SelfUpdate { .. } => false,
+            Self::Diff { .. }
+            | Self::Open { .. }
+            | Self::Transcript { .. }
+            | Self::History { .. }
+            | Self::Log { .. }
+            | Self::Verify
+            | Self::Preview { .. }
+            | Self::List { .. }
+            | Self::Next { .. }
+            | Self::Uploads { .. }
+            | Self::Gaps { .. }
+            | Self::Calendar { .. }
+            | Self::Anniversaries { .. }
+            | Self::Sheet { .. }
+            | Self::Report { .. }
+            | Self::Site { .. }
+            | Self::Feed { .. }
+            | Self::Stats { .. }
+            | Self::Thumbnails { .. }
+            | Self::Check { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TranscriptCommand {
+    /// Print transcript with title and date, in a format for another site
+    Export {
+        /// Id of the post
+        id: String,
+        #[arg(short, long, value_enum, default_value_t = TranscriptFormat::Plain)]
+        format: TranscriptFormat,
+        /// File to write to, instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// List saved versions, oldest first
+    Show {
+        /// Id of the post
+        id: String,
+    },
+    /// Print differences between two versions
+    Diff {
+        /// Id of the post
+        id: String,
+        /// Number of older version, from `history show`
+        old: usize,
+        /// Number of newer version, from `history show`
+        new: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TrashCommand {
+    /// List posts in trash, oldest first
+    List,
+    /// Move a post out of trash, back to its original directory
+    Restore {
+        /// Name of trash entry, or post id to restore most recently deleted
+        name: String,
+    },
+    /// Permanently remove all posts in trash
+    Empty,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BackupCommand {
+    /// List snapshots, oldest first
+    List,
+    /// Replace posts and metadata with those of a snapshot. Current state is backed up first.
+    Restore {
+        /// Timestamp of snapshot, from `backup list`
+        timestamp: String,
     },
 }
+
+#[derive(Debug, Subcommand)]
+pub enum UploadsCommand {
+    /// List recorded uploads, oldest first
+    List {
+        /// Only list uploads of this post
+        id: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleCommand {
+    /// Set the publish date of a post
+    Set {
+        /// Id of the post to schedule
+        id: String,
+        /// Date to publish the post
+        #[arg(value_parser = parse_date, allow_hyphen_values = true)]
+        date: NaiveDate,
+    },
+    /// List scheduled posts, soonest first
+    List,
+    /// Show posts which are due today (or overdue)
+    Next,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum JobsCommand {
+    /// List queued jobs, in the order they will run
+    List,
+    /// Run every queued job in order, removing each once it succeeds
+    Run,
+    /// Remove every queued job, without running it
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SiteCommand {
+    /// Write pages for each post and year, with images and an Atom feed, into a directory
+    Build {
+        /// Directory to write site into, which may already contain a previous build
+        output: PathBuf,
+    },
+}
+
+/// ISO date, or a date relative to today
+fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+    match input {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - Days::new(1)),
+        _ => (),
+    }
+
+    if let Some(weekday) = input.strip_prefix("last-") {
+        let weekday: Weekday = weekday
+            .parse()
+            .map_err(|_| format!("Invalid weekday `{}`", weekday))?;
+        // Never today, even if it is the same weekday
+        let days_since =
+            (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday() - 1) % 7
+                + 1;
+        return Ok(today - Days::new(days_since as u64));
+    }
+
+    if let Some(offset) = input.strip_suffix('d') {
+        if let Ok(offset) = offset.parse::<i64>() {
+            let days = Days::new(offset.unsigned_abs());
+            let date = if offset < 0 {
+                today.checked_sub_days(days)
+            } else {
+                today.checked_add_days(days)
+            };
+            return date.ok_or_else(|| "Date offset is out of range".to_string());
+        }
+    }
+
+    NaiveDate::parse_from_str(input, "%Y-%m-%d").map_err(|error| error.to_string())
+}
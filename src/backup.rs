@@ -0,0 +1,255 @@
+use crate::location::Location;
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use chrono::{Local, NaiveDateTime};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+/// Suffix of snapshot while it is being written, so an interrupted backup is never restored
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Directory of a backup, named by its timestamp, with the same structure as the location
+#[derive(Debug)]
+pub struct Snapshot {
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub path: PathBuf,
+}
+
+/// `dir` (eg. from `--to`) takes precedence over `backup.dir` in config, which is relative to
+/// location
+pub fn snapshots_dir(location: &Location, dir: Option<&Path>) -> PathBuf {
+    if let Some(dir) = dir {
+        return dir.to_path_buf();
+    }
+    match &location.config().backup.dir {
+        Some(dir) => location.base_dir().join(dir),
+        None => location.backups_dir(),
+    }
+}
+
+/// Sorted by time of creation, oldest first. Skips unfinished snapshots and other files.
+pub fn list(dir: &Path) -> Result<Vec<Snapshot>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| "Reading backups directory")? {
+        let path = entry.with_context(|| "Reading backups directory")?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let Ok(created_at) = NaiveDateTime::parse_from_str(&name, TIMESTAMP_FORMAT) else {
+            continue;
+        };
+        if path.is_dir() {
+            snapshots.push(Snapshot {
+                name,
+                created_at,
+                path,
+            });
+        }
+    }
+    snapshots.sort_by_key(|snapshot| snapshot.created_at);
+    Ok(snapshots)
+}
+
+/// Copies posts and metadata files into a new snapshot. Files which are unchanged since the
+/// previous snapshot are hard links to it, so only changed files take more space. Files are never
+/// linked to the location itself, since some are written in place.
+pub fn create(location: &Location, dir: &Path) -> Result<Snapshot> {
+    fs::create_dir_all(dir).with_context(|| "Creating backups directory")?;
+    let previous = list(dir)?.pop();
+
+    // Names only have a resolution of one second, eg. when restoring just after a backup
+    let (created_at, name, path) = loop {
+        let created_at = Local::now().naive_local();
+        let name = created_at.format(TIMESTAMP_FORMAT).to_string();
+        let path = dir.join(&name);
+        if !path.exists() {
+            break (created_at, name, path);
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    let partial_path = dir.join(format!("{}{}", name, PARTIAL_SUFFIX));
+    if partial_path.exists() {
+        fs::remove_dir_all(&partial_path).with_context(|| "Removing unfinished backup")?;
+    }
+
+    for relative in backed_up_paths(location) {
+        let source = location.base_dir().join(&relative);
+        if !source.exists() {
+            continue;
+        }
+        let previous = previous
+            .as_ref()
+            .map(|previous| previous.path.join(&relative));
+        let destination = partial_path.join(&relative);
+        fs::create_dir_all(
+            destination
+                .parent()
+                .expect("backed up path should have parent"),
+        )
+        .with_context(|| "Creating backup directory")?;
+        copy_tree(&source, &destination, previous.as_deref())
+            .with_context(|| format!("Backing up {:?}", relative))?;
+    }
+    fs::rename(&partial_path, &path).with_context(|| "Finishing backup")?;
+
+    Ok(Snapshot {
+        name,
+        created_at,
+        path,
+    })
+}
+
+/// Removes oldest snapshots, until only `keep` remain. Returns removed snapshots.
+pub fn prune(dir: &Path, keep: usize) -> Result<Vec<Snapshot>> {
+    let mut snapshots = list(dir)?;
+    let excess = snapshots.len().saturating_sub(keep);
+    let removed: Vec<Snapshot> = snapshots.drain(..excess).collect();
+    for snapshot in &removed {
+        fs::remove_dir_all(&snapshot.path)
+            .with_context(|| format!("Removing backup {:?}", snapshot.path))?;
+    }
+    Ok(removed)
+}
+
+/// Replaces posts and metadata files of location with those of the snapshot. Files which did not
+/// exist when the snapshot was made are removed. Duplicate images linked with `dedupe` are
+/// restored as separate copies. Symlinks (eg. posts directory on another drive) are kept, and
+/// their target is restored instead.
+pub fn restore(location: &Location, snapshot: &Snapshot) -> Result<()> {
+    fs::create_dir_all(location.temp_dir()).with_context(|| "Creating temp directory")?;
+    for relative in backed_up_paths(location) {
+        let saved = snapshot.path.join(&relative);
+        let live = location.base_dir().join(&relative);
+        let link_target = match fs::symlink_metadata(&live) {
+            Ok(metadata) if metadata.file_type().is_symlink() => Some(
+                fs::read_link(&live).with_context(|| format!("Reading symlink {:?}", relative))?,
+            ),
+            _ => None,
+        };
+
+        // Moved aside first, so location is not left without the file if copying fails.
+        // Target of symlink may be on another filesystem, so it is moved aside next to itself.
+        let (live, aside) = match link_target {
+            Some(target) => {
+                let live = live
+                    .parent()
+                    .expect("backed up path should have parent")
+                    .join(target);
+                let mut aside = live.clone().into_os_string();
+                aside.push(".restore");
+                (live, PathBuf::from(aside))
+            }
+            None => {
+                let aside = location.temp_dir().join(format!(
+                    "restore.{}",
+                    relative.to_string_lossy().replace(['/', '\\'], ".")
+                ));
+                (live, aside)
+            }
+        };
+        if aside.exists() {
+            remove_path(&aside).with_context(|| "Removing previous restored file")?;
+        }
+        if live.exists() {
+            fs::rename(&live, &aside).with_context(|| format!("Moving aside {:?}", relative))?;
+        }
+        if saved.exists() {
+            if let Some(parent) = live.parent() {
+                fs::create_dir_all(parent).with_context(|| "Creating restored directory")?;
+            }
+            if let Err(error) = copy_tree(&saved, &live, None) {
+                let _ = remove_path(&live);
+                let _ = fs::rename(&aside, &live);
+                return Err(error).with_context(|| format!("Restoring {:?}", relative));
+            }
+        }
+        if aside.exists() {
+            remove_path(&aside).with_context(|| format!("Removing replaced {:?}", relative))?;
+        }
+    }
+    Ok(())
+}
+
+/// Paths relative to location. Not every path exists.
+fn backed_up_paths(location: &Location) -> Vec<PathBuf> {
+    [
+        location.posts_dir(),
+        location.old_dir(),
+        location.submissions_dir(),
+        location.hooks_dir(),
+        location.config_file(),
+        location.schedule_file(),
+        location.uploads_file(),
+        location.ratings_file(),
+        location.skips_file(),
+        location.watermarks_file(),
+        location.jobs_file(),
+        location.claims_file(),
+        location.glossary_file(),
+        location.dictionary_file(),
+        location.caption_template_file(),
+    ]
+    .into_iter()
+    .filter_map(|path| {
+        path.strip_prefix(location.base_dir())
+            .ok()
+            .map(Path::to_path_buf)
+    })
+    .collect()
+}
+
+/// Hard links each file to the same file in `previous` if it has the same size and modification
+/// time, otherwise copies it. Modification time is kept, so the next snapshot can compare it.
+/// Symlinks are followed, so a symlinked directory is copied with its contents.
+fn copy_tree(source: &Path, destination: &Path, previous: Option<&Path>) -> io::Result<()> {
+    let metadata = fs::metadata(source)?;
+    if metadata.is_dir() {
+        fs::create_dir(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let previous = previous.map(|previous| previous.join(entry.file_name()));
+            copy_tree(
+                &entry.path(),
+                &destination.join(entry.file_name()),
+                previous.as_deref(),
+            )?;
+        }
+        return Ok(());
+    }
+
+    if let Some(previous) = previous {
+        if is_unchanged(&metadata, previous)? && fs::hard_link(previous, destination).is_ok() {
+            return Ok(());
+        }
+    }
+    // Also copies permissions
+    fs::copy(source, destination)?;
+    File::open(destination)?.set_modified(metadata.modified()?)
+}
+
+fn is_unchanged(metadata: &fs::Metadata, previous: &Path) -> io::Result<bool> {
+    let Ok(previous) = fs::metadata(previous) else {
+        return Ok(false);
+    };
+    Ok(previous.is_file()
+        && previous.len() == metadata.len()
+        && previous.modified()? == metadata.modified()?)
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -0,0 +1,145 @@
+use crate::range::YearMonth;
+
+use chrono::{Datelike as _, NaiveDate};
+use image::{Rgb, RgbImage};
+
+/// Size of each day in heatmap, in pixels
+const CELL_SIZE: u32 = 12;
+const CELL_GAP: u32 = 3;
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// How much work has been done for the comic of a day
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Coverage {
+    NoSource,
+    SourceOnly,
+    Generated,
+    /// In `posts`, whether uploaded or not
+    Completed,
+}
+
+impl Coverage {
+    const ALL: [Self; 4] = [
+        Self::NoSource,
+        Self::SourceOnly,
+        Self::Generated,
+        Self::Completed,
+    ];
+
+    /// Shown after day number, so coverage is visible without color
+    fn marker(self) -> char {
+        match self {
+            Self::NoSource => ' ',
+            Self::SourceOnly => '.',
+            Self::Generated => '+',
+            Self::Completed => '*',
+        }
+    }
+
+    /// ANSI SGR parameters
+    fn color(self) -> &'static str {
+        match self {
+            Self::NoSource => "2",
+            Self::SourceOnly => "31",
+            Self::Generated => "33",
+            Self::Completed => "32",
+        }
+    }
+
+    fn rgb(self) -> Rgb<u8> {
+        match self {
+            Self::NoSource => Rgb([235, 237, 240]),
+            Self::SourceOnly => Rgb([240, 160, 150]),
+            Self::Generated => Rgb([155, 233, 168]),
+            Self::Completed => Rgb([33, 110, 57]),
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::NoSource => "no source comic",
+            Self::SourceOnly => "no post",
+            Self::Generated => "generated",
+            Self::Completed => "completed",
+        }
+    }
+}
+
+/// Weeks start on Monday
+pub fn print_month(month: YearMonth, color: bool, coverage: impl Fn(NaiveDate) -> Coverage) {
+    let first_day = month.first_day();
+    println!("{:^27}", first_day.format("%B %Y").to_string());
+    println!("Mo  Tu  We  Th  Fr  Sa  Su");
+
+    let mut line = "    ".repeat(first_day.weekday().num_days_from_monday() as usize);
+    for date in first_day
+        .iter_days()
+        .take_while(|date| month.contains(*date))
+    {
+        line += &format_day(date, coverage(date), color);
+        if date.weekday() == chrono::Weekday::Sun {
+            println!("{}", line.trim_end());
+            line.clear();
+        } else {
+            line += " ";
+        }
+    }
+    if !line.is_empty() {
+        println!("{}", line.trim_end());
+    }
+}
+
+pub fn print_legend(color: bool) {
+    let entries: Vec<String> = Coverage::ALL
+        .into_iter()
+        .map(|coverage| {
+            let sample = paint(&format!("##{}", coverage.marker()), coverage, color);
+            format!("{} {}", sample, coverage.description())
+        })
+        .collect();
+    println!("{}", entries.join("   "));
+}
+
+fn format_day(date: NaiveDate, coverage: Coverage, color: bool) -> String {
+    paint(
+        &format!("{:>2}{}", date.day(), coverage.marker()),
+        coverage,
+        color,
+    )
+}
+
+fn paint(text: &str, coverage: Coverage, color: bool) -> String {
+    if !color {
+        return text.to_string();
+    }
+    format!("\x1b[{}m{}\x1b[0m", coverage.color(), text)
+}
+
+/// One column per week, and one row per weekday from Monday, like a GitHub contribution graph
+pub fn render_heatmap(year: i32, coverage: impl Fn(NaiveDate) -> Coverage) -> RgbImage {
+    let first_day = NaiveDate::from_ymd_opt(year, 1, 1).expect("year should be valid");
+    let offset = first_day.weekday().num_days_from_monday();
+    let days: Vec<NaiveDate> = first_day
+        .iter_days()
+        .take_while(|date| date.year() == year)
+        .collect();
+    let weeks = (offset + days.len() as u32).div_ceil(7);
+
+    let stride = CELL_SIZE + CELL_GAP;
+    let mut image =
+        RgbImage::from_pixel(weeks * stride + CELL_GAP, 7 * stride + CELL_GAP, BACKGROUND);
+    for (i, date) in days.into_iter().enumerate() {
+        let index = offset + i as u32;
+        let (left, top) = (
+            (index / 7) * stride + CELL_GAP,
+            (index % 7) * stride + CELL_GAP,
+        );
+        let color = coverage(date).rgb();
+        for y in top..top + CELL_SIZE {
+            for x in left..left + CELL_SIZE {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+    image
+}
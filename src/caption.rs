@@ -0,0 +1,95 @@
+use crate::config::Variant;
+use crate::constants::*;
+use crate::file;
+use crate::location::Location;
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+
+/// Used when location has no caption template file
+const DEFAULT_TEMPLATE: &str = "{{title}}\n\n{{transcript}}";
+
+/// Caption of a post, from the location's template.
+///
+/// Template variables are `{{title}}`, `{{date}}`, `{{url}}` (of original strip), `{{watermark}}`,
+/// and `{{transcript}}` (of the variant). Missing files give empty values.
+pub fn render_caption(location: &Location, post_path: &Path, variant: &Variant) -> Result<String> {
+    let template_path = location.caption_template_file();
+    let template = if template_path.exists() {
+        fs::read_to_string(&template_path).with_context(|| "Reading caption template file")?
+    } else {
+        DEFAULT_TEMPLATE.to_string()
+    };
+
+    let date = read_date(post_path)?;
+    let variables = [
+        ("title", read_optional(&post_path.join(post_file::TITLE))?),
+        (
+            "date",
+            date.map(|date| date.to_string()).unwrap_or_default(),
+        ),
+        ("url", date.map(original_url).unwrap_or_default()),
+        (
+            "watermark",
+            read_optional(&post_path.join(post_file::WATERMARK))?,
+        ),
+        (
+            "transcript",
+            read_optional(&post_path.join(&variant.transcript))?,
+        ),
+    ];
+
+    Ok(substitute(&template, &variables).trim().to_string())
+}
+
+/// Single pass, so values (eg. a title containing `{{transcript}}`) are never expanded themselves.
+/// Unknown variables are kept as-is.
+fn substitute(template: &str, variables: &[(&str, String)]) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output += &rest[..start];
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = &after[..end];
+            let (_, value) = variables.iter().find(|(key, _)| *key == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                output += value.trim();
+                rest = &after[end + 2..];
+            }
+            None => {
+                output += "{{";
+                rest = after;
+            }
+        }
+    }
+    output += rest;
+    output
+}
+
+fn original_url(date: NaiveDate) -> String {
+    date.format("https://www.gocomics.com/garfield/%Y/%m/%d")
+        .to_string()
+}
+
+fn read_date(post_path: &Path) -> Result<Option<NaiveDate>> {
+    let path = post_path.join(post_file::DATE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = fs::File::open(path).with_context(|| "Opening date file")?;
+    Ok(Some(file::read_last_line_as_date(file)?))
+}
+
+fn read_optional(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    file::read_text(path)
+}
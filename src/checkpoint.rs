@@ -0,0 +1,79 @@
+use crate::error::Error;
+use crate::location::Location;
+
+use std::fs;
+use std::io::Write as _;
+
+use anyhow::{bail, Context as _, Result};
+
+/// Completed steps of a multi-step flow (eg. `revise`) for a post, so it can be resumed if any step
+/// fails. Saved as `checkpoints/<id>`, with name of flow on first line, then each completed step.
+#[derive(Debug)]
+pub struct Checkpoint {
+    id: String,
+    flow: String,
+    steps: Vec<String>,
+}
+
+impl Checkpoint {
+    /// Fails if flow was already started for post, and was not completed
+    pub fn start(location: &Location, flow: &str, id: &str) -> Result<Self> {
+        if let Some(existing) = Self::load(location, id)? {
+            bail!(Error::usage(format!(
+                "Post already has an unfinished `{}`. Continue it with `resume`.",
+                existing.flow
+            )));
+        }
+        let path = location.checkpoint_file(id);
+        fs::create_dir_all(path.parent().expect("checkpoint file should have parent"))
+            .with_context(|| "Creating checkpoints directory")?;
+        fs::write(&path, format!("{}\n", flow)).with_context(|| "Writing checkpoint file")?;
+        Ok(Self {
+            id: id.to_string(),
+            flow: flow.to_string(),
+            steps: Vec::new(),
+        })
+    }
+
+    pub fn load(location: &Location, id: &str) -> Result<Option<Self>> {
+        let path = location.checkpoint_file(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path).with_context(|| "Reading checkpoint file")?;
+        let mut lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let flow = lines.next().with_context(|| "Checkpoint file is empty")?;
+        Ok(Some(Self {
+            id: id.to_string(),
+            flow: flow.to_string(),
+            steps: lines.map(String::from).collect(),
+        }))
+    }
+
+    pub fn flow(&self) -> &str {
+        &self.flow
+    }
+
+    pub fn is_done(&self, step: &str) -> bool {
+        self.steps.iter().any(|done| done == step)
+    }
+
+    pub fn complete(&mut self, location: &Location, step: &str) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(location.checkpoint_file(&self.id))
+            .with_context(|| "Opening checkpoint file")?;
+        writeln!(file, "{}", step).with_context(|| "Writing to checkpoint file")?;
+        self.steps.push(step.to_string());
+        Ok(())
+    }
+
+    /// Removes checkpoint once every step is complete
+    pub fn finish(self, location: &Location) -> Result<()> {
+        fs::remove_file(location.checkpoint_file(&self.id))
+            .with_context(|| "Removing checkpoint file")
+    }
+}
@@ -0,0 +1,73 @@
+use crate::constants::*;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use sha2::{Digest as _, Sha256};
+
+/// File in a post directory which differs from its manifest
+#[derive(Debug)]
+pub enum Mismatch {
+    Changed(String),
+    Missing(String),
+    Unrecorded(String),
+}
+
+/// Rewrites manifest of post, with hashes of every file in post directory.
+/// Format is compatible with `sha256sum --check`.
+pub fn update(post_path: &Path) -> Result<()> {
+    let mut contents = String::new();
+    for (file_name, hash) in hash_files(post_path)? {
+        contents += &format!("{}  {}\n", hash, file_name);
+    }
+    fs::write(post_path.join(post_file::CHECKSUMS), contents)
+        .with_context(|| "Writing checksum manifest")
+}
+
+/// Returns `None` if post has no manifest
+pub fn verify(post_path: &Path) -> Result<Option<Vec<Mismatch>>> {
+    let manifest_path = post_path.join(post_file::CHECKSUMS);
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let manifest =
+        fs::read_to_string(&manifest_path).with_context(|| "Reading checksum manifest")?;
+    let mut expected: BTreeMap<String, String> = manifest
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(hash, file_name)| (file_name.to_string(), hash.to_string()))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for (file_name, hash) in hash_files(post_path)? {
+        match expected.remove(&file_name) {
+            Some(expected_hash) if expected_hash == hash => (),
+            Some(_) => mismatches.push(Mismatch::Changed(file_name)),
+            None => mismatches.push(Mismatch::Unrecorded(file_name)),
+        }
+    }
+    mismatches.extend(expected.into_keys().map(Mismatch::Missing));
+    Ok(Some(mismatches))
+}
+
+/// Regular files directly in post directory, excluding manifest itself, and a submitted
+/// transcript which has not been reviewed
+fn hash_files(post_path: &Path) -> Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for entry in fs::read_dir(post_path).with_context(|| "Reading post directory")? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name == post_file::CHECKSUMS || file_name == post_file::PENDING_TRANSCRIPT {
+            continue;
+        }
+        let bytes = fs::read(entry.path())
+            .with_context(|| format!("Reading `{}` file for checksum", file_name))?;
+        hashes.insert(file_name, format!("{:x}", Sha256::digest(&bytes)));
+    }
+    Ok(hashes)
+}
@@ -0,0 +1,99 @@
+use crate::commands;
+use crate::file;
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context as _, Result};
+use fs2::FileExt as _;
+
+/// Exit code conventionally used for termination by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static TASKS: Mutex<Vec<(usize, Task)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub enum Task {
+    /// Kill image viewer (or other process) by name
+    KillProcess(String),
+    RemoveFile(PathBuf),
+    /// Remove file only if it still matches its initial contents, so any unsaved work is kept
+    RemoveUnchangedFile {
+        path: PathBuf,
+        contents: String,
+    },
+    /// Clear PID from lock file and release advisory lock held on it. Lock is also released when
+    /// file is closed.
+    Unlock(File),
+}
+
+/// Runs its task when dropped, or when the program is interrupted
+#[must_use = "task runs as soon as guard is dropped"]
+pub struct Guard {
+    id: usize,
+}
+
+pub fn init_signal_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        let tasks = std::mem::take(&mut *lock_tasks());
+        // Most recently registered first, like with normal drop order
+        for (_, task) in tasks.into_iter().rev() {
+            task.run();
+        }
+        process::exit(INTERRUPTED_EXIT_CODE);
+    })
+    .with_context(|| "Setting interrupt signal handler")
+}
+
+pub fn register(task: Task) -> Guard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    lock_tasks().push((id, task));
+    Guard { id }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let task = {
+            let mut tasks = lock_tasks();
+            let Some(index) = tasks.iter().position(|(id, _)| *id == self.id) else {
+                // Already run by signal handler
+                return;
+            };
+            tasks.remove(index).1
+        };
+        task.run();
+    }
+}
+
+impl Task {
+    /// Errors are ignored, as there is nothing left to do about them
+    fn run(self) {
+        match self {
+            Self::KillProcess(name) => {
+                let _ = commands::kill_process_name(&name);
+            }
+            Self::RemoveFile(path) => {
+                let _ = fs::remove_file(&path);
+            }
+            Self::RemoveUnchangedFile { path, contents } => {
+                if let Ok(true) = file::file_matches_string(&path, &contents) {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+            Self::Unlock(file) => {
+                // Otherwise PID of a still running process would look like an older version
+                let _ = file.set_len(0);
+                let _ = file.unlock();
+            }
+        }
+    }
+}
+
+fn lock_tasks() -> std::sync::MutexGuard<'static, Vec<(usize, Task)>> {
+    // A panic while holding the lock should not prevent cleanup
+    TASKS.lock().unwrap_or_else(|error| error.into_inner())
+}
@@ -0,0 +1,138 @@
+use crate::commands;
+use crate::error::Error;
+
+use std::env;
+use std::fs::File;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardBackend {
+    /// Detect from display server and installed programs
+    #[default]
+    Auto,
+    WlCopy,
+    Xclip,
+    /// Text only
+    Xsel,
+    /// Requires `arboard` feature
+    Arboard,
+}
+
+impl ClipboardBackend {
+    /// Resolves `Auto` into a specific backend
+    pub fn resolve(self) -> Result<Self> {
+        if self != Self::Auto {
+            return Ok(self);
+        }
+        if env::var_os("WAYLAND_DISPLAY").is_some() && commands::is_program_installed("wl-copy") {
+            return Ok(Self::WlCopy);
+        }
+        if env::var_os("DISPLAY").is_some() {
+            if commands::is_program_installed("xclip") {
+                return Ok(Self::Xclip);
+            }
+            if commands::is_program_installed("xsel") {
+                return Ok(Self::Xsel);
+            }
+        }
+        if cfg!(feature = "arboard") {
+            return Ok(Self::Arboard);
+        }
+        bail!("No clipboard backend available. Install `wl-copy`, `xclip`, or `xsel`, or enable the `arboard` feature.");
+    }
+}
+
+/// PNG files are copied as images, anything else as text
+pub fn copy_file(backend: ClipboardBackend, path: &Path) -> Result<()> {
+    let is_image = path.extension().is_some_and(|extension| extension == "png");
+
+    let status = match backend.resolve()? {
+        ClipboardBackend::Auto => unreachable!("backend should be resolved"),
+        ClipboardBackend::WlCopy => Command::new("wl-copy")
+            .stdin(open_file(path)?)
+            .status()
+            .with_context(|| "Running `wl-copy`")?,
+        ClipboardBackend::Xclip => {
+            let mut command = Command::new("xclip");
+            command.args(["-selection", "clipboard"]);
+            if is_image {
+                command.args(["-target", "image/png"]);
+            }
+            command
+                .stdin(open_file(path)?)
+                .status()
+                .with_context(|| "Running `xclip`")?
+        }
+        ClipboardBackend::Xsel => {
+            if is_image {
+                bail!("Clipboard backend `xsel` does not support images");
+            }
+            Command::new("xsel")
+                .args(["--clipboard", "--input"])
+                .stdin(open_file(path)?)
+                .status()
+                .with_context(|| "Running `xsel`")?
+        }
+        ClipboardBackend::Arboard => return copy_file_arboard(path, is_image),
+    };
+
+    if !status.success() {
+        bail!(Error::external_tool(
+            "Clipboard program did not exit successfully"
+        ));
+    }
+    Ok(())
+}
+
+fn open_file(path: &Path) -> Result<File> {
+    File::open(path).with_context(|| "Opening file")
+}
+
+#[cfg(feature = "arboard")]
+fn copy_file_arboard(path: &Path, is_image: bool) -> Result<()> {
+    use std::borrow::Cow;
+    use std::cell::RefCell;
+
+    thread_local! {
+        // Kept alive until program exits, as clipboard contents are served by this process
+        static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+    }
+
+    CLIPBOARD.with(|clipboard| {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().with_context(|| "Opening clipboard")?);
+        }
+        let clipboard = clipboard.as_mut().expect("clipboard should be initialized");
+
+        if is_image {
+            let image = image::open(path)
+                .with_context(|| "Opening image")?
+                .to_rgba8();
+            let image_data = arboard::ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: Cow::from(image.into_raw()),
+            };
+            clipboard
+                .set_image(image_data)
+                .with_context(|| "Copying image to clipboard")?;
+        } else {
+            let text = std::fs::read_to_string(path).with_context(|| "Reading file")?;
+            clipboard
+                .set_text(text)
+                .with_context(|| "Copying text to clipboard")?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(feature = "arboard"))]
+fn copy_file_arboard(_path: &Path, _is_image: bool) -> Result<()> {
+    bail!("Clipboard backend `arboard` requires the `arboard` feature to be enabled");
+}
@@ -1,57 +1,501 @@
-use std::ffi::OsStr;
+use crate::clipboard::{self, ClipboardBackend};
+use crate::error::Error;
+use crate::timings::{self, Phase};
+use crate::ui::Prompter;
+
+use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Write as _;
-use std::fs::File;
-use std::path::Path;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
+use std::sync::OnceLock;
 use std::thread;
 use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+static PROCESS_REGISTRY: OnceLock<PathBuf> = OnceLock::new();
+
+/// Opens files with their default program
+#[cfg(target_os = "macos")]
+pub const DEFAULT_OPENER: &str = "open";
+#[cfg(windows)]
+pub const DEFAULT_OPENER: &str = "start";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const DEFAULT_OPENER: &str = "xdg-open";
+
+/// Used to arrange image viewer windows, and to switch to upload destination
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowManager {
+    Hyprland,
+    /// Windows are left where they open, and pasting into upload destination is manual
+    None,
+}
+
+impl Default for WindowManager {
+    fn default() -> Self {
+        if cfg!(target_os = "linux") {
+            Self::Hyprland
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// External programs run by actions, so they can be replaced (eg. with a fake in tests)
+pub trait SystemCommands {
+    fn spawn_image_viewer(
+        &self,
+        program: &str,
+        paths: &[PathBuf],
+        name: &str,
+        fullscreen: bool,
+        slideshow_delay: Option<u64>,
+    ) -> Result<()>;
+    fn kill_process_name(&self, name: &str) -> Result<()>;
+    fn setup_image_viewer_window(
+        &self,
+        program: &str,
+        paths: &[PathBuf],
+        window_name: &str,
+    ) -> Result<()>;
+    fn open_editor(&self, program: &str, path: &Path) -> Result<()>;
+    fn spawn_detached(&self, program: &str, path: &Path) -> Result<()>;
+    fn toggle_upload_destination(&self) -> Result<()>;
+    fn upload_file(
+        &self,
+        prompter: &dyn Prompter,
+        clipboard: ClipboardBackend,
+        path: &Path,
+    ) -> Result<()>;
+
+    /// Whether windows are actually opened, so they need to be cleaned up
+    fn has_gui(&self) -> bool {
+        true
+    }
+}
+
+/// Runs programs for real, with the functions of this module
+pub struct RealSystem {
+    pub window_manager: WindowManager,
+}
+
+/// Prints paths instead of opening windows, for sessions without a display (eg. over SSH).
+/// Terminal editor still runs normally.
+pub struct HeadlessSystem;
 
-pub fn spawn_image_viewer(paths: &[impl AsRef<OsStr>], name: &str, fullscreen: bool) -> Result<()> {
-    let mut command = Command::new("swiv");
+impl SystemCommands for RealSystem {
+    fn spawn_image_viewer(
+        &self,
+        program: &str,
+        paths: &[PathBuf],
+        name: &str,
+        fullscreen: bool,
+        slideshow_delay: Option<u64>,
+    ) -> Result<()> {
+        spawn_image_viewer(program, paths, name, fullscreen, slideshow_delay)
+    }
+    fn kill_process_name(&self, name: &str) -> Result<()> {
+        kill_process_name(name)
+    }
+    fn setup_image_viewer_window(
+        &self,
+        program: &str,
+        paths: &[PathBuf],
+        window_name: &str,
+    ) -> Result<()> {
+        setup_image_viewer_window(program, paths, window_name, self.window_manager)
+    }
+    fn open_editor(&self, program: &str, path: &Path) -> Result<()> {
+        open_editor(program, path)
+    }
+    fn spawn_detached(&self, program: &str, path: &Path) -> Result<()> {
+        spawn_detached(program, path)
+    }
+    fn toggle_upload_destination(&self) -> Result<()> {
+        toggle_upload_destination(self.window_manager)
+    }
+    fn upload_file(
+        &self,
+        prompter: &dyn Prompter,
+        clipboard: ClipboardBackend,
+        path: &Path,
+    ) -> Result<()> {
+        upload_file(prompter, self.window_manager, clipboard, path)
+    }
+}
+
+impl SystemCommands for HeadlessSystem {
+    fn spawn_image_viewer(
+        &self,
+        _program: &str,
+        paths: &[PathBuf],
+        _name: &str,
+        _fullscreen: bool,
+        _slideshow_delay: Option<u64>,
+    ) -> Result<()> {
+        print_paths(paths);
+        Ok(())
+    }
+    fn kill_process_name(&self, _name: &str) -> Result<()> {
+        Ok(())
+    }
+    fn setup_image_viewer_window(
+        &self,
+        _program: &str,
+        paths: &[PathBuf],
+        _window_name: &str,
+    ) -> Result<()> {
+        print_paths(paths);
+        Ok(())
+    }
+    fn open_editor(&self, program: &str, path: &Path) -> Result<()> {
+        open_editor(program, path)
+    }
+    fn spawn_detached(&self, _program: &str, path: &Path) -> Result<()> {
+        println!("{}", path.display());
+        Ok(())
+    }
+    fn toggle_upload_destination(&self) -> Result<()> {
+        bail!(Error::external_tool(
+            "Uploading with clipboard requires a graphical session (try `--target`)"
+        ));
+    }
+    fn upload_file(
+        &self,
+        _prompter: &dyn Prompter,
+        _clipboard: ClipboardBackend,
+        _path: &Path,
+    ) -> Result<()> {
+        bail!(Error::external_tool(
+            "Uploading with clipboard requires a graphical session (try `--target`)"
+        ));
+    }
+    fn has_gui(&self) -> bool {
+        false
+    }
+}
+
+fn print_paths(paths: &[PathBuf]) {
+    for path in paths {
+        println!("{}", path.display());
+    }
+}
+
+/// Whether a Wayland or X11 display is available. Always `true` on macOS and Windows.
+pub fn has_display() -> bool {
+    if cfg!(any(target_os = "macos", windows)) {
+        return true;
+    }
+    env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_some()
+}
+
+/// Spawned image viewers are recorded in this file (as `<name> <pid>` lines), so they can later be
+/// killed by PID, instead of by matching command lines
+pub fn init_process_registry(path: PathBuf) {
+    PROCESS_REGISTRY
+        .set(path)
+        .expect("process registry should only be initialized once");
+}
+
+/// Viewer must accept `sxiv`-style options, unless it is `DEFAULT_OPENER`.
+/// Images opened with `DEFAULT_OPENER` are not fullscreen, and cannot be killed later.
+pub fn spawn_image_viewer(
+    program: &str,
+    paths: &[impl AsRef<OsStr>],
+    name: &str,
+    fullscreen: bool,
+    slideshow_delay: Option<u64>,
+) -> Result<()> {
+    if program == DEFAULT_OPENER {
+        for path in paths {
+            spawn_detached(program, path)?;
+        }
+        return Ok(());
+    }
+    let mut command = Command::new(program);
     if fullscreen {
         command.args([
             "-f", // Fullscreen
             "-s", "f", // Scale mode: fit
         ]);
     }
-    command
+    if let Some(delay) = slideshow_delay {
+        command.arg("-S").arg(delay.to_string()); // Slideshow delay in seconds
+    }
+    let child = command
         .args(["-N", name]) // Window name (so it can be killed later)
         .args(["-B", "#000000"]) // Background color
         .args(paths)
         .spawn()
-        .with_context(|| "Spawning image viewer")?;
+        .with_context(|| Error::external_tool("Spawning image viewer"))?;
+    register_process(name, child.id()).with_context(|| "Recording image viewer process")?;
     Ok(())
 }
 
 pub fn kill_process_name(name: &str) -> Result<()> {
-    Command::new("pkill")
-        .arg("--full")
-        .arg(name)
-        .status()
-        .with_context(|| "Killing image viewer")?;
-    // Non-zero exit means no process found; not necessarily an error
+    let Some(registry) = PROCESS_REGISTRY.get() else {
+        return kill_process_name_unchecked(name);
+    };
+    if !registry.exists() {
+        // Any running instances must be from before processes were tracked
+        kill_process_name_unchecked(name)?;
+        create_parent_dir(registry)?;
+        fs::write(registry, "").with_context(|| "Creating process registry file")?;
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(registry).with_context(|| "Reading process registry file")?;
+    let mut remaining = String::new();
+    for line in contents.lines() {
+        // Name may contain spaces, but PID cannot
+        let Some((entry_name, pid)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        if entry_name != name {
+            remaining += line;
+            remaining += "\n";
+            continue;
+        }
+        // PID may have been reused by an unrelated process
+        if let Ok(pid) = pid.parse() {
+            let system = refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]));
+            if let Some(process) = system.process(Pid::from_u32(pid)) {
+                if process.cmd().iter().any(|arg| arg == name) {
+                    process.kill();
+                }
+            }
+        }
+    }
+    fs::write(registry, remaining).with_context(|| "Writing process registry file")?;
+
     Ok(())
 }
 
-pub fn open_editor(path: impl AsRef<OsStr>) -> Result<()> {
-    let status = Command::new("nvim")
+fn register_process(name: &str, pid: u32) -> Result<()> {
+    let Some(registry) = PROCESS_REGISTRY.get() else {
+        return Ok(());
+    };
+    create_parent_dir(registry)?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(registry)?;
+    writeln!(file, "{} {}", name, pid)?;
+    Ok(())
+}
+
+/// Whether any process has this PID, on any platform
+pub fn is_pid_running(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    refresh_processes(ProcessesToUpdate::Some(&[pid]))
+        .process(pid)
+        .is_some()
+}
+
+/// Includes command lines, which are not refreshed by default
+fn refresh_processes(processes: ProcessesToUpdate) -> System {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        processes,
+        true,
+        ProcessRefreshKind::new().with_cmd(UpdateKind::Always),
+    );
+    system
+}
+
+fn create_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| "Creating parent directory")?;
+    }
+    Ok(())
+}
+
+/// May kill unrelated processes, whose command lines happen to contain `name` (like `pkill --full`)
+fn kill_process_name_unchecked(name: &str) -> Result<()> {
+    let own_pid = Pid::from_u32(process::id());
+    let system = refresh_processes(ProcessesToUpdate::All);
+    for (pid, process) in system.processes() {
+        let command_line = process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if *pid != own_pid && command_line.contains(name) {
+            process.kill();
+        }
+    }
+    Ok(())
+}
+
+/// `start` is a builtin of `cmd` on Windows, not a program
+fn program_command(program: &str) -> Command {
+    if cfg!(windows) && program == "start" {
+        let mut command = Command::new("cmd");
+        // Empty title, so a quoted path is not used as the title
+        command.args(["/C", "start", ""]);
+        return command;
+    }
+    Command::new(program)
+}
+
+pub fn open_editor(program: &str, path: impl AsRef<OsStr>) -> Result<()> {
+    let status = program_command(program)
         .arg(path)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .status()
-        .with_context(|| "Opening editor")?;
+        .with_context(|| Error::external_tool("Opening editor"))?;
     if !status.success() {
-        bail!("Editor did not exit successfully");
+        bail!(Error::external_tool("Editor did not exit successfully"));
     }
     Ok(())
 }
 
-/// Hyprland-specific functionality
-pub fn setup_image_viewer_window(paths: &[impl AsRef<OsStr>], window_name: &str) -> Result<()> {
-    spawn_image_viewer(paths, window_name, false)?;
+/// Program is not waited for, and its output is discarded
+pub fn spawn_detached(program: &str, path: impl AsRef<OsStr>) -> Result<()> {
+    program_command(program)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| Error::external_tool(format!("Spawning `{}`", program)))?;
+    Ok(())
+}
+
+/// Exports SVG file as PNG, scaled to `width`. Program must accept Inkscape 1.x options.
+pub fn render_svg(program: &str, svg_path: &Path, png_path: &Path, width: u32) -> Result<()> {
+    let mut command = Command::new(program);
+    command
+        .arg("--export-type=png")
+        .arg(format!("--export-width={}", width))
+        .arg("--export-filename")
+        .arg(png_path)
+        .arg(svg_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit());
+    let status = timings::time(Phase::External, || command.status())
+        .with_context(|| Error::external_tool(format!("Running `{}`", program)))?;
+    if !status.success() {
+        bail!(Error::external_tool(format!(
+            "`{}` did not exit successfully",
+            program
+        )));
+    }
+    Ok(())
+}
+
+/// Field of a `multipart/form-data` request
+pub enum FormField {
+    Text { name: String, value: String },
+    File { name: String, path: PathBuf },
+}
+
+impl FormField {
+    pub fn text(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Text {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+    pub fn file(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self::File {
+            name: name.into(),
+            path: path.into(),
+        }
+    }
+}
+
+/// Sends `multipart/form-data` POST request with `curl`. Returns response body.
+pub fn http_post_form(url: &str, headers: &[&str], fields: &[FormField]) -> Result<Vec<u8>> {
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--show-error", "--fail"]);
+    for field in fields {
+        match field {
+            // `--form-string` does not interpret leading `@` or `<` in value
+            FormField::Text { name, value } => {
+                command
+                    .arg("--form-string")
+                    .arg(format!("{}={}", name, value));
+            }
+            FormField::File { name, path } => {
+                let mut arg = OsString::from(format!("{}=@", name));
+                arg.push(path);
+                command.arg("--form").arg(arg);
+            }
+        }
+    }
+    run_curl(command, &curl_config(url, headers))
+}
+
+/// Follows redirects, eg. to download release assets
+#[cfg(feature = "self-update")]
+pub fn http_get(url: &str, headers: &[&str]) -> Result<Vec<u8>> {
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--show-error", "--fail", "--location"]);
+    run_curl(command, &curl_config(url, headers))
+}
+
+/// URL and headers may contain secrets (eg. a webhook URL or access token), so they are given on
+/// stdin instead of as arguments, which other users can read with `ps`
+fn curl_config(url: &str, headers: &[&str]) -> String {
+    let quote = |value: &str| {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    };
+    let mut config = format!("url = {}\n", quote(url));
+    for header in headers {
+        writeln!(config, "header = {}", quote(header)).expect("write to string should not fail");
+    }
+    config
+}
+
+fn run_curl(mut command: Command, config: &str) -> Result<Vec<u8>> {
+    command
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit());
+    let output = timings::time(Phase::External, || {
+        let mut child = command.spawn()?;
+        // Dropped after writing, so `curl` sees end of input
+        child
+            .stdin
+            .take()
+            .expect("stdin should be piped")
+            .write_all(config.as_bytes())?;
+        child.wait_with_output()
+    })
+    .with_context(|| "Running `curl`")?;
+    if !output.status.success() {
+        bail!(Error::external_tool("HTTP request did not succeed"));
+    }
+    Ok(output.stdout)
+}
+
+/// Only arranges window with Hyprland
+pub fn setup_image_viewer_window(
+    program: &str,
+    paths: &[impl AsRef<OsStr>],
+    window_name: &str,
+    window_manager: WindowManager,
+) -> Result<()> {
+    spawn_image_viewer(program, paths, window_name, false, None)?;
+    if window_manager != WindowManager::Hyprland {
+        return Ok(());
+    }
 
     // Wait for image viewer to completely start
     // TODO(fix): Spin until image viewer window has spawned
@@ -70,20 +514,27 @@ pub fn sleep(milliseconds: u64) {
     thread::sleep(Duration::from_millis(milliseconds));
 }
 
-/// Hyprland-specific functionality
-pub fn toggle_upload_destination() -> Result<()> {
+/// Hyprland-specific functionality. Does nothing with other window managers.
+pub fn toggle_upload_destination(window_manager: WindowManager) -> Result<()> {
+    if window_manager != WindowManager::Hyprland {
+        return Ok(());
+    }
     hyprctl_command(&["togglespecialworkspace", "social"])?;
     Ok(())
 }
 
-/// Hyprland-specific functionality
-pub fn upload_file(path: impl AsRef<Path>) -> Result<()> {
-    // Copy file contents to clipboard
-    let file = File::open(&path).with_context(|| "Opening file")?;
-    Command::new("wl-copy")
-        .stdin(file)
-        .status()
-        .with_context(|| "Copying file contents")?;
+/// Pastes automatically with Hyprland, otherwise waits for user to paste
+pub fn upload_file(
+    prompter: &dyn Prompter,
+    window_manager: WindowManager,
+    clipboard: ClipboardBackend,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    clipboard::copy_file(clipboard, path.as_ref()).with_context(|| "Copying file contents")?;
+    if window_manager != WindowManager::Hyprland {
+        confirm_paste(prompter, path.as_ref());
+        return Ok(());
+    }
 
     // Send 'paste' shortcut to application
     hyprctl_command(&["sendshortcut", "CTRL,", "V,", "class:^(Ferdium)$"])?;
@@ -91,17 +542,32 @@ pub fn upload_file(path: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// Pasting can only be automated on Hyprland
+fn confirm_paste(prompter: &dyn Prompter, path: &Path) {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    prompter.ask(&format!(
+        "Copied `{}`. Paste it into upload destination, then press Enter.",
+        file_name
+    ));
+}
+
+pub fn is_program_installed(program: &str) -> bool {
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&paths).any(|dir| dir.join(program).is_file())
+}
+
 fn hyprctl_command(args: &[impl AsRef<OsStr>]) -> Result<process::Output> {
-    let output = Command::new("hyprctl")
-        .arg("dispatch")
-        .args(args)
-        .output()
-        .with_context(|| format!("Run command `hyprctl dispatch {}`", stringify_args(args)))?;
+    let output = timings::time(Phase::External, || {
+        Command::new("hyprctl").arg("dispatch").args(args).output()
+    })
+    .with_context(|| format!("Run command `hyprctl dispatch {}`", stringify_args(args)))?;
     if !output.status.success() {
-        bail!(
+        bail!(Error::external_tool(format!(
             "Command did not exit successfully: `hyprctl dispatch {}`",
             stringify_args(args)
-        );
+        )));
     }
     Ok(output)
 }
@@ -116,3 +582,50 @@ fn stringify_args(args: &[impl AsRef<OsStr>]) -> String {
     }
     output
 }
+
+/// Words which `hunspell` or `aspell` does not recognize, in order of appearance.
+/// Program is detected by name; any other program must accept `hunspell`-style options.
+pub fn list_misspelled_words(program: &str, language: &str, text: &str) -> Result<Vec<String>> {
+    let mut command = Command::new(program);
+    if Path::new(program).file_name() == Some(OsStr::new("aspell")) {
+        command.args(["list", "--lang", language]);
+    } else {
+        command.args(["-l", "-d", language]);
+    }
+    let output = pipe_through(command, text)?;
+    Ok(output.lines().map(|word| word.to_string()).collect())
+}
+
+/// Uses an `apertium` language pair, eg. `en-eo`
+pub fn translate_apertium(pair: &str, text: &str) -> Result<String> {
+    let mut command = Command::new("apertium");
+    command.args(["-u", pair]); // No unknown-word marks
+    pipe_through(command, text)
+}
+
+/// Writes `input` to stdin of command, and returns its stdout
+fn pipe_through(mut command: Command, input: &str) -> Result<String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| Error::external_tool(format!("Spawning `{}`", program)))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(input.as_bytes())
+        .with_context(|| format!("Writing input to `{}`", program))?;
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Waiting for `{}`", program))?;
+    if !output.status.success() {
+        bail!(Error::external_tool(format!(
+            "`{}` did not exit successfully",
+            program
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
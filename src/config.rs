@@ -0,0 +1,539 @@
+use crate::clipboard::ClipboardBackend;
+use crate::commands::{self, WindowManager};
+use crate::constants::*;
+use crate::error::Error;
+use crate::schedule::UploadPolicy;
+use crate::ui::PromptPolicy;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_VIEWER: &str = if cfg!(target_os = "linux") {
+    "swiv"
+} else {
+    commands::DEFAULT_OPENER
+};
+const DEFAULT_EDITOR: &str = if cfg!(windows) { "notepad" } else { "nvim" };
+const DEFAULT_FILE_MANAGER: &str = commands::DEFAULT_OPENER;
+const DEFAULT_SVG_EDITOR: &str = "inkscape";
+const DEFAULT_RENDER_PROGRAM: &str = "inkscape";
+const DEFAULT_SPELLING_PROGRAM: &str = "hunspell";
+const DEFAULT_SPELLING_LANGUAGE: &str = "eo";
+const DEFAULT_FONT_SIZE: f32 = 18.0;
+const DEFAULT_APERTIUM_PAIR: &str = "en-eo";
+const DEFAULT_SITE_TITLE: &str = "Garfield-EO";
+const DEFAULT_NOTIFICATION_MIN_SECONDS: u64 = 30;
+const DEFAULT_INITIAL_FILE: &str = "esperanto.png";
+const DEFAULT_DUPLICATE_FILE: &str = "english.png";
+const DEFAULT_SVG_FILE: &str = "esperanto.svg";
+
+/// Per-location settings, read from the config file if it exists
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub layout: PostLayout,
+    pub viewer: ViewerConfig,
+    pub editor: EditorConfig,
+    pub file_manager: FileManagerConfig,
+    pub svg_editor: SvgEditorConfig,
+    pub render: RenderConfig,
+    pub publish: PublishConfig,
+    pub clipboard: ClipboardConfig,
+    pub spelling: SpellingConfig,
+    pub preview: PreviewConfig,
+    pub translation: TranslationConfig,
+    pub vcs: VcsConfig,
+    pub make: MakeConfig,
+    pub upload: UploadConfig,
+    pub notifications: NotificationsConfig,
+    pub revise: ReviseConfig,
+    pub prompts: PromptsConfig,
+    pub site: SiteConfig,
+    pub feed: FeedConfig,
+    pub identity: IdentityConfig,
+    pub encryption: EncryptionConfig,
+    pub backup: BackupConfig,
+    pub plan: PlanConfig,
+}
+
+/// Names of language-specific files in each post directory.
+/// Changing these does not rename files of existing posts.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostLayout {
+    /// Translated image, generated from source comic, eg. `esperanto.png`
+    pub initial: String,
+    /// Image in original language, which starts as a copy of the initial image, eg. `english.png`
+    pub duplicate: String,
+    /// Editable source of translated image, eg. `esperanto.svg`
+    pub svg: String,
+    /// Language codes of additional translations, eg. `["es"]`, each with its own `<code>.png`,
+    /// `<code>.svg`, and `transcript.<code>` files
+    pub variants: Vec<String>,
+}
+
+/// Files of one translation of a post
+#[derive(Clone, Debug)]
+pub struct Variant {
+    /// `None` for the primary translation
+    pub language: Option<String>,
+    pub image: String,
+    pub svg: String,
+    pub transcript: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ViewerConfig {
+    /// Image viewer program, which must accept `sxiv`-style options
+    pub program: String,
+    /// Window name of image viewer used by `show`
+    pub show_class: String,
+    /// Window name of image viewer used by `transcribe`
+    pub transcribe_class: String,
+    /// `hyprland` to arrange viewer windows and paste uploads automatically, or `none`
+    pub window_manager: WindowManager,
+    /// Appended to window names, so concurrent workflows don't kill each other's windows
+    #[serde(skip)]
+    pub instance: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorConfig {
+    /// Text editor program, which runs in the terminal
+    pub program: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileManagerConfig {
+    /// Program to open post directories, which runs detached
+    pub program: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SvgEditorConfig {
+    /// Program to open post svg files, which runs detached
+    pub program: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderConfig {
+    /// Program to export post svg files as PNG, which must accept Inkscape 1.x options
+    pub program: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// One of `auto`, `wl-copy`, `xclip`, `xsel`, `arboard`
+    pub backend: ClipboardBackend,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpellingConfig {
+    /// Either `hunspell` or `aspell`
+    pub program: String,
+    /// Dictionary name, eg. `eo` for Esperanto
+    pub language: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreviewConfig {
+    /// TrueType or OpenType font file, which is used in comics
+    pub font: Option<PathBuf>,
+    /// In pixels, at the scale of a real panel
+    pub font_size: f32,
+}
+
+/// Backend for `transcribe --draft`
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranslationConfig {
+    /// Language pair of local `apertium` install, used if `http_url` is not set
+    pub apertium_pair: String,
+    /// Receives a form with a `text` field, and responds with the plain translated text
+    pub http_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MakeConfig {
+    /// Create duplicate image as a hard link to initial image, instead of a copy.
+    /// Linked images are separated before post is opened for editing, or rendered.
+    pub link_duplicate: bool,
+    /// Losslessly recompress generated image, as with `optimize`
+    pub optimize: bool,
+    /// Icons used instead of `icon.png` for comics of some years. First matching rule is used.
+    pub icons: Vec<IconRule>,
+}
+
+/// Eg. `{ file = "eighties.png", from = 1980, until = 1989 }`
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IconRule {
+    /// Name of image in `icons` directory of location
+    pub file: String,
+    /// First year of comics to use icon for. Any year if unset.
+    pub from: Option<i32>,
+    /// Last year (inclusive) of comics to use icon for. Any year if unset.
+    pub until: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReviseConfig {
+    /// Seconds to wait for revised post to be finished, before giving up. Waits forever if unset.
+    pub wait_timeout: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SiteConfig {
+    /// Heading of every page, and title of feeds (including `feed`)
+    pub title: String,
+    /// Where site is hosted, eg. `https://example.com/comics/`, for absolute links in feed
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedConfig {
+    /// Where post images are hosted, as `<image_base_url>/<id>.png`. Entries have no image if
+    /// unset.
+    pub image_base_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdentityConfig {
+    /// Recorded as author of transcripts and SVGs saved by this instance. Overridden by
+    /// `--author`.
+    pub name: Option<String>,
+}
+
+/// Transcripts and titles are encrypted with `age` when saved, and decrypted when read. Files
+/// saved before encryption was set stay as plain text until saved again. Unsaved transcripts in
+/// temp directory are never encrypted, since the editor must read them.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EncryptionConfig {
+    /// Encrypt with a passphrase, which is asked once per run. The passphrase only unlocks a
+    /// generated key, which encrypts the files themselves.
+    pub passphrase: bool,
+    /// Encrypt with the secret key in this file (eg. written by `age-keygen`), relative to
+    /// location, instead of a passphrase
+    pub key_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Directory of snapshots, relative to location. Hard links between snapshots only work if it
+    /// is on the same filesystem as the previous snapshot.
+    pub dir: Option<PathBuf>,
+    /// Number of snapshots to keep after each backup, removing the oldest. Every snapshot is kept
+    /// if unset.
+    pub keep: Option<usize>,
+}
+
+/// For `next --plan`
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlanConfig {
+    /// Day of month (eg. `1`) whose post should be special. Publishing dates of slots are only
+    /// known once a numeric post is scheduled with `schedule set`.
+    pub special_day: Option<u32>,
+}
+
+/// Answer of each prompt: `ask` (default), `yes`, or `no`
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptsConfig {
+    /// Resume an unsaved transcript from a previous session, in `transcribe`
+    pub resume_transcript: PromptPolicy,
+    /// Save edited transcript, in `transcribe`. With `no`, it is kept as an unsaved transcript.
+    pub save_transcript: PromptPolicy,
+    /// Move old post to old directory, in `revise`. With `no`, revise can be continued later.
+    pub move_old_post: PromptPolicy,
+    /// Permanently remove all posts, in `trash empty`
+    pub empty_trash: PromptPolicy,
+    /// Accept a transcript submitted with `serve`, in `review`. With `no`, it is kept for later.
+    pub accept_transcript: PromptPolicy,
+    /// Finish and transcribe revised post once it is exported, in `revise`. With `no`, revise can
+    /// be continued later.
+    pub finish_revised_post: PromptPolicy,
+    /// Fail when any prompt is answered no, instead of continuing without that step
+    #[serde(skip)]
+    pub abort_on_no: bool,
+}
+
+/// Sent when batch operations (eg. `make --between`, `optimize --all`, `report`) finish or fail
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Operations which finish sooner than this don't send a notification
+    pub min_seconds: u64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UploadConfig {
+    /// Used by `next --upload`. One of `oldest`, `scheduled`, `weekdays`
+    pub policy: UploadPolicy,
+    /// `upload` warns when this many posts have already been uploaded today
+    pub max_per_day: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VcsConfig {
+    /// Commit changes after `make`, `transcribe`, `revise`, and `finish`.
+    /// Defaults to whether the location is inside a git repository.
+    pub git: Option<bool>,
+}
+
+/// Targets for `upload --target`
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublishConfig {
+    pub discord: Option<DiscordConfig>,
+    pub mastodon: Option<MastodonConfig>,
+    pub http: Option<HttpConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MastodonConfig {
+    /// eg. `https://mastodon.social`
+    pub instance_url: String,
+    /// Needs `write:media` and `write:statuses` scopes
+    pub access_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpConfig {
+    pub url: String,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).with_context(|| "Reading config file")?;
+        let config: Self = toml::from_str(&contents).with_context(|| "Parsing config file")?;
+        config.layout.validate()?;
+        Ok(config)
+    }
+}
+
+impl IconRule {
+    pub fn matches(&self, year: i32) -> bool {
+        self.from.map_or(true, |from| year >= from)
+            && self.until.map_or(true, |until| year <= until)
+    }
+}
+
+impl Default for PostLayout {
+    fn default() -> Self {
+        Self {
+            initial: DEFAULT_INITIAL_FILE.to_string(),
+            duplicate: DEFAULT_DUPLICATE_FILE.to_string(),
+            svg: DEFAULT_SVG_FILE.to_string(),
+            variants: Vec::new(),
+        }
+    }
+}
+
+impl PostLayout {
+    /// Primary translation if `language` is `None`
+    pub fn variant(&self, language: Option<&str>) -> Result<Variant> {
+        let Some(language) = language else {
+            return Ok(Variant {
+                language: None,
+                image: self.initial.clone(),
+                svg: self.svg.clone(),
+                transcript: post_file::TRANSCRIPT.to_string(),
+            });
+        };
+        if !self.variants.iter().any(|variant| variant == language) {
+            bail!(Error::usage(format!(
+                "No variant `{}` is configured in `layout.variants`",
+                language
+            )));
+        }
+        Ok(Variant {
+            language: Some(language.to_string()),
+            image: format!("{}.{}", language, SOURCE_FORMAT),
+            svg: format!("{}.svg", language),
+            transcript: format!("{}.{}", post_file::TRANSCRIPT, language),
+        })
+    }
+
+    /// Every file of every variant must have a different name, so no variant overwrites another
+    fn validate(&self) -> Result<()> {
+        let mut names: Vec<(String, String)> =
+            vec![(self.duplicate.clone(), "`layout.duplicate`".to_string())];
+        for variant in self.all_variants() {
+            let owner = match &variant.language {
+                Some(language) => {
+                    if language.is_empty() || language.contains(['/', '\\', '.']) {
+                        bail!(Error::usage(format!(
+                            "Invalid variant name `{}` in `layout.variants`",
+                            language
+                        )));
+                    }
+                    format!("variant `{}`", language)
+                }
+                None => "primary translation".to_string(),
+            };
+            for file_name in [variant.image, variant.svg, variant.transcript] {
+                if let Some((_, other)) = names.iter().find(|(name, _)| *name == file_name) {
+                    bail!(Error::usage(format!(
+                        "File `{}` of {} in `layout` is also used by {}",
+                        file_name, owner, other
+                    )));
+                }
+                names.push((file_name, owner.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Primary translation first
+    pub fn all_variants(&self) -> Vec<Variant> {
+        let mut variants = vec![self
+            .variant(None)
+            .expect("primary variant should always exist")];
+        for language in &self.variants {
+            variants.push(
+                self.variant(Some(language))
+                    .expect("configured variant should exist"),
+            );
+        }
+        variants
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_seconds: DEFAULT_NOTIFICATION_MIN_SECONDS,
+        }
+    }
+}
+
+impl Default for ViewerConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_VIEWER.to_string(),
+            show_class: window_name::SHOW.to_string(),
+            transcribe_class: window_name::TRANSCRIBE.to_string(),
+            window_manager: WindowManager::default(),
+            instance: None,
+        }
+    }
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_EDITOR.to_string(),
+        }
+    }
+}
+
+impl Default for FileManagerConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_FILE_MANAGER.to_string(),
+        }
+    }
+}
+
+impl Default for SvgEditorConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_SVG_EDITOR.to_string(),
+        }
+    }
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_RENDER_PROGRAM.to_string(),
+        }
+    }
+}
+
+impl Default for SpellingConfig {
+    fn default() -> Self {
+        Self {
+            program: DEFAULT_SPELLING_PROGRAM.to_string(),
+            language: DEFAULT_SPELLING_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            font: None,
+            font_size: DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_SITE_TITLE.to_string(),
+            base_url: None,
+        }
+    }
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            apertium_pair: DEFAULT_APERTIUM_PAIR.to_string(),
+            http_url: None,
+        }
+    }
+}
+
+impl ViewerConfig {
+    pub fn show_window(&self) -> String {
+        self.scoped(&self.show_class)
+    }
+    pub fn transcribe_window(&self) -> String {
+        self.scoped(&self.transcribe_class)
+    }
+
+    fn scoped(&self, class: &str) -> String {
+        match &self.instance {
+            Some(instance) => format!("{}-{}", class, instance),
+            None => class.to_string(),
+        }
+    }
+}
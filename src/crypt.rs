@@ -0,0 +1,190 @@
+use crate::error::Error;
+use crate::location::Location;
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+
+/// Start of every file encrypted with `age`
+const AGE_HEADER: &[u8] = b"age-encryption.org/v1\n";
+
+/// Key of location, from `encryption` in config
+#[derive(Clone, Debug)]
+enum KeySource {
+    /// Path of key which is encrypted with the passphrase
+    Passphrase(PathBuf),
+    KeyFile(PathBuf),
+}
+
+static KEY_SOURCE: OnceLock<KeySource> = OnceLock::new();
+
+/// Without `encryption` in config, files are written as plain text, and encrypted files cannot be
+/// read
+pub fn init(location: &Location) {
+    let config = &location.config().encryption;
+    let source = match &config.key_file {
+        Some(path) => KeySource::KeyFile(location.base_dir().join(path)),
+        None if config.passphrase => KeySource::Passphrase(location.wrapped_key_file()),
+        None => return,
+    };
+    let _ = KEY_SOURCE.set(source);
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(AGE_HEADER)
+}
+
+pub fn decrypt(bytes: &[u8]) -> Result<Vec<u8>> {
+    let Some(source) = KEY_SOURCE.get() else {
+        bail!(Error::usage(
+            "File is encrypted, but `encryption` is not set in config"
+        ));
+    };
+    backend::decrypt(source, bytes)
+}
+
+/// Plain text if encryption is not set in config
+pub fn encode(plaintext: &[u8]) -> Result<Vec<u8>> {
+    match KEY_SOURCE.get() {
+        Some(source) => backend::encrypt(source, plaintext),
+        None => Ok(plaintext.to_vec()),
+    }
+}
+
+#[cfg(feature = "encryption")]
+mod backend {
+    use super::KeySource;
+    use crate::error::Error;
+
+    use std::fs;
+    use std::io::{Read as _, Write as _};
+    use std::iter;
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    use age::secrecy::{ExposeSecret as _, SecretString};
+    use anyhow::{bail, Context as _, Result};
+
+    struct Key {
+        identity: Box<dyn age::Identity + Send + Sync>,
+        recipient: Box<dyn age::Recipient + Send + Sync>,
+    }
+
+    static KEY: OnceLock<Key> = OnceLock::new();
+
+    pub fn encrypt(source: &KeySource, plaintext: &[u8]) -> Result<Vec<u8>> {
+        encrypt_with(&*key(source)?.recipient, plaintext)
+    }
+
+    pub fn decrypt(source: &KeySource, bytes: &[u8]) -> Result<Vec<u8>> {
+        decrypt_with(&*key(source)?.identity, bytes)
+    }
+
+    fn encrypt_with(recipient: &dyn age::Recipient, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let encryptor = age::Encryptor::with_recipients(iter::once(recipient))
+            .with_context(|| "Creating encryptor")?;
+        let mut output = Vec::new();
+        let mut writer = encryptor
+            .wrap_output(&mut output)
+            .with_context(|| "Encrypting file")?;
+        writer
+            .write_all(plaintext)
+            .with_context(|| "Encrypting file")?;
+        writer.finish().with_context(|| "Encrypting file")?;
+        Ok(output)
+    }
+
+    fn decrypt_with(identity: &dyn age::Identity, bytes: &[u8]) -> Result<Vec<u8>> {
+        let decryptor = age::Decryptor::new(bytes).with_context(|| "Reading encrypted file")?;
+        let mut reader = decryptor
+            .decrypt(iter::once(identity))
+            .with_context(|| Error::usage("Wrong passphrase or key file for encrypted file"))?;
+        let mut plaintext = Vec::new();
+        reader
+            .read_to_end(&mut plaintext)
+            .with_context(|| "Decrypting file")?;
+        Ok(plaintext)
+    }
+
+    /// Passphrase is only asked once per run, when first needed
+    fn key(source: &KeySource) -> Result<&'static Key> {
+        if let Some(key) = KEY.get() {
+            return Ok(key);
+        }
+        let identity = match source {
+            KeySource::Passphrase(path) => unwrap_key(path)?,
+            KeySource::KeyFile(path) => {
+                let contents =
+                    fs::read_to_string(path).with_context(|| "Reading encryption key file")?;
+                parse_identity(&contents)?
+            }
+        };
+        let key = Key {
+            recipient: Box::new(identity.to_public()),
+            identity: Box::new(identity),
+        };
+        Ok(KEY.get_or_init(|| key))
+    }
+
+    /// Deriving a key from the passphrase is slow, so it is only done once, to decrypt a generated
+    /// key. The key is generated when first needed, and the passphrase must be repeated.
+    fn unwrap_key(path: &Path) -> Result<age::x25519::Identity> {
+        if !path.exists() {
+            let passphrase = rpassword::prompt_password("New passphrase of location: ")
+                .with_context(|| "Reading passphrase")?;
+            let repeated = rpassword::prompt_password("Repeat passphrase: ")
+                .with_context(|| "Reading passphrase")?;
+            if passphrase != repeated {
+                bail!(Error::usage("Passphrases do not match"));
+            }
+
+            let identity = age::x25519::Identity::generate();
+            let recipient = age::scrypt::Recipient::new(SecretString::from(passphrase));
+            let wrapped = encrypt_with(&recipient, identity.to_string().expose_secret().as_bytes())
+                .with_context(|| "Encrypting generated key")?;
+            fs::write(path, wrapped).with_context(|| "Writing encryption key file")?;
+            return Ok(identity);
+        }
+
+        let wrapped = fs::read(path).with_context(|| "Reading encryption key file")?;
+        let passphrase = rpassword::prompt_password("Passphrase of location: ")
+            .with_context(|| "Reading passphrase")?;
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+        let contents = decrypt_with(&identity, &wrapped)?;
+        let contents =
+            String::from_utf8(contents).with_context(|| "Reading encryption key file")?;
+        parse_identity(&contents)
+    }
+
+    /// First secret key in file, in the format written by `age-keygen`
+    fn parse_identity(contents: &str) -> Result<age::x25519::Identity> {
+        let line = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+            .with_context(|| Error::usage("Encryption key file contains no `AGE-SECRET-KEY`"))?;
+        match line.parse() {
+            Ok(identity) => Ok(identity),
+            Err(error) => bail!(Error::usage(format!("Invalid encryption key: {}", error))),
+        }
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+mod backend {
+    use super::KeySource;
+    use crate::error::Error;
+
+    use anyhow::{bail, Result};
+
+    pub fn encrypt(_source: &KeySource, _plaintext: &[u8]) -> Result<Vec<u8>> {
+        bail!(Error::usage("Encryption requires the `encryption` feature"));
+    }
+
+    pub fn decrypt(_source: &KeySource, _bytes: &[u8]) -> Result<Vec<u8>> {
+        bail!(Error::usage(
+            "Encrypted files can only be read with the `encryption` feature"
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::config::PostLayout;
+use crate::file;
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+
+/// Whether duplicate image of post is a hard link to its initial image
+#[cfg(unix)]
+pub fn is_linked(layout: &PostLayout, post_path: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt as _;
+    let initial = fs::metadata(post_path.join(&layout.initial))
+        .with_context(|| "Reading initial image metadata")?;
+    let duplicate = fs::metadata(post_path.join(&layout.duplicate))
+        .with_context(|| "Reading duplicate image metadata")?;
+    Ok(initial.dev() == duplicate.dev() && initial.ino() == duplicate.ino())
+}
+
+/// File identity is not available on stable Rust for other platforms, so images are never treated
+/// as linked. `make.link_duplicate` should not be used there.
+#[cfg(not(unix))]
+pub fn is_linked(_layout: &PostLayout, _post_path: &Path) -> Result<bool> {
+    Ok(false)
+}
+
+/// Whether duplicate image can be replaced with a hard link, because it is still byte-for-byte
+/// identical to the initial image, and not already linked
+pub fn can_link(layout: &PostLayout, post_path: &Path) -> Result<bool> {
+    let initial_path = post_path.join(&layout.initial);
+    let duplicate_path = post_path.join(&layout.duplicate);
+    if !initial_path.is_file() || !duplicate_path.is_file() || is_linked(layout, post_path)? {
+        return Ok(false);
+    }
+    file::compare_files(&initial_path, &duplicate_path)
+        .with_context(|| "Comparing initial and duplicate images")
+}
+
+/// Replaces duplicate image with a hard link to initial image.
+/// Duplicate is only replaced once link is created, so it is never missing.
+pub fn link(layout: &PostLayout, post_path: &Path) -> Result<()> {
+    let duplicate_path = post_path.join(&layout.duplicate);
+    let temp_path = post_path.join(format!("{}.link", layout.duplicate));
+    fs::hard_link(post_path.join(&layout.initial), &temp_path)
+        .with_context(|| "Creating hard link to initial image")?;
+    fs::rename(&temp_path, &duplicate_path)
+        .with_context(|| "Replacing duplicate image with hard link")?;
+    Ok(())
+}
+
+/// Replaces a hard-linked duplicate image with its own copy, so editing either image in place does
+/// not modify the other
+pub fn unlink(layout: &PostLayout, post_path: &Path) -> Result<()> {
+    let initial_path = post_path.join(&layout.initial);
+    let duplicate_path = post_path.join(&layout.duplicate);
+    if !initial_path.is_file() || !duplicate_path.is_file() || !is_linked(layout, post_path)? {
+        return Ok(());
+    }
+    let temp_path = post_path.join(format!("{}.copy", layout.duplicate));
+    fs::copy(&initial_path, &temp_path).with_context(|| "Copying initial image")?;
+    fs::rename(&temp_path, &duplicate_path)
+        .with_context(|| "Replacing hard link with copy of initial image")?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::process::ExitCode;
+
+/// Class of failure, which determines the exit code of the process
+///
+/// Exit code `1` is used for any error without a kind, and `2` is also used by `clap` for invalid
+/// arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Invalid input, which could not be caught while parsing arguments
+    Usage = 2,
+    /// No post exists, or it is not in the expected directory
+    MissingPost = 3,
+    /// An external program could not be run, or did not succeed
+    ExternalTool = 4,
+    /// A check or precondition on existing files failed
+    Validation = 5,
+    /// A prompt was answered no, with `--abort-on-no`
+    Declined = 6,
+}
+
+/// Message with a kind, which can be found in an `anyhow::Error` chain with `exit_code`.
+///
+/// Either use directly with `bail!`, or as context of another error.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Usage, message)
+    }
+    pub fn missing_post(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::MissingPost, message)
+    }
+    pub fn external_tool(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::ExternalTool, message)
+    }
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Validation, message)
+    }
+    pub fn declined(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Declined, message)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Uses the outermost kind in the error chain
+pub fn exit_code(error: &anyhow::Error) -> ExitCode {
+    match error.downcast_ref::<Error>() {
+        Some(error) => ExitCode::from(error.kind() as u8),
+        None => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,85 @@
+use crate::report::escape_html;
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Local};
+
+#[derive(Debug)]
+pub struct FeedEntry {
+    pub title: String,
+    /// Unique and permanent, eg. URL of post
+    pub id: String,
+    pub link: Option<String>,
+    pub updated: DateTime<Local>,
+    pub summary: String,
+    pub image_url: Option<String>,
+}
+
+/// Atom feed, with most recent entry as time of feed
+pub fn render_atom(
+    title: &str,
+    feed_id: &str,
+    home_url: Option<&str>,
+    entries: &[FeedEntry],
+) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_else(Local::now);
+
+    let mut output = String::new();
+    writeln!(
+        output,
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+        <title>{}</title>\n\
+        <id>{}</id>\n\
+        <updated>{}</updated>",
+        escape_html(title),
+        escape_html(feed_id),
+        updated.to_rfc3339(),
+    )
+    .expect("write to string should not fail");
+    if let Some(home_url) = home_url {
+        writeln!(output, "<link href=\"{}\"/>", escape_html(home_url))
+            .expect("write to string should not fail");
+    }
+    for entry in entries {
+        writeln!(
+            output,
+            "<entry>\n\
+            <title>{}</title>\n\
+            <id>{}</id>\n\
+            <updated>{}</updated>\n\
+            <summary>{}</summary>",
+            escape_html(&entry.title),
+            escape_html(&entry.id),
+            entry.updated.to_rfc3339(),
+            escape_html(&entry.summary),
+        )
+        .expect("write to string should not fail");
+        if let Some(link) = &entry.link {
+            writeln!(output, "<link href=\"{}\"/>", escape_html(link))
+                .expect("write to string should not fail");
+        }
+        if let Some(image_url) = &entry.image_url {
+            // Content is escaped HTML, as required for `type="html"`
+            let content = format!(
+                "<img src=\"{}\" alt=\"{}\"><p>{}</p>",
+                escape_html(image_url),
+                escape_html(&entry.title),
+                escape_html(&entry.summary).replace('\n', "<br>"),
+            );
+            writeln!(
+                output,
+                "<content type=\"html\">{}</content>",
+                escape_html(&content)
+            )
+            .expect("write to string should not fail");
+        }
+        output += "</entry>\n";
+    }
+    output += "</feed>\n";
+    output
+}
@@ -1,11 +1,15 @@
+use crate::crypt;
+use crate::error::Error;
 use crate::random;
 
 use std::fs::{self, DirEntry, File};
-use std::io::{self, BufRead as _, BufReader, Read, Write as _};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
+use std::sync::{mpsc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use ::notify::{RecursiveMode, Watcher as _};
 use anyhow::{bail, Context as _, Result};
 use chrono::NaiveDate;
 use rand::Rng as _;
@@ -23,22 +27,90 @@ pub fn discard_read_line(reader: &mut impl Read) {
     }
 }
 
-pub fn get_random_directory_entry<F>(
-    dir: impl AsRef<Path>,
-    predicate: F,
-) -> Result<Option<DirEntry>>
+/// Contents of text file, decrypted if it is encrypted
+pub fn read_text(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).with_context(|| format!("Reading {:?}", path))?;
+    let bytes = if crypt::is_encrypted(&bytes) {
+        crypt::decrypt(&bytes).with_context(|| format!("Decrypting {:?}", path))?
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes).with_context(|| format!("Reading {:?} as UTF-8", path))
+}
+
+/// Contents to write to a transcript or title file, which is encrypted if `encryption` is set in
+/// config
+pub fn encode_text(contents: &str) -> Result<Vec<u8>> {
+    crypt::encode(contents.as_bytes())
+}
+
+/// Returns empty string if file does not exist
+pub fn read_first_line(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    let contents = read_text(path)?;
+    let line = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty());
+    Ok(line.unwrap_or_default().to_string())
+}
+
+/// Entries of source directory, including those in year subdirectories (`source/<year>/`)
+pub fn read_source_dir(dir: impl AsRef<Path>) -> Result<Vec<DirEntry>> {
+    let mut entries = Vec::new();
+    for entry in read_dir(&dir)? {
+        let entry = entry?;
+        if is_year_dir(&entry.path()) {
+            for entry in read_dir(entry.path())? {
+                entries.push(entry?);
+            }
+        } else {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Uniformly random entry of source directory (see `read_source_dir`) matching predicate, without
+/// collecting or sorting entries
+pub fn sample_entry<F>(dir: impl AsRef<Path>, mut predicate: F) -> Result<Option<DirEntry>>
 where
     F: FnMut(&DirEntry) -> bool,
 {
-    let entries = read_dir(&dir)?.flatten().filter(predicate);
-    let mut entries = sort_dir_entries(entries.collect());
-
-    if entries.is_empty() {
-        return Ok(None);
+    let mut chosen = None;
+    let mut count = 0;
+    // Replaces chosen entry with probability `1 / count`
+    let mut consider = |entry: DirEntry| {
+        if predicate(&entry) {
+            count += 1;
+            if random::with_rng(|rng| rng.gen_range(0..count)) == 0 {
+                chosen = Some(entry);
+            }
+        }
+    };
+    for entry in read_dir(&dir)? {
+        let entry = entry?;
+        if is_year_dir(&entry.path()) {
+            for entry in read_dir(entry.path())? {
+                consider(entry?);
+            }
+        } else {
+            consider(entry);
+        }
     }
-    let index = random::with_rng(|rng| rng.gen_range(0..entries.len()));
-    let entry = entries.swap_remove(index); // Get owned element in O(1) time
-    Ok(Some(entry))
+    Ok(chosen)
+}
+
+pub fn is_year_dir(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let name = name.to_string_lossy();
+    // Name is checked first, to avoid reading metadata of every comic
+    name.len() == 4 && name.chars().all(|ch| ch.is_ascii_digit()) && path.is_dir()
 }
 
 /// Wrapper for `fs::read_dir` which provides context for some errors
@@ -55,15 +127,6 @@ pub fn sort_dir_entries(mut entries: Vec<DirEntry>) -> Vec<DirEntry> {
     entries
 }
 
-pub fn append_date(path: impl AsRef<Path>, date: NaiveDate) -> io::Result<()> {
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)?;
-    writeln!(file, "{}", date)?;
-    Ok(())
-}
-
 pub fn get_date_from_path(path: impl AsRef<Path>) -> Result<Option<NaiveDate>> {
     let path = path.as_ref();
     let date_str = path
@@ -119,23 +182,143 @@ where
     Ok(None)
 }
 
+/// Always `true` on platforms other than Unix, where it cannot be checked
+#[cfg(unix)]
+pub fn is_same_filesystem(a: impl AsRef<Path>, b: impl AsRef<Path>) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt as _;
+    // Follows symlinks
+    Ok(fs::metadata(a)?.dev() == fs::metadata(b)?.dev())
+}
+
+#[cfg(not(unix))]
+pub fn is_same_filesystem(_a: impl AsRef<Path>, _b: impl AsRef<Path>) -> io::Result<bool> {
+    Ok(true)
+}
+
+/// Renames file or directory, or copies then removes it if destination is on another filesystem
+/// (eg. through a symlinked directory). Copies keep permissions and modification times, and are
+/// compared to the source before it is removed. Hard links within a copied directory are not kept.
+pub fn rename_or_copy(path_a: impl AsRef<Path>, path_b: impl AsRef<Path>) -> io::Result<()> {
+    let (path_a, path_b) = (path_a.as_ref(), path_b.as_ref());
+    let Err(error) = fs::rename(path_a, path_b) else {
+        return Ok(());
+    };
+    let Some(parent_b) = path_b.parent().filter(|parent| parent.is_dir()) else {
+        return Err(error);
+    };
+    if !path_a.exists() || path_b.exists() || is_same_filesystem(path_a, parent_b)? {
+        return Err(error);
+    }
+    if let Err(error) = copy_verified(path_a, path_b) {
+        // Partial copy would prevent trying again
+        let _ = remove_path(path_b);
+        return Err(error);
+    }
+    remove_path(path_a)
+}
+
+fn copy_verified(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_verified(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        // Also copies permissions
+        fs::copy(from, to)?;
+        if !compare_files(from, to)? {
+            return Err(io::Error::other(format!(
+                "Copy of {:?} does not match original",
+                from
+            )));
+        }
+    }
+    // After children are copied, which would update modification time of directory
+    File::open(to)?.set_modified(metadata.modified()?)?;
+    fs::set_permissions(to, metadata.permissions())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Fails with a validation error if less than `required` bytes are available to the filesystem
+/// of `dir`
+pub fn require_free_space(dir: impl AsRef<Path>, required: u64) -> Result<()> {
+    let available = fs2::available_space(dir.as_ref()).with_context(|| "Reading free space")?;
+    if available < required {
+        bail!(Error::validation(format!(
+            "Not enough free space in {:?}: about {} MiB is needed, but only {} MiB is available",
+            dir.as_ref(),
+            required.div_ceil(1024 * 1024),
+            available / 1024 / 1024
+        )));
+    }
+    Ok(())
+}
+
+/// Every matching child, in order of name
+pub fn find_children<F>(dir: impl AsRef<Path>, predicate: F) -> Result<Vec<String>>
+where
+    F: Fn(&Path) -> Result<bool>,
+{
+    let mut names = Vec::new();
+    for entry in sort_dir_entries(read_dir(&dir)?.flatten().collect()) {
+        if predicate(&entry.path())? {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    Ok(names)
+}
+
 pub fn file_matches_string(file_path: impl AsRef<Path>, target: &str) -> io::Result<bool> {
-    let file = fs::OpenOptions::new().read(true).open(file_path)?;
+    let file = File::open(file_path)?;
+    readers_match(file, target.as_bytes())
+}
 
-    let mut file_bytes = BufReader::new(file).bytes();
-    let mut target_bytes = target.bytes();
+/// Whether files are byte-for-byte identical, without reading either file into memory
+pub fn compare_files(path_a: impl AsRef<Path>, path_b: impl AsRef<Path>) -> io::Result<bool> {
+    let file_a = File::open(path_a)?;
+    let file_b = File::open(path_b)?;
+    if file_a.metadata()?.len() != file_b.metadata()?.len() {
+        return Ok(false);
+    }
+    readers_match(file_a, file_b)
+}
 
-    // Check for any mismatched bytes
-    let zipped = (&mut file_bytes).zip(&mut target_bytes);
-    for (fb, tb) in zipped {
-        if fb? != tb {
+fn readers_match(a: impl Read, b: impl Read) -> io::Result<bool> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    let mut reader_a = BufReader::with_capacity(CHUNK_SIZE, a);
+    let mut reader_b = BufReader::with_capacity(CHUNK_SIZE, b);
+    loop {
+        let chunk_a = reader_a.fill_buf()?;
+        let chunk_b = reader_b.fill_buf()?;
+        if chunk_a.is_empty() || chunk_b.is_empty() {
+            // Both must be exhausted, or lengths mismatch
+            return Ok(chunk_a.is_empty() && chunk_b.is_empty());
+        }
+        // Chunks may differ in length, so only compare their common prefix
+        let length = chunk_a.len().min(chunk_b.len());
+        if chunk_a[..length] != chunk_b[..length] {
             return Ok(false);
         }
+        reader_a.consume(length);
+        reader_b.consume(length);
     }
+}
 
-    // If either iterator is unexhausted, then lengths mismatch
-    let lengths_match = file_bytes.next().is_none() && target_bytes.next().is_none();
-    Ok(lengths_match)
+/// BLAKE3 hash of file, as lowercase hex
+pub fn hash_file(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok(hasher.finalize().to_hex().to_string())
 }
 
 pub fn file_contains_line(file: File, needle: &str) -> io::Result<bool> {
@@ -149,9 +332,121 @@ pub fn file_contains_line(file: File, needle: &str) -> io::Result<bool> {
     Ok(false)
 }
 
-pub fn wait_for_file(path: impl AsRef<Path>) {
-    const WAIT_DELAY: Duration = Duration::from_millis(500);
-    while !path.as_ref().exists() {
-        thread::sleep(WAIT_DELAY);
+/// Returns `Ok(true)` if `path` was modified more recently than `other`
+pub fn is_newer_than(path: impl AsRef<Path>, other: impl AsRef<Path>) -> io::Result<bool> {
+    let modified = fs::metadata(path)?.modified()?;
+    let other_modified = fs::metadata(other)?.modified()?;
+    Ok(modified > other_modified)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitOutcome {
+    Done,
+    TimedOut,
+    Aborted,
+}
+
+enum WaitMessage {
+    Changed,
+    /// `None` if stdin is closed
+    Input(Option<String>),
+}
+
+/// Read of stdin which was still pending once waiting finished, to be taken by the next prompt
+static PENDING_INPUT: Mutex<Option<mpsc::Receiver<WaitMessage>>> = Mutex::new(None);
+
+/// Waits until `is_done` returns `true`, checking whenever a file in any of `dirs` changes.
+/// Entering `q` aborts waiting, and a reminder is printed periodically.
+///
+/// Stdin is still being read once done, so the line is kept for the next prompt (see
+/// `take_pending_input`), instead of being lost.
+pub fn wait_until(
+    dirs: &[&Path],
+    mut is_done: impl FnMut() -> bool,
+    timeout: Option<Duration>,
+) -> Result<WaitOutcome> {
+    const REMINDER_INTERVAL: Duration = Duration::from_secs(60);
+
+    let (sender, receiver) = mpsc::channel();
+    let event_sender = sender.clone();
+    let mut watcher = ::notify::recommended_watcher(move |_: ::notify::Result<_>| {
+        let _ = event_sender.send(WaitMessage::Changed);
+    })
+    .with_context(|| "Creating file watcher")?;
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Watching directory {:?}", dir))?;
     }
+
+    println!("(waiting until done... enter `q` to abort)");
+    spawn_line_reader(sender.clone());
+    let mut is_stdin_open = true;
+
+    let started = Instant::now();
+    let mut last_reminder = started;
+    let outcome = loop {
+        // Checked after watching, so no change is missed
+        if is_done() {
+            break WaitOutcome::Done;
+        }
+        let mut wait = REMINDER_INTERVAL.saturating_sub(last_reminder.elapsed());
+        if let Some(timeout) = timeout {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                break WaitOutcome::TimedOut;
+            }
+            wait = wait.min(remaining);
+        }
+        if wait.is_zero() {
+            println!("Still waiting - enter `q` to abort");
+            last_reminder = Instant::now();
+            continue;
+        }
+
+        match receiver.recv_timeout(wait) {
+            Ok(WaitMessage::Input(Some(line))) if line.trim() == "q" => {
+                break WaitOutcome::Aborted;
+            }
+            Ok(WaitMessage::Input(Some(_))) => spawn_line_reader(sender.clone()),
+            Ok(WaitMessage::Input(None)) => is_stdin_open = false,
+            Ok(WaitMessage::Changed) | Err(_) => (),
+        }
+    };
+
+    let is_reading = is_stdin_open && outcome != WaitOutcome::Aborted;
+    if is_reading {
+        *PENDING_INPUT
+            .lock()
+            .unwrap_or_else(|error| error.into_inner()) = Some(receiver);
+    }
+    Ok(outcome)
+}
+
+/// Line read by `wait_until` after it finished, blocking until it is entered. `None` if no read
+/// is pending, otherwise `Some(None)` if stdin was closed.
+pub fn take_pending_input() -> Option<Option<String>> {
+    let receiver = PENDING_INPUT
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .take()?;
+    loop {
+        match receiver.recv() {
+            Ok(WaitMessage::Input(line)) => return Some(line),
+            Ok(WaitMessage::Changed) => (),
+            Err(_) => return Some(None),
+        }
+    }
+}
+
+/// Reads a single line, so no line is taken after waiting has finished
+fn spawn_line_reader(sender: mpsc::Sender<WaitMessage>) {
+    thread::spawn(move || {
+        let mut line = String::new();
+        let line = match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
+        };
+        let _ = sender.send(WaitMessage::Input(line));
+    });
 }
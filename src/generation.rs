@@ -0,0 +1,97 @@
+use crate::constants::*;
+use crate::file;
+use crate::location::Location;
+use crate::timings::{self, Phase};
+use crate::watermarks::WatermarkSet;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use image::DynamicImage;
+use sha2::{Digest as _, Sha256};
+
+/// Increase whenever parameters of `comic_format::convert_image` change, so every post is
+/// regenerated
+const FORMAT_VERSION: u32 = 1;
+
+/// Watermarks and icons, loaded once for a batch of posts being made
+pub struct MakeContext<'a> {
+    location: &'a Location,
+    watermarks: WatermarkSet,
+    /// Icon depends on date of comic, so each is only loaded when first needed
+    icons: HashMap<PathBuf, DynamicImage>,
+    icon_hashes: HashMap<PathBuf, String>,
+}
+
+impl<'a> MakeContext<'a> {
+    pub fn new(location: &'a Location) -> Result<Self> {
+        let watermarks =
+            WatermarkSet::load(location).with_context(|| "Reading watermarks files")?;
+        Ok(Self {
+            location,
+            watermarks,
+            icons: HashMap::new(),
+            icon_hashes: HashMap::new(),
+        })
+    }
+
+    pub fn location(&self) -> &'a Location {
+        self.location
+    }
+
+    pub fn watermarks(&self) -> &WatermarkSet {
+        &self.watermarks
+    }
+
+    pub fn icon(&mut self, path: &Path) -> Result<&DynamicImage> {
+        if !self.icons.contains_key(path) {
+            let icon = timings::time(Phase::Decode, || image::open(path))
+                .with_context(|| "Opening icon image")?;
+            self.icons.insert(path.to_path_buf(), icon);
+        }
+        Ok(&self.icons[path])
+    }
+
+    /// Hash of everything which a generated image depends on
+    pub fn hash_inputs(
+        &mut self,
+        source_path: &Path,
+        icon_path: &Path,
+        watermark: &str,
+    ) -> Result<String> {
+        if !self.icon_hashes.contains_key(icon_path) {
+            let hash = file::hash_file(icon_path).with_context(|| "Hashing icon image")?;
+            self.icon_hashes.insert(icon_path.to_path_buf(), hash);
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(file::hash_file(source_path).with_context(|| "Hashing comic image")?);
+        hasher.update(&self.icon_hashes[icon_path]);
+        hasher.update(watermark.as_bytes());
+        hasher.update(FORMAT_VERSION.to_le_bytes());
+        hasher.update([self.location.config().make.optimize as u8]);
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Whether images of existing post were generated from the same inputs, and so do not need
+    /// to be generated again
+    pub fn is_reusable(
+        &mut self,
+        post_path: &Path,
+        source_path: &Path,
+        icon_path: &Path,
+        watermark: &str,
+    ) -> Result<bool> {
+        let layout = self.location.layout();
+        let hash_path = post_path.join(post_file::GENERATION);
+        if !hash_path.is_file()
+            || !post_path.join(&layout.initial).is_file()
+            || !post_path.join(&layout.duplicate).is_file()
+        {
+            return Ok(false);
+        }
+        let recorded = fs::read_to_string(hash_path).with_context(|| "Reading generation file")?;
+        Ok(recorded.trim() == self.hash_inputs(source_path, icon_path, watermark)?)
+    }
+}
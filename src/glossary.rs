@@ -0,0 +1,70 @@
+use crate::location::Location;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+/// Preferred translations of recurring English terms, eg.
+///
+/// ```toml
+/// [lasagna]
+/// preferred = "lazanjo"
+/// avoid = ["lasanjo", "lazanja"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Glossary {
+    /// English term -> translations
+    terms: BTreeMap<String, GlossaryTerm>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GlossaryTerm {
+    pub preferred: String,
+    /// Matched case-insensitively anywhere in a line, so a word stem also matches its inflections
+    #[serde(default)]
+    pub avoid: Vec<String>,
+}
+
+/// Non-preferred variant found in some text
+#[derive(Debug)]
+pub struct GlossaryMatch<'a> {
+    pub english: &'a str,
+    pub variant: &'a str,
+    pub preferred: &'a str,
+}
+
+impl Glossary {
+    pub fn load(location: &Location) -> Result<Self> {
+        let path = location.glossary_file();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).with_context(|| "Reading glossary file")?;
+        toml::from_str(&contents).with_context(|| "Parsing glossary file")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn find_avoided(&self, text: &str) -> Vec<GlossaryMatch<'_>> {
+        let text = text.to_lowercase();
+        let mut matches = Vec::new();
+        for (english, term) in &self.terms {
+            for variant in &term.avoid {
+                if text.contains(&variant.to_lowercase()) {
+                    matches.push(GlossaryMatch {
+                        english,
+                        variant,
+                        preferred: &term.preferred,
+                    });
+                }
+            }
+        }
+        matches
+    }
+}
@@ -0,0 +1,143 @@
+use crate::args::Args;
+
+use std::fmt::Write as _;
+
+use clap::CommandFactory as _;
+
+/// Step of the workflow, for `guide` and examples of `--help`
+struct Step {
+    /// Subcommand, which must exist. Its description is read from `Args`, so it always matches
+    /// `--help`.
+    command: &'static str,
+    /// Arguments after `garfutils`, which must parse
+    example: &'static str,
+    details: &'static str,
+    /// Paths relative to location, with default file names of `layout`
+    files: &'static [&'static str],
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        command: "show",
+        example: "show --new",
+        details: "Pick a comic to translate. Without a date, shows a random comic. The date is \
+            remembered, so the next step can use it.",
+        files: &["source/2004-07-11.png"],
+    },
+    Step {
+        command: "make",
+        example: "make --recent",
+        details: "Generates the translated image, watermarked and with the icon of the location, \
+            into a post named by a random code and its date, eg. `abcd:2004-07-11`. Any unique \
+            start of an id can be used for later commands.",
+        files: &[
+            "generated/abcd:2004-07-11/esperanto.png",
+            "generated/abcd:2004-07-11/english.png",
+            "generated/abcd:2004-07-11/date",
+        ],
+    },
+    Step {
+        command: "open",
+        example: "open abcd",
+        details: "Open the post directory, then edit the text of `esperanto.png` in Inkscape. \
+            Save the drawing as `esperanto.svg`, and export it over `esperanto.png`.",
+        files: &["generated/abcd:2004-07-11/esperanto.svg"],
+    },
+    Step {
+        command: "finish",
+        example: "finish abcd",
+        details: "Asks for a title if the post has none, then moves it into posts directory with \
+            the next sequential id.",
+        files: &["posts/123/title", "posts/123/checksums.sha256"],
+    },
+    Step {
+        command: "transcribe",
+        example: "transcribe 123",
+        details: "Displays the post and opens an editor with a template for each panel. Saved \
+            transcripts need review before upload, or stay drafts with `--draft`.",
+        files: &["posts/123/transcript"],
+    },
+    Step {
+        command: "revise",
+        example: "revise 123",
+        details: "Later, eg. after changing the icon: the current version is moved into old \
+            directory, the post is generated again, then finished and transcribed as above. \
+            Continue with `resume` if any step fails.",
+        files: &["old/123/esperanto.png", "generated/123/esperanto.png"],
+    },
+    Step {
+        command: "review",
+        example: "review 123",
+        details: "Check the images and transcript of the post, and approve it for upload.",
+        files: &["posts/123/props"],
+    },
+    Step {
+        command: "upload",
+        example: "upload 123",
+        details: "Copies each image and the transcript into the upload window, or publishes \
+            directly with `--target`. Each upload is logged for `uploads list`.",
+        files: &["posts/123/uploaded", "uploads"],
+    },
+];
+
+/// Examples of each step, for `--help`
+pub fn examples() -> String {
+    let mut text = String::from("Examples:\n");
+    for step in STEPS {
+        writeln!(text, "  garfutils {}", step.example).expect("write to string should not fail");
+    }
+    text.push_str("\nRun `garfutils guide` for a walkthrough of the full workflow");
+    text
+}
+
+/// Walkthrough of the workflow, from choosing a comic to uploading its post
+pub fn guide() -> String {
+    let command = Args::command();
+    let mut text = String::from(
+        "Each post starts as a comic in `source/`, becomes a generated post in `generated/`, then \
+        a completed post in `posts/`.\n",
+    );
+    for (i, step) in STEPS.iter().enumerate() {
+        let subcommand = command
+            .find_subcommand(step.command)
+            .unwrap_or_else(|| panic!("guide step should be a command: {}", step.command));
+
+        writeln!(text, "\n{}. {}", i + 1, step.command).expect("write to string should not fail");
+        if let Some(about) = subcommand.get_about() {
+            writeln!(text, "   {}", about).expect("write to string should not fail");
+        }
+        writeln!(text, "\n   $ garfutils {}\n", step.example)
+            .expect("write to string should not fail");
+        writeln!(text, "   {}", step.details).expect("write to string should not fail");
+        if !step.files.is_empty() {
+            text.push('\n');
+            for file in step.files {
+                writeln!(text, "   - {}", file).expect("write to string should not fail");
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::Parser as _;
+
+    #[test]
+    fn steps_are_valid_commands() {
+        let command = Args::command();
+        for step in STEPS {
+            assert!(
+                command.find_subcommand(step.command).is_some(),
+                "guide step should be a command: {}",
+                step.command
+            );
+            let example_args = ["garfutils"].into_iter().chain(step.example.split(' '));
+            if let Err(error) = Args::try_parse_from(example_args) {
+                panic!("guide example should be valid: {}\n{}", step.example, error);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+
+const HISTORY_DIR: &str = "history";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Saved copy of a post file, named `<file>.<timestamp>` in the post's history directory. Later
+/// versions saved in the same second are named `<file>.<timestamp>-<count>`.
+#[derive(Debug)]
+pub struct Version {
+    /// Starting from 1, in order of saving
+    pub number: usize,
+    pub file_name: String,
+    pub saved_at: NaiveDateTime,
+    pub path: PathBuf,
+}
+
+/// Copies current contents of a post file into history, after it has been saved
+pub fn record(post_path: &Path, file_name: &str) -> Result<()> {
+    let history_dir = post_path.join(HISTORY_DIR);
+    fs::create_dir_all(&history_dir).with_context(|| "Creating history directory")?;
+    copy_version(
+        &history_dir,
+        &post_path.join(file_name),
+        file_name,
+        Local::now().naive_local(),
+    )
+}
+
+/// Before a file is overwritten, copies it into history if it has no history yet, so drafts from
+/// before history was kept are not lost. Its modification time is used as the time saved.
+pub fn record_previous(post_path: &Path, file_name: &str) -> Result<()> {
+    let path = post_path.join(file_name);
+    if !path.exists() {
+        return Ok(());
+    }
+    let has_history = list_versions(post_path)?
+        .iter()
+        .any(|version| version.file_name == file_name);
+    if has_history {
+        return Ok(());
+    }
+
+    let history_dir = post_path.join(HISTORY_DIR);
+    fs::create_dir_all(&history_dir).with_context(|| "Creating history directory")?;
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| "Reading modification time of previous version")?;
+    let modified = DateTime::<Local>::from(modified).naive_local();
+    copy_version(&history_dir, &path, file_name, modified)
+}
+
+/// Sorted by time saved, oldest first. Skips files with malformed names.
+pub fn list_versions(post_path: &Path) -> Result<Vec<Version>> {
+    let history_dir = post_path.join(HISTORY_DIR);
+    if !history_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(&history_dir).with_context(|| "Reading history directory")? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some((file_name, timestamp)) = name.rsplit_once('.') else {
+            continue;
+        };
+        let (timestamp, count) = match timestamp.split_once('-') {
+            Some((timestamp, count)) => match count.parse::<usize>() {
+                Ok(count) => (timestamp, count),
+                Err(_) => continue,
+            },
+            None => (timestamp, 0),
+        };
+        let Ok(saved_at) = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT) else {
+            continue;
+        };
+        versions.push((
+            count,
+            Version {
+                number: 0,
+                file_name: file_name.to_string(),
+                saved_at,
+                path,
+            },
+        ));
+    }
+    versions.sort_by(|(a_count, a), (b_count, b)| {
+        (a.saved_at, a_count, &a.file_name).cmp(&(b.saved_at, b_count, &b.file_name))
+    });
+    let mut versions: Vec<Version> = versions.into_iter().map(|(_, version)| version).collect();
+    for (i, version) in versions.iter_mut().enumerate() {
+        version.number = i + 1;
+    }
+    Ok(versions)
+}
+
+/// Copies history of a post into another post directory (eg. when revising)
+pub fn copy_history(from_post_path: &Path, to_post_path: &Path) -> Result<()> {
+    let versions = list_versions(from_post_path)?;
+    if versions.is_empty() {
+        return Ok(());
+    }
+    let history_dir = to_post_path.join(HISTORY_DIR);
+    fs::create_dir_all(&history_dir).with_context(|| "Creating history directory")?;
+    for version in versions {
+        let name = version
+            .path
+            .file_name()
+            .expect("version path should have file name");
+        fs::copy(&version.path, history_dir.join(name))
+            .with_context(|| "Copying history version")?;
+    }
+    Ok(())
+}
+
+/// Adds a count to the name if a version of the file already exists with the same timestamp
+fn copy_version(
+    history_dir: &Path,
+    source_path: &Path,
+    file_name: &str,
+    saved_at: NaiveDateTime,
+) -> Result<()> {
+    let timestamp = saved_at.format(TIMESTAMP_FORMAT).to_string();
+    let mut path = history_dir.join(format!("{}.{}", file_name, timestamp));
+    let mut count = 1;
+    while path.exists() {
+        path = history_dir.join(format!("{}.{}-{}", file_name, timestamp, count));
+        count += 1;
+    }
+    fs::copy(source_path, path)
+        .with_context(|| format!("Saving version of `{}` file", file_name))?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::error::Error;
+use crate::location::Location;
+use crate::output;
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context as _, Result};
+
+/// Runs `hooks/pre-<action>`, if it exists. Action should not continue if this fails.
+pub fn run_pre(location: &Location, action: &str, id: &str, post_path: &Path) -> Result<()> {
+    run(location, &format!("pre-{}", action), id, post_path)
+}
+
+/// Runs `hooks/post-<action>`, if it exists. Failure is only printed, since action is complete.
+pub fn run_post(location: &Location, action: &str, id: &str, post_path: &Path) {
+    if let Err(error) = run(location, &format!("post-{}", action), id, post_path) {
+        output::warning(format!("{:#}", error));
+    }
+}
+
+/// Hook receives `GARFUTILS_HOOK`, `GARFUTILS_ID`, `GARFUTILS_POST_DIR` (which may not exist yet
+/// for `pre-make`), and `GARFUTILS_LOCATION` environment variables
+fn run(location: &Location, hook: &str, id: &str, post_path: &Path) -> Result<()> {
+    let path = location.hook_file(hook);
+    if !path.is_file() {
+        return Ok(());
+    }
+    if !is_executable(&path).with_context(|| format!("Reading permissions of `{}` hook", hook))? {
+        output::warning(format!(
+            "Hook `{}` is not executable, so it was skipped",
+            hook
+        ));
+        return Ok(());
+    }
+
+    let status = Command::new(&path)
+        .env("GARFUTILS_HOOK", hook)
+        .env("GARFUTILS_ID", id)
+        .env("GARFUTILS_POST_DIR", post_path)
+        .env("GARFUTILS_LOCATION", location.base_dir())
+        .current_dir(location.base_dir())
+        .status()
+        .with_context(|| Error::external_tool(format!("Running `{}` hook", hook)))?;
+    if !status.success() {
+        bail!(Error::external_tool(format!(
+            "Hook `{}` did not exit successfully",
+            hook
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> Result<bool> {
+    use std::os::unix::fs::PermissionsExt as _;
+    Ok(path.metadata()?.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows has no executable permission
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> Result<bool> {
+    Ok(true)
+}
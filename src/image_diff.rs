@@ -0,0 +1,71 @@
+use image::{imageops, DynamicImage, GenericImageView as _, Rgba, RgbaImage};
+
+/// Gap between images in composite
+const MARGIN: u32 = 20;
+const BACKGROUND: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+pub struct ImageDiff {
+    /// Old image, new image, and difference heat map, side-by-side
+    pub composite: RgbaImage,
+    /// Proportion of compared pixels which differ, from 0.0 to 1.0
+    pub changed_ratio: f64,
+}
+
+/// Heat map compares overlapping area only, if image sizes differ
+pub fn diff_images(old: &DynamicImage, new: &DynamicImage) -> ImageDiff {
+    let (heat_map, changed_ratio) = create_heat_map(old, new);
+
+    let width = old.width() + new.width() + heat_map.width() + MARGIN * 2;
+    let height = old.height().max(new.height()).max(heat_map.height());
+    let mut composite = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+    let mut x = 0;
+    imageops::overlay(&mut composite, &old.to_rgba8(), x, 0);
+    x += (old.width() + MARGIN) as i64;
+    imageops::overlay(&mut composite, &new.to_rgba8(), x, 0);
+    x += (new.width() + MARGIN) as i64;
+    imageops::overlay(&mut composite, &heat_map, x, 0);
+
+    ImageDiff {
+        composite,
+        changed_ratio,
+    }
+}
+
+/// Changed pixels are red (brighter for greater difference), unchanged pixels are a faded grey
+fn create_heat_map(old: &DynamicImage, new: &DynamicImage) -> (RgbaImage, f64) {
+    let width = old.width().min(new.width());
+    let height = old.height().min(new.height());
+    let mut heat_map = RgbaImage::new(width, height);
+    let mut changed_count: u64 = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let Rgba(a) = old.get_pixel(x, y);
+            let Rgba(b) = new.get_pixel(x, y);
+            let difference: u32 = a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| a.abs_diff(*b) as u32)
+                .sum();
+
+            let pixel = if difference == 0 {
+                let luma = ((b[0] as u32 + b[1] as u32 + b[2] as u32) / 3 / 4) as u8;
+                Rgba([luma, luma, luma, 255])
+            } else {
+                changed_count += 1;
+                let intensity = (128 + difference.min(4 * 255) * 127 / (4 * 255)) as u8;
+                Rgba([intensity, 0, 0, 255])
+            };
+            heat_map.put_pixel(x, y, pixel);
+        }
+    }
+
+    let total = width as u64 * height as u64;
+    let changed_ratio = if total == 0 {
+        0.0
+    } else {
+        changed_count as f64 / total as f64
+    };
+    (heat_map, changed_ratio)
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+
+/// Calls `on_file` with file name and contents, for every file in a directory (recursively) or zip
+/// archive, in order of path
+pub fn read_files(path: &Path, mut on_file: impl FnMut(&str, &[u8]) -> Result<()>) -> Result<()> {
+    if path.is_dir() {
+        let mut paths = Vec::new();
+        collect_dir_files(path, &mut paths)?;
+        paths.sort();
+        for path in paths {
+            let bytes = fs::read(&path).with_context(|| "Reading file to import")?;
+            on_file(&path.file_name().unwrap().to_string_lossy(), &bytes)?;
+        }
+        return Ok(());
+    }
+
+    let file = fs::File::open(path).with_context(|| "Opening archive")?;
+    let mut archive = zip::ZipArchive::new(file).with_context(|| "Reading zip archive")?;
+    let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+    names.sort();
+    for name in names {
+        let mut entry = archive
+            .by_name(&name)
+            .with_context(|| "Reading file in archive")?;
+        if !entry.is_file() {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .with_context(|| "Extracting file from archive")?;
+        let file_name = name.rsplit('/').next().unwrap_or(&name);
+        on_file(file_name, &bytes)?;
+    }
+    Ok(())
+}
+
+fn collect_dir_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| "Reading import directory")? {
+        let path = entry
+            .with_context(|| "Reading import directory entry")?
+            .path();
+        if path.is_dir() {
+            collect_dir_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Recognizes `2024-01-31`, `2024_01_31`, `2024.01.31`, `20240131`, and `ga240131`, anywhere in
+/// file name (ignoring extension), such as `garfield-2024-01-31.gif`
+pub fn parse_file_date(file_name: &str) -> Option<NaiveDate> {
+    let stem = match file_name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file_name,
+    };
+    let groups: Vec<&str> = stem
+        .split(|ch: char| !ch.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .collect();
+
+    for window in groups.windows(3) {
+        if let [year, month, day] = window {
+            if year.len() == 4 && month.len() == 2 && day.len() == 2 {
+                if let Some(date) = date_from_parts(year, month, day) {
+                    return Some(date);
+                }
+            }
+        }
+    }
+    for group in &groups {
+        let date = match group.len() {
+            8 => date_from_parts(&group[..4], &group[4..6], &group[6..]),
+            // Years `69` to `99` are read as 1900s, which covers every comic
+            6 => NaiveDate::parse_from_str(group, "%y%m%d").ok(),
+            _ => None,
+        };
+        if date.is_some() {
+            return date;
+        }
+    }
+    None
+}
+
+fn date_from_parts(year: &str, month: &str, day: &str) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+}
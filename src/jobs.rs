@@ -0,0 +1,110 @@
+use crate::location::Location;
+
+use std::fmt;
+use std::fs;
+use std::io::{Read as _, Seek as _, SeekFrom, Write as _};
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+use fs2::FileExt as _;
+use serde::{Deserialize, Serialize};
+
+/// Work queued to be run later with `jobs run`, eg. from another device with `serve`.
+/// Line of jobs file, as `<kind>\t<date or id>`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Job {
+    Make { date: NaiveDate },
+    Transcribe { id: String },
+}
+
+impl Job {
+    fn from_line(line: &str) -> Option<Self> {
+        let (kind, target) = line.trim().split_once('\t')?;
+        match kind {
+            "make" => Some(Self::Make {
+                date: NaiveDate::parse_from_str(target, "%Y-%m-%d").ok()?,
+            }),
+            "transcribe" => Some(Self::Transcribe {
+                id: target.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Self::Make { date } => format!("make\t{}", date),
+            Self::Transcribe { id } => format!("transcribe\t{}", id),
+        }
+    }
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Make { date } => write!(f, "make {}", date),
+            Self::Transcribe { id } => write!(f, "transcribe {}", id),
+        }
+    }
+}
+
+/// In order of queueing. Skips malformed lines.
+pub fn load(location: &Location) -> Result<Vec<Job>> {
+    let path = location.jobs_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading jobs file")?;
+    Ok(contents.lines().filter_map(Job::from_line).collect())
+}
+
+/// Does not need the location to be locked, since `serve` appends while `jobs run` is running.
+/// The jobs file itself is locked instead, so a job is not lost while another is being removed.
+pub fn append(location: &Location, job: &Job) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(location.jobs_file())
+        .with_context(|| "Opening jobs file")?;
+    file.lock_exclusive().with_context(|| "Locking jobs file")?;
+    writeln!(file, "{}", job.to_line()).with_context(|| "Writing to jobs file")
+}
+
+/// Removes first job equal to `job`. File is read again while locked, to keep jobs which were
+/// appended since it was loaded.
+pub fn remove(location: &Location, job: &Job) -> Result<()> {
+    modify(location, |jobs| {
+        if let Some(index) = jobs.iter().position(|queued| queued == job) {
+            jobs.remove(index);
+        }
+    })
+}
+
+pub fn clear(location: &Location) -> Result<()> {
+    modify(location, Vec::clear)
+}
+
+/// Lock is released when file is closed
+fn modify(location: &Location, update: impl FnOnce(&mut Vec<Job>)) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(location.jobs_file())
+        .with_context(|| "Opening jobs file")?;
+    file.lock_exclusive().with_context(|| "Locking jobs file")?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .with_context(|| "Reading jobs file")?;
+    let mut jobs: Vec<Job> = contents.lines().filter_map(Job::from_line).collect();
+    update(&mut jobs);
+
+    let contents: String = jobs.iter().map(|job| job.to_line() + "\n").collect();
+    file.set_len(0)
+        .and_then(|()| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| file.write_all(contents.as_bytes()))
+        .with_context(|| "Writing jobs file")
+}
@@ -1,30 +1,118 @@
 pub mod actions;
 // TODO(refactor): Rename module `names`
 pub mod names;
+pub mod output;
 
+mod backup;
+mod calendar;
+mod caption;
+mod checkpoint;
+mod checksum;
+mod cleanup;
+mod clipboard;
 mod commands;
+mod config;
+mod crypt;
+mod dedupe;
+mod error;
+mod feed;
 // TODO(refactor): Rename module `file`
 mod file;
+mod generation;
+mod glossary;
+mod history;
+mod hooks;
+mod image_diff;
+mod import;
+mod jobs;
 mod location;
+mod lock;
+mod notify;
+mod ocr;
+mod optimize;
+mod pipeline;
+mod preview;
+mod props;
+mod publish;
 mod random;
 mod range;
+mod ratings;
+mod recent;
+mod report;
+mod schedule;
+mod select;
+#[cfg(feature = "server")]
+pub mod server;
+mod setup;
+mod sheet;
+mod site;
+mod skips;
+mod source_dates;
+mod special;
+mod submissions;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text_diff;
+mod thumbnail;
+mod timings;
+mod transcript;
+mod trash;
+mod ui;
+#[cfg(feature = "self-update")]
+pub mod update;
+mod uploads;
+mod vcs;
+mod watch;
+mod watermarks;
 
-pub use location::Location;
+pub use checkpoint::Checkpoint;
+pub use cleanup::init_signal_handler;
+pub use commands::{
+    has_display, init_process_registry, HeadlessSystem, RealSystem, SystemCommands, WindowManager,
+};
+pub use config::{Config, PostLayout, Variant};
+pub use crypt::init as init_encryption;
+pub use error::{exit_code, Error, ErrorKind};
+#[cfg(feature = "testing")]
+pub use file::sample_entry;
+pub use generation::MakeContext;
+pub use location::{Location, Stage};
+pub use lock::acquire as lock_location;
+pub use notify::notify_finished;
+#[cfg(feature = "testing")]
+pub use pipeline::Stage as PipelineStage;
+pub use publish::PublishTarget;
 pub use random::init_rng;
-pub use range::DateRange;
+pub use range::{DateRange, FullDateRange, WeekdayFilter, YearMonth, YearOrMonth};
+pub use recent::init_command_line;
+pub use report::ReportFormat;
+pub use setup::{offer_wizard as offer_setup_wizard, run_wizard as run_setup_wizard};
+pub use special::SpecialKind;
+pub use timings::{enable as enable_timings, finish as finish_timings};
+pub use transcript::TranscriptFormat;
+pub use ui::{PromptPolicy, Prompter, TerminalPrompter};
 
 mod constants {
     pub const SOURCE_FORMAT: &str = "png";
     pub mod post_file {
-        pub const INITIAL: &str = "esperanto.png";
-        pub const DUPLICATE: &str = "english.png";
-        pub const SVG: &str = "esperanto.svg";
         pub const TITLE: &str = "title";
         pub const DATE: &str = "date";
         pub const TRANSCRIPT: &str = "transcript";
         pub const PROPS: &str = "props";
         pub const SPECIAL: &str = "special";
+        pub const NOTES: &str = "notes";
+        /// Watermark text used when image was generated
+        pub const WATERMARK: &str = "watermark";
+        /// Hash of inputs that images were generated from, so `revise` can skip regenerating
+        pub const GENERATION: &str = "generation";
+        /// Date of most recent upload
+        pub const UPLOADED: &str = "uploaded";
+        /// Hashes of every other file, in `sha256sum` format
+        pub const CHECKSUMS: &str = "checksums.sha256";
+        /// Transcript submitted with `serve`, which has not been reviewed
+        pub const PENDING_TRANSCRIPT: &str = "transcript.pending";
     }
+    /// Defaults, if not set in config
     pub mod window_name {
         pub const TRANSCRIBE: &str = "garfutils-transcribe";
         pub const SHOW: &str = "garfutils-show";
@@ -37,3 +125,24 @@ pub fn confirm(prompt: &str) {
     io::stdout().flush().expect("failed to flush stdout");
     file::discard_read_line(&mut io::stdin());
 }
+
+/// Returns `true` only if the answer is `y` or `yes` (case-insensitive)
+pub fn ask_yes_no(prompt: &str) -> bool {
+    let answer = ask(&format!("{} [y/N]", prompt));
+    matches!(answer.to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Returns trimmed line of input
+pub fn ask(prompt: &str) -> String {
+    use std::io::{self, Write as _};
+    print!("{} ", prompt);
+    io::stdout().flush().expect("failed to flush stdout");
+    if let Some(answer) = file::take_pending_input() {
+        return answer.unwrap_or_default().trim().to_string();
+    }
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .expect("failed to read stdin");
+    answer.trim().to_string()
+}
@@ -1,21 +1,69 @@
-use std::path::PathBuf;
+use crate::commands::{RealSystem, SystemCommands, WindowManager};
+use crate::config::{Config, PostLayout};
+use crate::constants::SOURCE_FORMAT;
+use crate::file;
+use crate::output;
+use crate::ui::{Prompter, TerminalPrompter};
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
+use chrono::{Datelike as _, NaiveDate};
+
+/// Directory which a post can be in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Stage {
+    Generated,
+    Completed,
+    Old,
+}
 
 pub struct Location {
     base_dir: PathBuf,
+    /// Regenerable files. Same as base directory, unless location is the default location.
+    cache_dir: PathBuf,
+    /// Runtime state, which is not worth backing up. Same as base directory, unless location is
+    /// the default location.
+    state_dir: PathBuf,
+    config: Config,
+    system: Box<dyn SystemCommands>,
+    prompter: Box<dyn Prompter>,
 }
 
 impl Location {
-    const DEFAULT_LOCATION_NAME: &str = "garfutils"; // $XDG_DATA_DIR/<name>/
+    const DEFAULT_LOCATION_NAME: &str = "garfutils"; // $XDG_{DATA,CACHE,STATE}_HOME/<name>/
     const SOURCE_DIR: &str = "source";
     const GENERATED_DIR: &str = "generated";
     const POSTS_DIR: &str = "posts";
     const OLD_DIR: &str = "old";
+    const TRASH_DIR: &str = "trash"; // Created when needed
     const TEMP_DIR: &str = "tmp"; // Not using `/tmp` to ensure same mount point as destination
+    const THUMBNAILS_DIR: &str = "thumbnails"; // Created when needed
+    const CHECKPOINTS_DIR: &str = "checkpoints"; // Created when needed
     const RECENT_FILE: &str = "recent";
+    const RATINGS_FILE: &str = "ratings"; // Optional
+    const SKIPS_FILE: &str = "skipped"; // Optional
     const WATERMARKS_FILE: &str = "watermarks";
     const ICON_FILE: &str = "icon.png";
+    const ICONS_DIR: &str = "icons"; // Optional
+    const PROCESS_REGISTRY_FILE: &str = "viewers";
+    const CONFIG_FILE: &str = "config.toml"; // Optional
+    const SCHEDULE_FILE: &str = "schedule.toml"; // Optional
+    const UPLOADS_FILE: &str = "uploads"; // Optional
+    const JOBS_FILE: &str = "jobs"; // Optional
+    const CLAIMS_FILE: &str = "claims"; // Optional
+    const SUBMISSIONS_DIR: &str = "submissions"; // Created when needed
+    const HOOKS_DIR: &str = "hooks"; // Optional
+    const BACKUPS_DIR: &str = "backups"; // Created when needed
+    const LOCK_FILE: &str = "lock";
+    const WRAPPED_KEY_FILE: &str = "encryption-key.age"; // Created when needed
+    const SOURCE_DATES_FILE: &str = "source-dates"; // Cache, created when needed
+    const TIMINGS_FILE: &str = "timings"; // Created when needed
+    const CAPTION_TEMPLATE_FILE: &str = "templates/caption.tmpl"; // Optional
+    const DICTIONARY_FILE: &str = "dictionary"; // Optional
+    const GLOSSARY_FILE: &str = "glossary.toml"; // Optional
 
     pub fn source_dir(&self) -> PathBuf {
         self.base_dir.join(Self::SOURCE_DIR)
@@ -29,11 +77,40 @@ impl Location {
     pub fn old_dir(&self) -> PathBuf {
         self.base_dir.join(Self::OLD_DIR)
     }
+    pub fn trash_dir(&self) -> PathBuf {
+        self.base_dir.join(Self::TRASH_DIR)
+    }
+    pub fn stage_dir(&self, stage: Stage) -> PathBuf {
+        match stage {
+            Stage::Generated => self.generated_dir(),
+            Stage::Completed => self.posts_dir(),
+            Stage::Old => self.old_dir(),
+        }
+    }
     pub fn temp_dir(&self) -> PathBuf {
         self.base_dir.join(Self::TEMP_DIR)
     }
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir
+    }
+    pub fn thumbnails_dir(&self) -> PathBuf {
+        self.cache_dir.join(Self::THUMBNAILS_DIR)
+    }
+    /// Progress of an unfinished multi-step flow for a post
+    pub fn checkpoint_file(&self, id: &str) -> PathBuf {
+        self.base_dir.join(Self::CHECKPOINTS_DIR).join(id)
+    }
+    pub fn ratings_file(&self) -> PathBuf {
+        self.base_dir.join(Self::RATINGS_FILE)
+    }
+    pub fn skips_file(&self) -> PathBuf {
+        self.base_dir.join(Self::SKIPS_FILE)
+    }
     pub fn recent_file(&self) -> PathBuf {
-        self.base_dir.join(Self::RECENT_FILE)
+        self.state_dir.join(Self::RECENT_FILE)
     }
     pub fn watermarks_file(&self) -> PathBuf {
         self.base_dir.join(Self::WATERMARKS_FILE)
@@ -41,16 +118,230 @@ impl Location {
     pub fn icon_file(&self) -> PathBuf {
         self.base_dir.join(Self::ICON_FILE)
     }
+    /// Icon of first matching rule in `make.icons`, otherwise `icon.<year>.png` if it exists,
+    /// otherwise `icon.png`
+    pub fn icon_for_date(&self, date: NaiveDate) -> PathBuf {
+        let year = date.year();
+        if let Some(rule) = self
+            .config
+            .make
+            .icons
+            .iter()
+            .find(|rule| rule.matches(year))
+        {
+            return self.base_dir.join(Self::ICONS_DIR).join(&rule.file);
+        }
+        let year_icon = self.base_dir.join(format!("icon.{}.png", year));
+        if year_icon.is_file() {
+            return year_icon;
+        }
+        self.icon_file()
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.base_dir.join(Self::CONFIG_FILE)
+    }
+    pub fn schedule_file(&self) -> PathBuf {
+        self.base_dir.join(Self::SCHEDULE_FILE)
+    }
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.base_dir.join(Self::HOOKS_DIR)
+    }
+    /// Executable run before or after an action, eg. `post-make`
+    pub fn hook_file(&self, name: &str) -> PathBuf {
+        self.hooks_dir().join(name)
+    }
+    /// Snapshots made by `backup`, unless `backup.dir` is set in config
+    pub fn backups_dir(&self) -> PathBuf {
+        self.base_dir.join(Self::BACKUPS_DIR)
+    }
+    pub fn uploads_file(&self) -> PathBuf {
+        self.base_dir.join(Self::UPLOADS_FILE)
+    }
+    /// Queue of work for `jobs run`
+    pub fn jobs_file(&self) -> PathBuf {
+        self.base_dir.join(Self::JOBS_FILE)
+    }
+    /// Secret key for `encryption.passphrase`, itself encrypted with the passphrase
+    pub fn wrapped_key_file(&self) -> PathBuf {
+        self.base_dir.join(Self::WRAPPED_KEY_FILE)
+    }
+    /// Posts being transcribed by someone else, with `serve`
+    pub fn claims_file(&self) -> PathBuf {
+        self.base_dir.join(Self::CLAIMS_FILE)
+    }
+    pub fn submissions_dir(&self) -> PathBuf {
+        self.base_dir.join(Self::SUBMISSIONS_DIR)
+    }
+    /// Transcript submitted with `serve`, until moved into post with `pull-queue`
+    pub fn submission_file(&self, id: &str) -> PathBuf {
+        self.submissions_dir().join(id)
+    }
+    pub fn caption_template_file(&self) -> PathBuf {
+        self.base_dir.join(Self::CAPTION_TEMPLATE_FILE)
+    }
+    pub fn dictionary_file(&self) -> PathBuf {
+        self.base_dir.join(Self::DICTIONARY_FILE)
+    }
+    pub fn glossary_file(&self) -> PathBuf {
+        self.base_dir.join(Self::GLOSSARY_FILE)
+    }
+    /// Outside of source directory, so writing it does not make itself stale
+    pub fn source_dates_file(&self) -> PathBuf {
+        self.cache_dir.join(Self::SOURCE_DATES_FILE)
+    }
+    /// Always in base directory, so every path to the location (eg. through a symlink) and older
+    /// versions share the same lock
+    pub fn lock_file(&self) -> PathBuf {
+        self.base_dir.join(Self::LOCK_FILE)
+    }
+    /// Log of runs with `--timings`
+    pub fn timings_file(&self) -> PathBuf {
+        self.state_dir.join(Self::TIMINGS_FILE)
+    }
+    pub fn process_registry_file(&self) -> PathBuf {
+        self.state_dir.join(Self::PROCESS_REGISTRY_FILE)
+    }
+
+    /// Unsaved transcript of a post, while it is being edited
+    pub fn temp_transcript_file(&self, id: &str) -> PathBuf {
+        // "{temp_dir}/transcript.{id}"
+        let mut path = self.temp_dir().join("transcript");
+        path.set_extension(id);
+        path
+    }
+
+    /// Original comic image for a date (may not exist)
+    ///
+    /// Either `source/<date>.png` or `source/<year>/<date>.png`. Flat file takes precedence, and
+    /// nested file is only used if year directory exists.
+    pub fn source_file(&self, date: NaiveDate) -> PathBuf {
+        let file_name = format!("{}.{}", date, SOURCE_FORMAT);
+        let flat_path = self.source_dir().join(&file_name);
+        if flat_path.exists() {
+            return flat_path;
+        }
+        let year_dir = self.source_year_dir(date.year());
+        if year_dir.is_dir() {
+            return year_dir.join(file_name);
+        }
+        flat_path
+    }
+    pub fn source_year_dir(&self, year: i32) -> PathBuf {
+        self.source_dir().join(year.to_string())
+    }
 
     pub fn from(base_dir: Option<PathBuf>) -> Result<Self> {
         let base_dir = Self::get_base_dir(base_dir)?;
-        let location = Self { base_dir };
+        let is_default = is_same_dir(&base_dir, &Self::default_base_dir()?);
+        let mut location = Self::from_unchecked(base_dir);
         location
             .check_dirs_exist()
             .with_context(|| "Checking directory structure is valid")?;
+        location
+            .check_same_filesystem()
+            .with_context(|| "Checking filesystems of directories")?;
+        // Other locations keep everything in base directory, so they do not share a lock or cache
+        if is_default {
+            location.cache_dir = Self::get_cache_dir()?;
+            location.state_dir = Self::get_state_dir()?;
+            for dir in [&location.cache_dir, &location.state_dir] {
+                fs::create_dir_all(dir).with_context(|| format!("Creating directory {:?}", dir))?;
+            }
+            location
+                .migrate_files()
+                .with_context(|| "Moving cache and state files out of location")?;
+        }
+        location.config = Config::load(location.config_file())?;
+        location.system = Box::new(RealSystem {
+            window_manager: location.config.viewer.window_manager,
+        });
+        Ok(location)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+    pub fn layout(&self) -> &PostLayout {
+        &self.config.layout
+    }
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    pub fn system(&self) -> &dyn SystemCommands {
+        self.system.as_ref()
+    }
+    pub fn set_system(&mut self, system: impl SystemCommands + 'static) {
+        self.system = Box::new(system);
+    }
+
+    pub fn prompter(&self) -> &dyn Prompter {
+        self.prompter.as_ref()
+    }
+    pub fn set_prompter(&mut self, prompter: impl Prompter + 'static) {
+        self.prompter = Box::new(prompter);
+    }
+
+    /// Does not check that directory structure is valid
+    pub(crate) fn from_unchecked(base_dir: PathBuf) -> Self {
+        Self {
+            cache_dir: base_dir.clone(),
+            state_dir: base_dir.clone(),
+            base_dir,
+            config: Config::default(),
+            system: Box::new(RealSystem {
+                window_manager: WindowManager::default(),
+            }),
+            prompter: Box::new(TerminalPrompter),
+        }
+    }
+
+    /// Empty location in a new temporary directory, with an icon and watermarks file.
+    /// External programs are recorded instead of run, until replaced with `set_system`.
+    #[cfg(feature = "testing")]
+    pub fn create_ephemeral() -> Result<Self> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!(
+            "garfutils-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut location = Self::from_unchecked(std::env::temp_dir().join(name));
+        location.set_system(crate::testing::RecordingSystem::default());
+        location.create_dirs()?;
+        fs::create_dir_all(location.source_dir()).with_context(|| "Creating source directory")?;
+        image::RgbaImage::from_pixel(32, 32, image::Rgba([255, 0, 0, 255]))
+            .save(location.icon_file())
+            .with_context(|| "Saving icon image")?;
+        fs::write(location.watermarks_file(), "test").with_context(|| "Writing watermarks file")?;
         Ok(location)
     }
 
+    /// Location used when `--location` is not given
+    pub fn default_base_dir() -> Result<PathBuf> {
+        Self::get_base_dir(None)
+    }
+
+    /// Creates any missing directories, except source directory
+    pub(crate) fn create_dirs(&self) -> Result<()> {
+        for dir in [
+            self.base_dir.clone(),
+            self.generated_dir(),
+            self.posts_dir(),
+            self.old_dir(),
+        ] {
+            fs::create_dir_all(&dir).with_context(|| format!("Creating directory {:?}", dir))?;
+        }
+        Ok(())
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
     fn get_base_dir(base_dir: Option<PathBuf>) -> Result<PathBuf> {
         if let Some(path) = base_dir {
             return Ok(path);
@@ -65,6 +356,93 @@ impl Location {
         );
     }
 
+    fn get_cache_dir() -> Result<PathBuf> {
+        let Some(path) = dirs_next::cache_dir() else {
+            bail!("Reading standard cache location. Try setting `$XDG_CACHE_HOME` or `$HOME`.");
+        };
+        Ok(path.join(Self::DEFAULT_LOCATION_NAME))
+    }
+
+    /// `$XDG_STATE_HOME`, which is not supported by `dirs_next`
+    fn get_state_dir() -> Result<PathBuf> {
+        let path = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                if cfg!(any(target_os = "macos", windows)) {
+                    dirs_next::data_local_dir()
+                } else {
+                    dirs_next::home_dir().map(|home| home.join(".local/state"))
+                }
+            });
+        let Some(path) = path else {
+            bail!("Reading standard state location. Try setting `$XDG_STATE_HOME` or `$HOME`.");
+        };
+        Ok(path.join(Self::DEFAULT_LOCATION_NAME))
+    }
+
+    /// Moves files which used to be in base directory, if they have not been moved already
+    fn migrate_files(&self) -> Result<()> {
+        let moved = [
+            (
+                self.base_dir.join(Self::THUMBNAILS_DIR),
+                self.thumbnails_dir(),
+            ),
+            (
+                self.base_dir.join(Self::SOURCE_DATES_FILE),
+                self.source_dates_file(),
+            ),
+            (self.base_dir.join(Self::RECENT_FILE), self.recent_file()),
+            (
+                self.temp_dir().join(Self::PROCESS_REGISTRY_FILE),
+                self.process_registry_file(),
+            ),
+        ];
+        for (old_path, new_path) in moved {
+            if !old_path.exists() || new_path.exists() {
+                continue;
+            }
+            file::rename_or_copy(&old_path, &new_path)
+                .with_context(|| format!("Moving {:?} to {:?}", old_path, new_path))?;
+        }
+        Ok(())
+    }
+
+    /// Posts are moved between these directories (and from temp directory). Any of them may be a
+    /// symlink to another filesystem, where posts are copied instead of renamed, which is slower
+    /// and not atomic.
+    fn check_same_filesystem(&self) -> Result<()> {
+        // Temp directory is created when needed, inside base directory
+        let temp_dir = if self.temp_dir().is_dir() {
+            self.temp_dir()
+        } else {
+            self.base_dir.clone()
+        };
+        let dirs = [
+            (Self::TEMP_DIR, temp_dir),
+            (Self::GENERATED_DIR, self.generated_dir()),
+            (Self::POSTS_DIR, self.posts_dir()),
+            (Self::OLD_DIR, self.old_dir()),
+        ];
+        let (first_name, first_dir) = &dirs[0];
+        for (name, dir) in &dirs[1..] {
+            if !file::is_same_filesystem(first_dir, dir)
+                .with_context(|| format!("Reading filesystem of `{}`", name))?
+            {
+                let resolved = fs::canonicalize(dir)
+                    .with_context(|| format!("Resolving symlink of `{}`", name))?;
+                // Not stdout, which may be machine-readable (eg. `feed`)
+                eprintln!(
+                    "(note: `{}` ({}) is on a different filesystem to `{}`, so posts are copied instead of moved)",
+                    name,
+                    resolved.display(),
+                    first_name,
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn check_dirs_exist(&self) -> Result<()> {
         if !self.base_dir.is_dir() {
             bail!(
@@ -109,7 +487,7 @@ impl Location {
                 Please ensure that these files and directories exist.\n\
                 Each item may be a symlink.\n\
                 \n\
-                \x1b[4m{}/\x1b[0m\n\
+                {}\n\
                     \t├─ {}/\n\
                     \t├─ {}/\n\
                     \t├─ {}/\n\
@@ -118,7 +496,7 @@ impl Location {
                 \n\
                 If the `--location` option wasn't provided, the default location `$XDG_DATA_HOME/{}` will be used.
             ",
-            self.base_dir.to_string_lossy(),
+            output::underline(format!("{}/", self.base_dir.to_string_lossy())),
             Self::SOURCE_DIR,
             Self::GENERATED_DIR,
             Self::POSTS_DIR,
@@ -128,3 +506,27 @@ impl Location {
         )
     }
 }
+
+impl Stage {
+    pub const ALL: [Self; 3] = [Self::Generated, Self::Completed, Self::Old];
+
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            Self::Generated => Location::GENERATED_DIR,
+            Self::Completed => Location::POSTS_DIR,
+            Self::Old => Location::OLD_DIR,
+        }
+    }
+
+    pub fn from_dir_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|stage| stage.dir_name() == name)
+    }
+}
+
+/// Default location is usually a symlink to the real directory (see `setup`)
+fn is_same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
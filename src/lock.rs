@@ -0,0 +1,80 @@
+use crate::cleanup;
+use crate::commands;
+use crate::location::Location;
+
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use fs2::FileExt as _;
+
+const WAIT_DELAY: Duration = Duration::from_millis(500);
+
+/// Prevents other instances from modifying the location, until guard is dropped.
+/// With `wait`, blocks until the location is free, otherwise fails immediately.
+///
+/// The lock is held by the operating system, so it is released even if the program crashes. The
+/// lock file itself is never removed. The PID written to it is shown to other instances, and
+/// respected from older versions, which do not lock the file.
+pub fn acquire(location: &Location, wait: bool) -> Result<cleanup::Guard> {
+    let path = location.lock_file();
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| "Opening lock file")?;
+    let mut has_printed_waiting = false;
+
+    loop {
+        match file.try_lock_exclusive() {
+            Ok(()) if !is_held_by_older_version(&path) => break,
+            Ok(()) => {
+                let _ = file.unlock();
+            }
+            Err(error) if error.kind() == fs2::lock_contended_error().kind() => (),
+            Err(error) => return Err(error).with_context(|| "Locking lock file"),
+        }
+
+        let holder = match read_lock_pid(&path) {
+            Some(pid) => format!("PID {}", pid),
+            None => "another instance".to_string(),
+        };
+        if !wait {
+            bail!(
+                "Location is already in use by {}.\n\
+                Run with `--wait` to wait until it is free.",
+                holder
+            );
+        }
+        if !has_printed_waiting {
+            println!("(waiting for {} to finish...)", holder);
+            has_printed_waiting = true;
+        }
+        thread::sleep(WAIT_DELAY);
+    }
+
+    file.set_len(0).with_context(|| "Writing lock file")?;
+    writeln!(file, "{}", process::id()).with_context(|| "Writing lock file")?;
+    Ok(cleanup::register(cleanup::Task::Unlock(file)))
+}
+
+/// Older versions only write their PID to the lock file, without locking it
+fn is_held_by_older_version(path: &Path) -> bool {
+    read_lock_pid(path)
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid != process::id() && commands::is_pid_running(pid))
+}
+
+/// `None` if lock file is empty (eg. just created by the holder), or cannot be read (eg. on
+/// Windows, where locked files cannot be read by other processes)
+fn read_lock_pid(path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    let pid = contents.trim();
+    (!pid.is_empty()).then(|| pid.to_string())
+}
@@ -1,56 +1,717 @@
 mod args;
+mod help;
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use std::io::{self, IsTerminal as _};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-use garfutils::{actions, confirm, names, Location};
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use clap::{CommandFactory as _, FromArgMatches as _};
 
-fn main() -> Result<()> {
+use garfutils::{actions, names, output, Checkpoint, DateRange, Error, Location, MakeContext};
+
+const REVISE_FLOW: &str = "revise";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{} {:?}", output::error_label(), error);
+            garfutils::exit_code(&error)
+        }
+    }
+}
+
+fn run() -> Result<()> {
     garfutils::init_rng();
-    let args = args::Args::parse();
-    let location = Location::from(args.location).with_context(|| "Parsing directory location")?;
+    garfutils::init_command_line(std::env::args().skip(1));
+    garfutils::init_signal_handler()?;
+    let matches = args::Args::command().get_matches();
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+    let args = args::Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    output::init(args.color);
+    if args.timings {
+        garfutils::enable_timings();
+    }
+    // Location may not be valid yet, or is not needed
+    match args.command {
+        args::Command::Setup => {
+            let suggested_dir = match args.location {
+                Some(base_dir) => base_dir,
+                None => Location::default_base_dir()?,
+            };
+            garfutils::run_setup_wizard(&suggested_dir).with_context(|| "Running setup")?;
+            return Ok(());
+        }
+        args::Command::Version { check } => return version(check),
+        args::Command::Guide => {
+            print!("{}", help::guide());
+            return Ok(());
+        }
+        #[cfg(feature = "self-update")]
+        args::Command::SelfUpdate { dry_run } => {
+            if let Some(release) = garfutils::update::print_check()? {
+                if !dry_run {
+                    garfutils::update::install(&release).with_context(|| "Installing update")?;
+                }
+            }
+            return Ok(());
+        }
+        _ => (),
+    }
+    let base_dir = match args.location {
+        Some(base_dir) => Some(base_dir),
+        None => garfutils::offer_setup_wizard(&Location::default_base_dir()?)
+            .with_context(|| "Running first-time setup")?,
+    };
+    let mut location = Location::from(base_dir).with_context(|| "Parsing directory location")?;
+    location.config_mut().viewer.instance = args.instance;
+    location.config_mut().prompts.abort_on_no = args.abort_on_no;
+    if let Some(author) = args.author {
+        location.config_mut().identity.name = Some(author);
+    }
+    garfutils::init_process_registry(location.process_registry_file());
+    garfutils::init_encryption(&location);
+    if args.no_gui || !garfutils::has_display() {
+        location.set_system(garfutils::HeadlessSystem);
+    }
+    let _lock = if args.command.requires_lock() {
+        Some(garfutils::lock_location(&location, args.wait).with_context(|| "Locking location")?)
+    } else {
+        None
+    };
 
     match args.command {
         args::Command::Show {
             date,
             sunday,
+            weekday,
             range,
+            today,
+            anniversary,
+            between,
             just_print,
+            slideshow,
+            count,
+            min_rating,
+            rate,
+            new,
         } => {
-            let input = names::get_show_input(date, range, sunday);
-            let date = names::get_show_date(&location, input).with_context(|| "Parsing date")?;
-            println!("{}", date);
-            if !just_print {
-                actions::show(&location, date).with_context(|| "Showing comic")?;
+            let anniversary = if today {
+                Some(Local::now().date_naive())
+            } else {
+                anniversary
+            };
+            let range = anniversary.map(DateRange::anniversary_of).or(range);
+            let input =
+                names::get_show_input(date, range, between, sunday, &weekday, min_rating, new);
+            if let Some(delay) = slideshow {
+                let dates = names::get_show_dates(&location, input, count)
+                    .with_context(|| "Parsing dates")?;
+                for date in &dates {
+                    println!("{}", date);
+                }
+                if !just_print {
+                    actions::show_slideshow(&location, &dates, delay)
+                        .with_context(|| "Showing comics")?;
+                }
+            } else {
+                let date =
+                    names::get_show_date(&location, input).with_context(|| "Parsing date")?;
+                println!("{}", date);
+                if !just_print {
+                    actions::show(&location, date).with_context(|| "Showing comic")?;
+                }
+                if rate {
+                    actions::ask_rating(&location, date).with_context(|| "Rating comic")?;
+                }
             }
         }
 
-        args::Command::Make { date, recent } => {
+        args::Command::Special { id, kind } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::special(&location, &id, kind).with_context(|| "Editing special file")?;
+        }
+
+        args::Command::Skip {
+            date,
+            reason,
+            remove,
+        } => {
+            actions::skip(&location, date, reason.as_deref(), remove)
+                .with_context(|| "Updating skip list")?;
+        }
+
+        args::Command::Rate { date, rating } => {
+            actions::rate(&location, date, rating).with_context(|| "Rating comic")?;
+        }
+
+        args::Command::Make {
+            between: Some(between),
+            ..
+        } => {
+            let started = Instant::now();
+            let result = actions::make_between(&location, between);
+            garfutils::notify_finished(&location, "Generating posts", started, &result);
+            result.with_context(|| "Generating posts")?;
+        }
+
+        args::Command::Make {
+            date: Some(date),
+            from_file: Some(path),
+            save_source,
+            special,
+            ..
+        } => {
+            let name = names::generate_name(date);
+            actions::make_from_file(&location, &path, date, &name, save_source, special)
+                .with_context(|| "Generating post from file")?;
+        }
+
+        args::Command::Make {
+            best_unposted: true,
+            special,
+            ..
+        } => {
+            let date = names::get_best_unposted_date(&location)
+                .with_context(|| "Finding best unposted comic")?;
+            println!("{}", date);
+            let name = names::generate_name(date);
+            actions::make(&location, date, &name, false, special)
+                .with_context(|| "Generating post")?;
+        }
+
+        args::Command::Make {
+            date,
+            recent,
+            special,
+            ..
+        } => {
             let date =
                 names::get_make_date(&location, date, recent).with_context(|| "Parsing date")?;
             let name = names::generate_name(date);
-            actions::make(&location, date, &name, false).with_context(|| "Generating post")?;
+            actions::make(&location, date, &name, false, special)
+                .with_context(|| "Generating post")?;
         }
 
-        args::Command::Transcribe { id } => {
-            let id = names::get_transcribe_id(&location, id).with_context(|| "Parsing post id")?;
-            actions::transcribe(&location, &id).with_context(|| "Transcribing post")?;
+        args::Command::Finish { id, new_id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::finish(&location, &id, new_id).with_context(|| "Finishing post")?;
+        }
+
+        args::Command::Transcribe {
+            id,
+            draft,
+            lang,
+            interactive,
+            first,
+        } => {
+            let id = id
+                .map(|id| names::resolve_id(&location, &id))
+                .transpose()
+                .with_context(|| "Resolving post id")?;
+            let interactive = is_interactive(interactive, first);
+            let id = names::get_transcribe_id(&location, id, lang.as_deref(), interactive)
+                .with_context(|| "Parsing post id")?;
+            actions::transcribe(&location, &id, draft, lang.as_deref())
+                .with_context(|| "Transcribing post")?;
+        }
+
+        args::Command::Revise {
+            all: true,
+            new_watermark,
+            ..
+        } => {
+            let started = Instant::now();
+            let result = revise_all(&location, args.wait, new_watermark);
+            garfutils::notify_finished(&location, "Revising posts", started, &result);
+            let count = result?;
+            println!("Revised {} post(s)", count);
         }
 
-        args::Command::Revise { id } => {
-            let id = names::get_revise_id(&location, id).with_context(|| "Parsing post id")?;
-            let date = names::read_date(&location, &id)
-                .with_context(|| "Reading date from existing post directory")?;
-            actions::make(&location, date, &id, true).with_context(|| "Generating post")?;
-            actions::revise(&location, &id).with_context(|| "Revising post")?;
-            confirm("Transcribe now?");
-            actions::transcribe(&location, &id).with_context(|| "Transcribing post")?;
+        args::Command::Revise {
+            id,
+            new_watermark,
+            interactive,
+            first,
+            ..
+        } => {
+            let (id, checkpoint) = {
+                let _lock = garfutils::lock_location(&location, args.wait)
+                    .with_context(|| "Locking location")?;
+                let id = id
+                    .map(|id| names::resolve_id(&location, &id))
+                    .transpose()
+                    .with_context(|| "Resolving post id")?;
+                let id = names::get_revise_id(&location, id, is_interactive(interactive, first))
+                    .with_context(|| "Parsing post id")?;
+                let checkpoint = Checkpoint::start(&location, REVISE_FLOW, &id)
+                    .with_context(|| "Starting checkpoint")?;
+                (id, checkpoint)
+            };
+            revise_flow(
+                &mut MakeContext::new(&location)?,
+                &id,
+                args.wait,
+                new_watermark,
+                checkpoint,
+            )?;
+        }
+
+        args::Command::Resume { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            let checkpoint = Checkpoint::load(&location, &id)
+                .with_context(|| "Reading checkpoint")?
+                .with_context(|| Error::usage("Post has no unfinished flow to resume"))?;
+            if checkpoint.flow() != REVISE_FLOW {
+                bail!(Error::usage(format!(
+                    "Cannot resume unknown flow `{}`",
+                    checkpoint.flow()
+                )));
+            }
+            revise_flow(
+                &mut MakeContext::new(&location)?,
+                &id,
+                args.wait,
+                false,
+                checkpoint,
+            )?;
+        }
+
+        args::Command::Upload {
+            id,
+            target,
+            lang,
+            force,
+        } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::upload(&location, &id, target, lang.as_deref(), force)
+                .with_context(|| "Uploading post")?;
+        }
+
+        args::Command::Diff { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::diff(&location, &id).with_context(|| "Comparing post images")?;
+        }
+
+        args::Command::Rename { old_id, new_id } => {
+            let old_id =
+                names::resolve_id(&location, &old_id).with_context(|| "Resolving post id")?;
+            actions::rename(&location, &old_id, &new_id).with_context(|| "Renaming post")?;
+        }
+
+        args::Command::Transcript { command } => match command {
+            args::TranscriptCommand::Export { id, format, output } => {
+                let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+                actions::transcript_export(&location, &id, format, output)
+                    .with_context(|| "Exporting transcript")?;
+            }
+        },
+
+        args::Command::Render { id, lang } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::render(&location, &id, lang.as_deref()).with_context(|| "Rendering post")?;
+        }
+
+        args::Command::Watch => {
+            actions::watch(&location).with_context(|| "Watching posts")?;
+        }
+
+        args::Command::Preview { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::preview(&location, &id).with_context(|| "Previewing transcript")?;
+        }
+
+        args::Command::Review {
+            id,
+            accept_submission,
+            reject_submission,
+            approve,
+            reject,
+            comment,
+        } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            let accept_submission =
+                (accept_submission || reject_submission).then_some(accept_submission);
+            let approve = (approve || reject).then_some(approve);
+            actions::review(
+                &location,
+                &id,
+                accept_submission,
+                approve,
+                comment.as_deref(),
+            )
+            .with_context(|| "Reviewing post")?;
+        }
+
+        args::Command::PullQueue => {
+            actions::pull_queue(&location).with_context(|| "Pulling submitted transcripts")?;
+        }
+
+        args::Command::Note { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::note(&location, &id).with_context(|| "Editing post notes")?;
+        }
+
+        args::Command::Open { id, file } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::open(&location, &id, file.as_deref()).with_context(|| "Opening post")?;
+        }
+
+        args::Command::List { stage, author } => {
+            actions::list(&location, stage, author.as_deref()).with_context(|| "Listing posts")?;
+        }
+
+        args::Command::Delete { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::delete(&location, &id).with_context(|| "Deleting post")?;
+        }
+
+        args::Command::Optimize { id, all: _ } => {
+            let id = id
+                .map(|id| names::resolve_id(&location, &id))
+                .transpose()
+                .with_context(|| "Resolving post id")?;
+            let started = Instant::now();
+            let result = actions::optimize(&location, id.as_deref());
+            garfutils::notify_finished(&location, "Optimizing images", started, &result);
+            result.with_context(|| "Optimizing images")?;
+        }
+
+        args::Command::Import { path } => {
+            actions::import(&location, &path).with_context(|| "Importing comics")?;
+        }
+
+        args::Command::MigrateSource => {
+            actions::migrate_source(&location).with_context(|| "Migrating source directory")?;
+        }
+
+        args::Command::Dedupe { dry_run } => {
+            actions::dedupe(&location, dry_run).with_context(|| "Linking duplicate images")?;
+        }
+
+        args::Command::Verify => {
+            if !actions::verify(&location).with_context(|| "Verifying checksums")? {
+                bail!(Error::validation("Some files do not match their checksums"));
+            }
         }
 
-        args::Command::Upload { id } => {
-            actions::upload(&location, &id).with_context(|| "Uploading post")?;
+        args::Command::Log { id } => {
+            let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+            actions::log(&location, &id).with_context(|| "Showing post commits")?;
         }
+
+        args::Command::History { command } => match command {
+            args::HistoryCommand::Show { id } => {
+                let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+                actions::history_show(&location, &id).with_context(|| "Listing post history")?;
+            }
+            args::HistoryCommand::Diff { id, old, new } => {
+                let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+                actions::history_diff(&location, &id, old, new)
+                    .with_context(|| "Comparing post versions")?;
+            }
+        },
+
+        args::Command::Trash { command } => match command {
+            args::TrashCommand::List => {
+                actions::trash_list(&location).with_context(|| "Listing trash")?;
+            }
+            args::TrashCommand::Restore { name } => {
+                actions::trash_restore(&location, &name).with_context(|| "Restoring post")?;
+            }
+            args::TrashCommand::Empty => {
+                actions::trash_empty(&location).with_context(|| "Emptying trash")?;
+            }
+        },
+
+        args::Command::Backup { command, to, keep } => match command {
+            None => {
+                actions::backup(&location, to.as_deref(), keep)
+                    .with_context(|| "Backing up location")?;
+            }
+            Some(args::BackupCommand::List) => {
+                actions::backup_list(&location, to.as_deref())
+                    .with_context(|| "Listing backups")?;
+            }
+            Some(args::BackupCommand::Restore { timestamp }) => {
+                actions::backup_restore(&location, to.as_deref(), &timestamp)
+                    .with_context(|| "Restoring backup")?;
+            }
+        },
+
+        args::Command::Uploads { command } => match command {
+            args::UploadsCommand::List { id } => {
+                let id = id
+                    .map(|id| names::resolve_id(&location, &id))
+                    .transpose()
+                    .with_context(|| "Resolving post id")?;
+                actions::uploads_list(&location, id.as_deref())
+                    .with_context(|| "Listing uploads")?;
+            }
+        },
+
+        args::Command::Schedule { command } => match command {
+            args::ScheduleCommand::Set { id, date } => {
+                let id = names::resolve_id(&location, &id).with_context(|| "Resolving post id")?;
+                actions::schedule_set(&location, &id, date).with_context(|| "Scheduling post")?;
+            }
+            args::ScheduleCommand::List => {
+                actions::schedule_list(&location).with_context(|| "Listing schedule")?;
+            }
+            args::ScheduleCommand::Next => {
+                actions::schedule_next(&location).with_context(|| "Finding due posts")?;
+            }
+        },
+
+        args::Command::Next { plan, upload } => {
+            if upload {
+                actions::next_upload(&location).with_context(|| "Finding post to upload")?;
+            }
+            if plan {
+                let planned =
+                    names::get_planned_post(&location).with_context(|| "Planning next post")?;
+                println!(
+                    "Next slot: {} ({}{})",
+                    planned.id,
+                    if planned.is_sunday {
+                        "sunday"
+                    } else {
+                        "weekday"
+                    },
+                    if planned.is_special { ", special" } else { "" },
+                );
+                if let Some(publish_date) = planned.publish_date {
+                    println!("Published: {}", publish_date);
+                }
+                println!("Suggested date: {}", planned.date);
+            }
+        }
+
+        args::Command::Sheet {
+            range,
+            month,
+            posts,
+            columns,
+            limit,
+        } => {
+            actions::sheet(&location, range, month, posts, columns, limit)
+                .with_context(|| "Composing sheet of comics")?;
+        }
+
+        args::Command::Gaps { year } => {
+            actions::gaps(&location, year).with_context(|| "Finding untranslated comics")?;
+        }
+
+        args::Command::Anniversaries { days } => {
+            actions::anniversaries(&location, days)
+                .with_context(|| "Finding upcoming anniversaries")?;
+        }
+
+        args::Command::Calendar { period, image } => {
+            actions::calendar(&location, period, image.as_deref())
+                .with_context(|| "Displaying calendar")?;
+        }
+
+        args::Command::Report {
+            month,
+            format,
+            output,
+        } => {
+            let started = Instant::now();
+            let result = actions::report(&location, month, format, output);
+            garfutils::notify_finished(&location, "Generating report", started, &result);
+            result.with_context(|| "Generating report")?;
+        }
+
+        args::Command::Feed { limit } => {
+            actions::feed(&location, limit).with_context(|| "Generating feed")?;
+        }
+
+        args::Command::Site { command } => match command {
+            args::SiteCommand::Build { output } => {
+                actions::site_build(&location, &output).with_context(|| "Building site")?;
+            }
+        },
+
+        args::Command::Jobs { command } => match command {
+            args::JobsCommand::List => {
+                actions::jobs_list(&location).with_context(|| "Listing jobs")?;
+            }
+            args::JobsCommand::Run => {
+                actions::jobs_run(&location).with_context(|| "Running jobs")?;
+            }
+            args::JobsCommand::Clear => {
+                actions::jobs_clear(&location).with_context(|| "Clearing jobs")?;
+            }
+        },
+
+        #[cfg(feature = "server")]
+        args::Command::Serve { host, port } => {
+            garfutils::server::serve(&location, &host, port).with_context(|| "Serving API")?;
+        }
+
+        args::Command::Thumbnails {
+            watch,
+            interval,
+            throttle,
+        } => {
+            let started = Instant::now();
+            let result = actions::thumbnails(
+                &location,
+                watch,
+                Duration::from_secs(interval),
+                Duration::from_millis(throttle),
+            );
+            garfutils::notify_finished(&location, "Generating thumbnails", started, &result);
+            result.with_context(|| "Generating thumbnails")?;
+        }
+
+        args::Command::Stats {
+            effort,
+            perf,
+            by_author,
+        } => {
+            if effort {
+                actions::stats_effort(&location).with_context(|| "Summarizing effort")?;
+            }
+            if perf {
+                actions::stats_perf(&location).with_context(|| "Summarizing timings")?;
+            }
+            if by_author {
+                actions::stats_by_author(&location).with_context(|| "Summarizing authors")?;
+            }
+        }
+
+        args::Command::Check {
+            duplicates,
+            source,
+            spelling,
+            glossary,
+        } => {
+            let mut passed = true;
+            if duplicates {
+                passed &= actions::check_duplicates(&location)
+                    .with_context(|| "Checking for duplicate dates")?;
+            }
+            if source {
+                passed &= actions::check_source(&location)
+                    .with_context(|| "Checking source directory")?;
+            }
+            if spelling {
+                passed &= actions::check_spelling(&location)
+                    .with_context(|| "Checking spelling of transcripts")?;
+            }
+            if glossary {
+                passed &= actions::check_glossary(&location)
+                    .with_context(|| "Checking translations against glossary")?;
+            }
+            if !passed {
+                bail!(Error::validation("Some checks failed"));
+            }
+        }
+
+        args::Command::Clean { tmp, older_than } => {
+            if tmp {
+                let Some(seconds) = older_than.checked_mul(60 * 60 * 24) else {
+                    bail!(Error::usage("Age given with `--older-than` is too large"));
+                };
+                let max_age = Duration::from_secs(seconds);
+                actions::clean_temp(&location, max_age).with_context(|| "Cleaning temp files")?;
+            }
+        }
+
+        // Handled above
+        args::Command::Setup | args::Command::Version { .. } | args::Command::Guide => {
+            unreachable!()
+        }
+        #[cfg(feature = "self-update")]
+        args::Command::SelfUpdate { .. } => unreachable!(),
     }
 
+    garfutils::finish_timings(&location, &command_name).with_context(|| "Logging timings")?;
     Ok(())
 }
+
+/// Revises every unrevised post in turn, with one make context for the whole batch. Returns number
+/// of revised posts.
+fn revise_all(location: &Location, wait: bool, new_watermark: bool) -> Result<usize> {
+    let mut context = MakeContext::new(location)?;
+    let mut count = 0;
+    loop {
+        let (id, checkpoint) = {
+            let _lock =
+                garfutils::lock_location(location, wait).with_context(|| "Locking location")?;
+            let Some(id) =
+                names::find_unrevised_post(location).with_context(|| "Finding post to revise")?
+            else {
+                return Ok(count);
+            };
+            println!("Post id: {}", output::id(&id));
+            let checkpoint = Checkpoint::start(location, REVISE_FLOW, &id)
+                .with_context(|| "Starting checkpoint")?;
+            (id, checkpoint)
+        };
+        revise_flow(&mut context, &id, wait, new_watermark, checkpoint)?;
+        count += 1;
+    }
+}
+
+/// Every step is recorded in checkpoint once complete, and skipped when resumed.
+/// Lock is released while waiting, so post can be finished by another instance.
+fn revise_flow(
+    context: &mut MakeContext,
+    id: &str,
+    wait: bool,
+    new_watermark: bool,
+    mut checkpoint: Checkpoint,
+) -> Result<()> {
+    let location = context.location();
+    {
+        let _lock = garfutils::lock_location(location, wait).with_context(|| "Locking location")?;
+        if !checkpoint.is_done("make") {
+            actions::remake_with(context, id, new_watermark).with_context(|| "Generating post")?;
+            checkpoint.complete(location, "make")?;
+        }
+        if !checkpoint.is_done("revise") {
+            actions::revise(location, id).with_context(|| "Revising post")?;
+            checkpoint.complete(location, "revise")?;
+        }
+    }
+    if !checkpoint.is_done("finish") {
+        actions::wait_for_revised_post(location, id)?;
+        let _lock = garfutils::lock_location(location, wait).with_context(|| "Locking location")?;
+        actions::finish_revised_post(location, id).with_context(|| "Finishing revised post")?;
+        checkpoint.complete(location, "finish")?;
+    }
+    let _lock = garfutils::lock_location(location, wait).with_context(|| "Locking location")?;
+    actions::transcribe(location, id, false, None).with_context(|| "Transcribing post")?;
+    checkpoint.finish(location)
+}
+
+fn version(check: bool) -> Result<()> {
+    if check {
+        return check_for_release();
+    }
+    println!("garfutils {}", env!("CARGO_PKG_VERSION"));
+    Ok(())
+}
+
+#[cfg(feature = "self-update")]
+fn check_for_release() -> Result<()> {
+    garfutils::update::print_check().with_context(|| "Checking for new release")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "self-update"))]
+fn check_for_release() -> Result<()> {
+    bail!(Error::usage(
+        "Checking for new releases requires the `self-update` feature"
+    ));
+}
+
+/// Interactive by default when output is a terminal
+fn is_interactive(interactive: bool, first: bool) -> bool {
+    interactive || (!first && io::stdout().is_terminal())
+}
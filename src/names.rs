@@ -1,17 +1,28 @@
+use crate::config::PostLayout;
 use crate::constants::*;
+use crate::error::Error;
 use crate::file;
-use crate::location::Location;
+use crate::location::{Location, Stage};
+use crate::output;
 use crate::random;
-use crate::range::DateRange;
+use crate::range::{DateRange, FullDateRange, WeekdayFilter};
+use crate::ratings;
+use crate::recent;
+use crate::schedule::Schedule;
+use crate::select;
+use crate::skips;
+use crate::source_dates;
+use crate::special;
+use crate::timings::{self, Phase};
 
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::Write as _;
 use std::fs;
-use std::fs::DirEntry;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context as _, Result};
-use chrono::Weekday;
 use chrono::{Datelike as _, NaiveDate};
+use chrono::{Days, Weekday};
 use rand::Rng as _;
 
 pub fn generate_name(date: NaiveDate) -> String {
@@ -38,20 +49,44 @@ pub fn generate_name(date: NaiveDate) -> String {
 
 #[derive(Clone, Copy, Debug)]
 pub enum ShowInput {
-    Exact { date: NaiveDate },
-    Range { range: DateRange, sunday: bool },
-    Any { sunday: bool },
+    Exact {
+        date: NaiveDate,
+    },
+    Random {
+        range: DateRange,
+        between: FullDateRange,
+        weekdays: WeekdayFilter,
+        min_rating: Option<u8>,
+        /// Exclude comics with a post, or in skip list
+        only_new: bool,
+    },
 }
 
 pub fn get_show_input(
     date: Option<NaiveDate>,
     range: Option<DateRange>,
+    between: Option<FullDateRange>,
     sunday: bool,
+    weekdays: &[Weekday],
+    min_rating: Option<u8>,
+    only_new: bool,
 ) -> ShowInput {
-    match (date, range, sunday) {
-        (Some(date), None, false) => ShowInput::Exact { date },
-        (None, Some(range), _) => ShowInput::Range { range, sunday },
-        (None, None, _) => ShowInput::Any { sunday },
+    let weekdays = if sunday {
+        WeekdayFilter::from_weekdays([Weekday::Sun])
+    } else {
+        WeekdayFilter::from_weekdays(weekdays.iter().copied())
+    };
+    match (date, range, between) {
+        (Some(date), None, None) if weekdays.is_all() && min_rating.is_none() && !only_new => {
+            ShowInput::Exact { date }
+        }
+        (None, range, between) => ShowInput::Random {
+            range: range.unwrap_or_else(DateRange::all),
+            between: between.unwrap_or_else(FullDateRange::all),
+            weekdays,
+            min_rating,
+            only_new,
+        },
         _ => {
             unreachable!("invalid argument combination (cli parsing is broken)");
         }
@@ -59,60 +94,132 @@ pub fn get_show_input(
 }
 
 pub fn get_show_date(location: &Location, input: ShowInput) -> Result<NaiveDate> {
-    let (range, sunday) = match input {
+    let date_predicate = match input {
         ShowInput::Exact { date } => return Ok(date),
-        ShowInput::Range { range, sunday } => (range, sunday),
-        ShowInput::Any { sunday } => (DateRange::all(), sunday),
+        ShowInput::Random { .. } => get_show_predicate(location, input)?,
     };
-    get_random_date(location, range, sunday).with_context(|| "Finding random comic date")
+    source_dates::choose_random(location, date_predicate)
+        .with_context(|| "Reading source dates")?
+        .with_context(|| "No comics found")
+}
+
+/// Distinct random dates matching input, up to `count`
+pub fn get_show_dates(
+    location: &Location,
+    input: ShowInput,
+    count: usize,
+) -> Result<Vec<NaiveDate>> {
+    let date_predicate = match input {
+        ShowInput::Exact { date } => return Ok(vec![date]),
+        ShowInput::Random { .. } => get_show_predicate(location, input)?,
+    };
+    let dates = source_dates::choose_random_many(location, date_predicate, count)
+        .with_context(|| "Reading source dates")?;
+    if dates.is_empty() {
+        bail!("No comics found");
+    }
+    Ok(dates)
+}
+
+/// Sorted dates of all source comics within range
+pub fn get_source_dates(location: &Location, between: FullDateRange) -> Result<Vec<NaiveDate>> {
+    let dates = source_dates::load(location).with_context(|| "Reading source dates")?;
+    Ok(dates
+        .into_iter()
+        .filter(|date| between.contains(*date))
+        .collect())
 }
 
 // TODO(refactor): Create `get_make_input` similar to `get_show_input`
+/// With `recent`, uses the comic shown that many comics ago (`1` is most recent)
 pub fn get_make_date(
     location: &Location,
     date: Option<NaiveDate>,
-    recent: bool,
+    recent: Option<usize>,
 ) -> Result<NaiveDate> {
-    if !recent {
+    let Some(recent) = recent else {
         return Ok(date.expect("date should be `Some` without `--recent` (cli parsing is broken)"));
-    }
+    };
     assert!(
         date.is_none(),
         "date should be `None` with `--recent` (cli parsing is broken)"
     );
-    let recent_date = get_recent_date(location).with_context(|| "Parsing recent date")?;
-    println!("Date: {}", recent_date);
-    Ok(recent_date)
+    let entry = recent::nth_most_recent(location, recent).with_context(|| "Parsing recent date")?;
+    match (entry.time, &entry.command_line) {
+        (Some(time), Some(command_line)) => println!(
+            "Date: {} (shown {} by `{}`)",
+            entry.date,
+            time.format("%H:%M:%S"),
+            command_line
+        ),
+        _ => println!("Date: {}", entry.date),
+    }
+    Ok(entry.date)
 }
 
-pub fn get_transcribe_id(location: &Location, id: Option<String>) -> Result<String> {
+/// Without an id, finds a post whose variant (or primary translation) has not been transcribed.
+/// With `interactive`, every such post is listed to choose from, instead of using the first.
+pub fn get_transcribe_id(
+    location: &Location,
+    id: Option<String>,
+    language: Option<&str>,
+    interactive: bool,
+) -> Result<String> {
     if let Some(id) = id {
         if !location.posts_dir().join(&id).is_dir() {
-            bail!("No post exists with that id");
+            bail!(Error::missing_post("No post exists with that id"));
         }
         return Ok(id);
     }
-    if let Some(id) =
-        find_untranscribed_post(location).with_context(|| "Finding post to transcribe")?
-    {
-        println!("Post id: {}", id);
-        return Ok(id);
-    }
-    bail!("No posts to transcribe");
+    let candidates = find_untranscribed_posts(location, language)
+        .with_context(|| "Finding posts to transcribe")?;
+    choose_post(location, candidates, "transcribe", interactive)
 }
 
-pub fn get_revise_id(location: &Location, id: Option<String>) -> Result<String> {
+/// With `interactive`, every unrevised post is listed to choose from, instead of using the first
+pub fn get_revise_id(location: &Location, id: Option<String>, interactive: bool) -> Result<String> {
     if let Some(id) = id {
         if !location.posts_dir().join(&id).is_dir() {
-            bail!("No post exists with that id");
+            bail!(Error::missing_post("No post exists with that id"));
         }
         return Ok(id);
     }
-    if let Some(id) = find_unrevised_post(location).with_context(|| "Finding post to revise")? {
-        println!("Post id: {}", id);
-        return Ok(id);
+    let candidates = find_unrevised_posts(location).with_context(|| "Finding posts to revise")?;
+    choose_post(location, candidates, "revise", interactive)
+}
+
+fn choose_post(
+    location: &Location,
+    mut candidates: Vec<String>,
+    action: &str,
+    interactive: bool,
+) -> Result<String> {
+    if candidates.is_empty() {
+        bail!(Error::missing_post(format!("No posts to {}", action)));
     }
-    bail!("No posts to revise");
+    if !interactive || candidates.len() == 1 {
+        println!("Post id: {}", output::id(&candidates[0]));
+        return Ok(candidates.swap_remove(0));
+    }
+    let labels: Vec<String> = candidates
+        .iter()
+        .map(|id| describe_post(location, id))
+        .collect();
+    let Some(index) = select::choose(location.prompter(), &format!("Post to {}", action), &labels)
+    else {
+        bail!(Error::usage("No post was chosen"));
+    };
+    Ok(candidates.swap_remove(index))
+}
+
+/// Id, date, and title, for a menu
+fn describe_post(location: &Location, id: &str) -> String {
+    let date = read_date(location, id)
+        .map(|date| date.to_string())
+        .unwrap_or_else(|_| "?".to_string());
+    let title =
+        file::read_text(location.posts_dir().join(id).join(post_file::TITLE)).unwrap_or_default();
+    format!("{:<6} {}  {}", id, date, title.trim())
 }
 
 pub fn read_date(location: &Location, id: &str) -> Result<NaiveDate> {
@@ -123,70 +230,380 @@ pub fn read_date(location: &Location, id: &str) -> Result<NaiveDate> {
     Ok(date)
 }
 
-fn get_random_date(location: &Location, range: DateRange, sunday: bool) -> Result<NaiveDate> {
-    let entry_predicate = |entry: &DirEntry| -> bool {
-        let path = entry.path();
-        (path_in_date_range(&path, range)) && (!sunday || path_is_sunday(&path))
+/// Next unfilled posting slot, and a suggested comic to fill it
+#[derive(Clone, Copy, Debug)]
+pub struct PlannedPost {
+    pub id: u32,
+    pub is_sunday: bool,
+    /// Slot falls on `plan.special_day`, so should be filled by a special post
+    pub is_special: bool,
+    /// Known if any numeric post is scheduled, assuming one post is published each day after it
+    pub publish_date: Option<NaiveDate>,
+    pub date: NaiveDate,
+}
+
+/// Posted ids are sequential, with every 7th being a 'sunday' slot. Slots published on
+/// `plan.special_day` of a month are reserved for special posts.
+/// Generated posts are assumed to fill the next slots of the matching kind.
+pub fn get_planned_post(location: &Location) -> Result<PlannedPost> {
+    let next_id = match get_latest_post_number(location)? {
+        Some((latest, _)) => latest + 1,
+        None => 0,
     };
 
-    let path = file::get_random_directory_entry(location.source_dir(), entry_predicate)
-        .with_context(|| "Reading source directory")?
-        .with_context(|| "No comics found")?
-        .path();
+    // Latest scheduled post, which later slots are published after
+    let scheduled = Schedule::load(location)?
+        .entries()
+        .into_iter()
+        .filter_map(|(date, id)| Some((date, id.parse::<u32>().ok()?)))
+        .max_by_key(|(_, id)| *id);
+    let publish_date = |id: u32| -> Option<NaiveDate> {
+        let (date, scheduled_id) = scheduled?;
+        date.checked_add_days(Days::new(id.checked_sub(scheduled_id)? as u64))
+    };
+    let special_day = location.config().plan.special_day;
+    let is_special_slot = |id: u32| -> bool {
+        special_day.is_some() && publish_date(id).map(|date| date.day()) == special_day
+    };
 
-    let date_opt = file::get_date_from_path(&path).with_context(|| "Parsing date from path")?;
+    // Without publishing dates, there are no special slots to fill
+    let has_special_slots = special_day.is_some() && scheduled.is_some();
+    let mut queued_specials = 0;
+    let mut queued_sundays = 0;
+    let mut queued_weekdays = 0;
+    for (id, date) in read_post_dates(location.generated_dir())? {
+        let post_path = location.generated_dir().join(id);
+        if has_special_slots && special::read(&post_path)?.is_some() {
+            queued_specials += 1;
+        } else if date.weekday() == Weekday::Sun {
+            queued_sundays += 1;
+        } else {
+            queued_weekdays += 1;
+        }
+    }
 
-    date_opt.with_context(|| {
-        "Found comic file with invalid name. Should contain date in YYYY-MM-DD format."
+    let mut id = next_id;
+    let (is_sunday, is_special) = loop {
+        let is_sunday = is_post_number_sunday(id);
+        let is_special = is_special_slot(id);
+        let queued = if is_special {
+            &mut queued_specials
+        } else if is_sunday {
+            &mut queued_sundays
+        } else {
+            &mut queued_weekdays
+        };
+        if *queued == 0 {
+            break (is_sunday, is_special);
+        }
+        *queued -= 1;
+        id += 1;
+    };
+    let publish_date = publish_date(id);
+
+    let used_dates = get_excluded_dates(location)?;
+    let is_available = |date: &NaiveDate| -> bool {
+        (date.weekday() == Weekday::Sun) == is_sunday && !used_dates.contains(date)
+    };
+    // Special slot is best filled by a comic published on the same day of the year
+    let anniversary = match publish_date {
+        Some(publish_date) if is_special => source_dates::choose_random(location, |date| {
+            is_available(date)
+                && date.month() == publish_date.month()
+                && date.day() == publish_date.day()
+        })
+        .with_context(|| "Reading source dates")?,
+        _ => None,
+    };
+    let date = match anniversary {
+        Some(date) => date,
+        None => source_dates::choose_random(location, is_available)
+            .with_context(|| "Reading source dates")?
+            .with_context(|| "No unposted comics found for slot")?,
+    };
+
+    Ok(PlannedPost {
+        id,
+        is_sunday,
+        is_special,
+        publish_date,
+        date,
     })
 }
 
-fn path_is_sunday(path: impl AsRef<Path>) -> bool {
-    let Ok(Some(date)) = file::get_date_from_path(path) else {
-        return false;
+/// Id must be usable as a directory name within a stage directory
+pub fn check_new_id(id: &str) -> Result<()> {
+    if id.is_empty() || id == "." || id == ".." || id.contains(['/', '\\']) {
+        bail!(Error::usage("Invalid post id"));
+    }
+    Ok(())
+}
+
+/// Next sequential post id, padded to the same width as the latest
+pub fn get_next_post_id(location: &Location) -> Result<String> {
+    let (number, width) = match get_latest_post_number(location)? {
+        Some((latest, width)) => (latest + 1, width),
+        None => (0, 1),
     };
-    date.weekday() == Weekday::Sun
+    Ok(format!("{:0width$}", number, width = width))
+}
+
+/// Finds which directory contains a post, preferring completed posts, then generated, then old
+pub fn find_post(location: &Location, id: &str) -> Result<(Stage, PathBuf)> {
+    for stage in [Stage::Completed, Stage::Generated, Stage::Old] {
+        let path = location.stage_dir(stage).join(id);
+        if path.is_dir() {
+            return Ok((stage, path));
+        }
+    }
+    bail!(Error::missing_post("No post exists with that id"));
 }
 
-fn path_in_date_range(path: impl AsRef<Path>, range: DateRange) -> bool {
-    let Ok(Some(date)) = file::get_date_from_path(path) else {
-        return false;
+/// Full id of the post which is the only one to start with `partial`, or whose date starts with it.
+/// An exact id is always used as-is.
+pub fn resolve_id(location: &Location, partial: &str) -> Result<String> {
+    let mut matches = BTreeSet::new();
+    for stage in Stage::ALL {
+        let dir = location.stage_dir(stage);
+        if dir.join(partial).is_dir() {
+            return Ok(partial.to_string());
+        }
+        for entry in file::read_dir(&dir)? {
+            let id = entry?.file_name().to_string_lossy().to_string();
+            let date = id.split_once(':').map(|(_, date)| date);
+            if id.starts_with(partial) || date.is_some_and(|date| date.starts_with(partial)) {
+                matches.insert(id);
+            }
+        }
+    }
+
+    let mut matches = matches.into_iter();
+    let Some(id) = matches.next() else {
+        bail!(Error::missing_post(format!(
+            "No post id matches `{}`",
+            partial
+        )));
     };
-    range.contains(date)
+    if matches.len() > 0 {
+        let candidates: Vec<String> = [id].into_iter().chain(matches).collect();
+        bail!(Error::usage(format!(
+            "Post id `{}` is ambiguous, could be any of: {}",
+            partial,
+            candidates.join(", ")
+        )));
+    }
+    Ok(id)
+}
+
+/// Posts in any directory with the given date
+pub fn find_posts_by_date(location: &Location, date: NaiveDate) -> Result<Vec<(Stage, String)>> {
+    let mut posts = Vec::new();
+    for stage in Stage::ALL {
+        for (id, post_date) in read_post_dates(location.stage_dir(stage))
+            .with_context(|| format!("Reading dates of `{}` directory", stage.dir_name()))?
+        {
+            if post_date == date {
+                posts.push((stage, id));
+            }
+        }
+    }
+    Ok(posts)
+}
+
+/// Dates of all posts, in any directory
+pub fn get_post_dates(location: &Location) -> Result<HashSet<NaiveDate>> {
+    let mut dates = HashSet::new();
+    for stage in Stage::ALL {
+        for (_, date) in read_post_dates(location.stage_dir(stage))
+            .with_context(|| format!("Reading dates of `{}` directory", stage.dir_name()))?
+        {
+            dates.insert(date);
+        }
+    }
+    Ok(dates)
+}
+
+/// Ids and dates of posts in a single directory, sorted by date
+pub fn get_stage_post_dates(location: &Location, stage: Stage) -> Result<Vec<(String, NaiveDate)>> {
+    let mut posts = read_post_dates(location.stage_dir(stage))?;
+    posts.sort_by_key(|(id, date)| (*date, id.clone()));
+    Ok(posts)
+}
+
+/// Dates shared by multiple distinct post ids, across all directories.
+/// The same id in multiple directories (eg. a revised post) is not a duplicate.
+pub fn find_duplicate_dates(location: &Location) -> Result<Vec<(NaiveDate, Vec<(Stage, String)>)>> {
+    let mut posts_by_date: BTreeMap<NaiveDate, Vec<(Stage, String)>> = BTreeMap::new();
+    for stage in Stage::ALL {
+        for (id, date) in read_post_dates(location.stage_dir(stage))
+            .with_context(|| format!("Reading dates of `{}` directory", stage.dir_name()))?
+        {
+            posts_by_date.entry(date).or_default().push((stage, id));
+        }
+    }
+
+    let duplicates = posts_by_date
+        .into_iter()
+        .filter(|(_, posts)| {
+            let ids: HashSet<&str> = posts.iter().map(|(_, id)| id.as_str()).collect();
+            ids.len() > 1
+        })
+        .collect();
+    Ok(duplicates)
 }
 
-fn get_recent_date(location: &Location) -> Result<NaiveDate> {
-    let recent_file = location.recent_file();
+pub fn is_post_number_sunday(id_number: u32) -> bool {
+    (id_number + 1) % 7 == 0
+}
 
-    if !recent_file.exists() {
-        bail!("Recent dates file does not yet exist");
+/// Highest numeric post id, and length of its directory name (including any zero-padding).
+/// Ignores non-numeric directory names.
+fn get_latest_post_number(location: &Location) -> Result<Option<(u32, usize)>> {
+    let mut latest: Option<(u32, usize)> = None;
+    for entry in file::read_dir(location.posts_dir())? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        let Ok(number) = name.parse::<u32>() else {
+            continue;
+        };
+        if latest.is_none_or(|(latest, _)| number > latest) {
+            latest = Some((number, name.len()));
+        }
     }
-    let file = fs::OpenOptions::new().read(true).open(&recent_file)?;
-    file::read_last_line_as_date(file).with_context(|| "Reading recent date file")
+    Ok(latest)
 }
 
-fn find_untranscribed_post(location: &Location) -> Result<Option<String>> {
-    find_post(
+/// Returns `(id, date)` of each post. Skips entries with missing date file.
+fn read_post_dates(dir: impl AsRef<Path>) -> Result<Vec<(String, NaiveDate)>> {
+    timings::time(Phase::Scan, || read_post_dates_untimed(dir.as_ref()))
+}
+
+fn read_post_dates_untimed(dir: &Path) -> Result<Vec<(String, NaiveDate)>> {
+    let mut dates = Vec::new();
+    for entry in file::read_dir(&dir)? {
+        let entry = entry?;
+        let date_file_path = entry.path().join(post_file::DATE);
+        if !date_file_path.exists() {
+            continue;
+        }
+        let date_file = fs::read_to_string(&date_file_path).with_context(|| "Reading date file")?;
+        let date = NaiveDate::parse_from_str(date_file.trim(), "%Y-%m-%d")
+            .with_context(|| "Parsing date in file")?;
+        let id = entry.file_name().to_string_lossy().to_string();
+        dates.push((id, date));
+    }
+    Ok(dates)
+}
+
+/// Whether a date matches random input. Ratings, posts, and skip list are only read if needed.
+fn get_show_predicate(
+    location: &Location,
+    input: ShowInput,
+) -> Result<impl Fn(&NaiveDate) -> bool> {
+    let ShowInput::Random {
+        range,
+        between,
+        weekdays,
+        min_rating,
+        only_new,
+    } = input
+    else {
+        unreachable!("exact input should not be filtered");
+    };
+    let ratings = match min_rating {
+        Some(_) => ratings::load(location).with_context(|| "Reading ratings")?,
+        None => BTreeMap::new(),
+    };
+    let excluded_dates = if only_new {
+        get_excluded_dates(location)?
+    } else {
+        HashSet::new()
+    };
+    Ok(move |date: &NaiveDate| -> bool {
+        let is_rated = match min_rating {
+            Some(min_rating) => ratings
+                .get(date)
+                .is_some_and(|rating| *rating >= min_rating),
+            None => true,
+        };
+        range.contains(*date)
+            && between.contains(*date)
+            && weekdays.contains(*date)
+            && is_rated
+            && !excluded_dates.contains(date)
+    })
+}
+
+/// Random date among highest-rated comics, which don't have a post in any directory
+pub fn get_best_unposted_date(location: &Location) -> Result<NaiveDate> {
+    let used_dates = get_excluded_dates(location)?;
+    let ratings = ratings::load(location).with_context(|| "Reading ratings")?;
+    let unposted: Vec<(NaiveDate, u8)> = ratings
+        .into_iter()
+        .filter(|(date, _)| !used_dates.contains(date) && location.source_file(*date).exists())
+        .collect();
+    let best_rating = unposted
+        .iter()
+        .map(|(_, rating)| *rating)
+        .max()
+        .with_context(|| Error::usage("No rated comics without a post"))?;
+    let best = unposted
+        .into_iter()
+        .filter(|(_, rating)| *rating == best_rating)
+        .map(|(date, _)| date);
+    Ok(random::sample(best).expect("at least one comic should have best rating"))
+}
+
+/// Dates which should not be suggested: those of any post, or in skip list
+pub fn get_excluded_dates(location: &Location) -> Result<HashSet<NaiveDate>> {
+    let mut dates = get_post_dates(location)?;
+    dates.extend(skips::load(location)?.into_keys());
+    Ok(dates)
+}
+
+pub fn find_untranscribed_posts(
+    location: &Location,
+    language: Option<&str>,
+) -> Result<Vec<String>> {
+    let variant = location.layout().variant(language)?;
+    find_posts_matching(
         location,
-        [|path: &Path| Ok(has_svg_file(path) && !has_transcript_file(path))],
+        [|path: &Path| {
+            Ok(path.join(&variant.svg).exists() && !path.join(&variant.transcript).exists())
+        }],
     )
 }
 
-fn find_unrevised_post(location: &Location) -> Result<Option<String>> {
-    find_post(
+/// Good posts first
+fn find_unrevised_posts(location: &Location) -> Result<Vec<String>> {
+    let layout = location.layout();
+    // Separate calls, since each closure has a different type
+    let mut ids = find_posts_matching(
         location,
-        [
-            |path: &Path| Ok(!has_svg_file(path) && is_post_good(path)?),
-            |path: &Path| Ok(!has_svg_file(path)),
-        ],
-    )
+        [|path: &Path| Ok(!has_svg_file(layout, path) && is_post_good(path)?)],
+    )?;
+    for id in find_posts_matching(location, [|path: &Path| Ok(!has_svg_file(layout, path))])? {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
 }
 
-fn has_svg_file(path: impl AsRef<Path>) -> bool {
-    path.as_ref().join(post_file::SVG).exists()
+/// Post without an SVG file, preferring good posts
+pub fn find_unrevised_post(location: &Location) -> Result<Option<String>> {
+    let layout = location.layout();
+    // Good posts are preferred
+    if let Some(id) = find_post_matching(
+        location,
+        [|path: &Path| Ok(!has_svg_file(layout, path) && is_post_good(path)?)],
+    )? {
+        return Ok(Some(id));
+    }
+    find_post_matching(location, [|path: &Path| Ok(!has_svg_file(layout, path))])
 }
-fn has_transcript_file(path: impl AsRef<Path>) -> bool {
-    path.as_ref().join(post_file::TRANSCRIPT).exists()
+
+fn has_svg_file(layout: &PostLayout, path: impl AsRef<Path>) -> bool {
+    path.as_ref().join(&layout.svg).exists()
 }
 
 /// Returns `Ok(true)` if post has a `props` file, which contains the line `good`
@@ -210,7 +627,25 @@ fn is_post_good(path: impl AsRef<Path>) -> Result<bool> {
 }
 
 /// Loop through 'criteria' functions, until one finds an appropriate post
-fn find_post<I, F>(location: &Location, criteria: I) -> Result<Option<String>>
+/// Posts matching any criterion, in order of criteria
+fn find_posts_matching<I, F>(location: &Location, criteria: I) -> Result<Vec<String>>
+where
+    I: IntoIterator<Item = F>,
+    F: Fn(&Path) -> Result<bool>,
+{
+    let posts_dir = location.posts_dir();
+    let mut ids = Vec::new();
+    for criterion in criteria {
+        for id in file::find_children(&posts_dir, criterion)? {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+fn find_post_matching<I, F>(location: &Location, criteria: I) -> Result<Option<String>>
 where
     I: IntoIterator<Item = F>,
     F: Fn(&Path) -> Result<bool>,
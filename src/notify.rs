@@ -0,0 +1,58 @@
+use crate::location::Location;
+use crate::output;
+
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+
+const APP_NAME: &str = "garfutils";
+
+/// Sends a desktop notification that a batch operation finished or failed, if notifications are
+/// enabled and it took long enough to be worth one.
+/// Failing to notify only prints a warning, so it never hides the result of the operation.
+pub fn notify_finished<T>(
+    location: &Location,
+    operation: &str,
+    started: Instant,
+    result: &Result<T>,
+) {
+    let config = &location.config().notifications;
+    if !config.enabled || started.elapsed().as_secs() < config.min_seconds {
+        return;
+    }
+    let (summary, body) = match result {
+        Ok(_) => (format!("{} finished", operation), String::new()),
+        Err(error) => (format!("{} failed", operation), format!("{:#}", error)),
+    };
+    if let Err(error) = send(&summary, &body) {
+        output::warning(format!("Failed to send notification: {:#}", error));
+    }
+}
+
+#[cfg(feature = "notify-rust")]
+fn send(summary: &str, body: &str) -> Result<()> {
+    notify_rust::Notification::new()
+        .appname(APP_NAME)
+        .summary(summary)
+        .body(body)
+        .show()
+        .with_context(|| "Showing notification")?;
+    Ok(())
+}
+
+/// Uses `notify-send`, if it is installed
+#[cfg(not(feature = "notify-rust"))]
+fn send(summary: &str, body: &str) -> Result<()> {
+    use crate::commands;
+    use std::process::Command;
+
+    const PROGRAM: &str = "notify-send";
+    if !commands::is_program_installed(PROGRAM) {
+        return Ok(());
+    }
+    Command::new(PROGRAM)
+        .args(["--app-name", APP_NAME, summary, body])
+        .status()
+        .with_context(|| format!("Running `{}`", PROGRAM))?;
+    Ok(())
+}
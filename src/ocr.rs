@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+/// English text of each panel, from `tesseract`. Comic is split into equal-width vertical strips,
+/// one for each panel, so `panel_count` should be `1` for comics without a simple layout.
+#[cfg(feature = "ocr")]
+pub fn recognize_panels(
+    image_path: &Path,
+    panel_count: u32,
+    temp_dir: &Path,
+) -> Result<Vec<String>> {
+    use crate::error::Error;
+
+    use std::fs;
+    use std::process::{Command, Stdio};
+
+    use anyhow::{bail, Context as _};
+    use image::GenericImageView as _;
+
+    let image = image::open(image_path).with_context(|| "Opening comic image")?;
+    let panel_width = image.width() / panel_count.max(1);
+    fs::create_dir_all(temp_dir).with_context(|| "Creating temp directory for panel images")?;
+
+    let mut panels = Vec::new();
+    for i in 0..panel_count.max(1) {
+        let panel_path = temp_dir.join(format!("ocr.{}.png", i));
+        image
+            .crop_imm(i * panel_width, 0, panel_width, image.height())
+            .save(&panel_path)
+            .with_context(|| "Saving panel image")?;
+
+        let output = Command::new("tesseract")
+            .arg(&panel_path)
+            .args(["stdout", "-l", "eng"])
+            .stderr(Stdio::null())
+            .output()
+            .with_context(|| Error::external_tool("Running `tesseract`"));
+        let _ = fs::remove_file(&panel_path);
+        let output = output?;
+        if !output.status.success() {
+            bail!(Error::external_tool(
+                "`tesseract` did not exit successfully"
+            ));
+        }
+        panels.push(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    Ok(panels)
+}
+
+#[cfg(not(feature = "ocr"))]
+pub fn recognize_panels(
+    _image_path: &Path,
+    _panel_count: u32,
+    _temp_dir: &Path,
+) -> Result<Vec<String>> {
+    Ok(Vec::new())
+}
@@ -0,0 +1,51 @@
+use crate::config::PostLayout;
+use crate::constants::*;
+use crate::dedupe;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+
+const PRESET: u8 = 2;
+
+/// Losslessly recompresses every PNG image in post directory, and returns number of bytes saved.
+/// Hard-linked duplicate images stay linked.
+pub fn optimize_post(layout: &PostLayout, post_path: &Path) -> Result<u64> {
+    let is_linked = dedupe::is_linked(layout, post_path).unwrap_or(false);
+
+    let mut saved = 0;
+    for entry in fs::read_dir(post_path).with_context(|| "Reading post directory")? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension() != Some(OsStr::new(SOURCE_FORMAT)) {
+            continue;
+        }
+        if is_linked && path.file_name() == Some(OsStr::new(&layout.duplicate)) {
+            continue;
+        }
+        saved += optimize_image(&path)?;
+    }
+
+    if is_linked {
+        dedupe::link(layout, post_path).with_context(|| "Relinking duplicate image")?;
+    }
+    Ok(saved)
+}
+
+/// Image is only replaced if optimized version is smaller
+pub fn optimize_image(path: &Path) -> Result<u64> {
+    let original = fs::read(path).with_context(|| "Reading image")?;
+    let optimized = oxipng::optimize_from_memory(&original, &oxipng::Options::from_preset(PRESET))
+        .with_context(|| format!("Optimizing `{}`", path.display()))?;
+    if optimized.len() >= original.len() {
+        return Ok(0);
+    }
+
+    // Replaced atomically, so image is never left partially written
+    let mut temp_path = path.to_path_buf();
+    temp_path.set_extension("png.optimized");
+    fs::write(&temp_path, &optimized).with_context(|| "Writing optimized image")?;
+    fs::rename(&temp_path, path).with_context(|| "Replacing image with optimized version")?;
+    Ok((original.len() - optimized.len()) as u64)
+}
@@ -0,0 +1,117 @@
+//! Styled terminal output, which is plain unless enabled with `init`
+
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal as _};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Only if output is a terminal, and `NO_COLOR` is not set to a non-empty value
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Style {
+    Bold,
+    Underline,
+    Green,
+    Yellow,
+    Red,
+}
+
+/// Value which is displayed with a style, if enabled
+pub struct Styled<T> {
+    value: T,
+    style: Style,
+}
+
+pub fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Auto => {
+            let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            !no_color && io::stdout().is_terminal()
+        }
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Post ids, and other names which the user may need to type
+pub fn id<T: fmt::Display>(value: T) -> Styled<T> {
+    Styled {
+        value,
+        style: Style::Bold,
+    }
+}
+
+pub fn underline<T: fmt::Display>(value: T) -> Styled<T> {
+    Styled {
+        value,
+        style: Style::Underline,
+    }
+}
+
+pub fn error_label() -> Styled<&'static str> {
+    Styled {
+        value: "Error:",
+        style: Style::Red,
+    }
+}
+
+/// Completed change to the location
+pub fn success(message: impl fmt::Display) {
+    let styled = Styled {
+        value: message,
+        style: Style::Green,
+    };
+    println!("{}", styled);
+}
+
+pub fn warning(message: impl fmt::Display) {
+    let label = Styled {
+        value: "Warning:",
+        style: Style::Yellow,
+    };
+    println!("{} {}", label, message);
+}
+
+impl Style {
+    /// ANSI SGR parameter
+    fn code(self) -> &'static str {
+        match self {
+            Self::Bold => "1",
+            Self::Underline => "4",
+            Self::Green => "32",
+            Self::Yellow => "33",
+            Self::Red => "31",
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !is_enabled() {
+            return write!(f, "{}", self.value);
+        }
+        let start = format!("\x1b[{}m", self.style.code());
+        // Nested styled values end with a reset, which would otherwise clear this style too
+        let value = self
+            .value
+            .to_string()
+            .replace(RESET, &(RESET.to_string() + &start));
+        write!(f, "{}{}{}", start, value, RESET)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::constants::*;
+use crate::error::Error;
+use crate::location::Location;
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+/// How far a post has progressed through the translation workflow.
+///
+/// Unlike `location::Stage`, this depends on the files inside a post directory, not only which
+/// directory it is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// In `generated`, without a title
+    Generated,
+    /// In `generated`, with a title
+    Titled,
+    /// In `posts`, without a transcript
+    Finished,
+    /// In `posts`, with a transcript
+    Transcribed,
+    /// In `generated`, while the previous version is in `old`
+    Revised,
+    /// In `posts`, and has been uploaded at least once
+    Uploaded,
+    /// Only in `old`
+    Archived,
+}
+
+impl Stage {
+    /// Post directory being worked on is preferred, ie. `generated`, then `posts`, then `old`
+    pub fn of(location: &Location, id: &str) -> Option<Self> {
+        let generated_path = location.generated_dir().join(id);
+        let post_path = location.posts_dir().join(id);
+        let old_path = location.old_dir().join(id);
+
+        if generated_path.is_dir() {
+            if old_path.is_dir() {
+                return Some(Self::Revised);
+            }
+            return Some(if has_file(&generated_path, post_file::TITLE) {
+                Self::Titled
+            } else {
+                Self::Generated
+            });
+        }
+        if post_path.is_dir() {
+            return Some(if has_file(&post_path, post_file::UPLOADED) {
+                Self::Uploaded
+            } else if has_file(&post_path, post_file::TRANSCRIPT) {
+                Self::Transcribed
+            } else {
+                Self::Finished
+            });
+        }
+        if old_path.is_dir() {
+            return Some(Self::Archived);
+        }
+        None
+    }
+
+    /// Staying in the same stage is allowed where the action can be repeated (eg. re-uploading)
+    pub fn can_transition_to(self, next: Self) -> bool {
+        use Stage::*;
+        matches!(
+            (self, next),
+            (Generated, Titled)
+                | (Generated | Titled | Revised, Finished)
+                | (Finished | Transcribed | Uploaded, Transcribed)
+                | (Transcribed | Uploaded, Uploaded)
+                | (Finished | Transcribed | Uploaded, Revised)
+        )
+    }
+
+    /// Whether post is still in `generated`, and must be finished before anything else
+    pub fn is_unfinished(self) -> bool {
+        matches!(self, Self::Generated | Self::Titled | Self::Revised)
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Generated => "generated",
+            Self::Titled => "titled",
+            Self::Finished => "finished",
+            Self::Transcribed => "transcribed",
+            Self::Revised => "revised",
+            Self::Uploaded => "uploaded",
+            Self::Archived => "archived",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns current stage of post, if it can move to `next`
+pub fn require_transition(location: &Location, id: &str, next: Stage) -> Result<Stage> {
+    let Some(current) = Stage::of(location, id) else {
+        bail!(Error::missing_post("No post exists with that id"));
+    };
+    if !current.can_transition_to(next) {
+        bail!(Error::validation(format!(
+            "Post is {}, so it cannot be {}",
+            current, next
+        )));
+    }
+    Ok(current)
+}
+
+fn has_file(post_path: &Path, file_name: &str) -> bool {
+    post_path.join(file_name).is_file()
+}
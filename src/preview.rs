@@ -0,0 +1,181 @@
+use crate::transcript::Transcript;
+
+use ab_glyph::{point, Font as _, FontVec, PxScale, ScaleFont as _};
+use image::{Rgba, RgbaImage};
+
+const PANEL_WIDTH: u32 = 400;
+const PANEL_HEIGHT: u32 = 400;
+/// Gap between panels, and around edges of image
+const MARGIN: u32 = 20;
+/// Between panel border and text
+const PADDING: u32 = 16;
+const BORDER_WIDTH: u32 = 3;
+/// Maximum panels in each row
+const COLUMNS: u32 = 3;
+
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BORDER: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const OVERFLOW_BORDER: Rgba<u8> = Rgba([220, 0, 0, 255]);
+const TEXT: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+pub struct Preview {
+    pub image: RgbaImage,
+    /// Indexes of panels whose text does not fit
+    pub overflowing_panels: Vec<usize>,
+}
+
+/// Each line of a panel is wrapped separately, with a blank gap between lines.
+/// Panels whose text is too tall have a red border.
+pub fn render_preview(transcript: &Transcript, font: &FontVec, font_size: f32) -> Preview {
+    let panel_count = transcript.panels.len().max(1) as u32;
+    let columns = panel_count.min(COLUMNS);
+    let rows = panel_count.div_ceil(columns);
+    let width = columns * (PANEL_WIDTH + MARGIN) + MARGIN;
+    let height = rows * (PANEL_HEIGHT + MARGIN) + MARGIN;
+    let mut image = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+    let scale = PxScale::from(font_size);
+    let scaled_font = font.as_scaled(scale);
+    let line_height = scaled_font.height() + scaled_font.line_gap();
+    let max_text_width = (PANEL_WIDTH - PADDING * 2) as f32;
+    let max_text_height = (PANEL_HEIGHT - PADDING * 2) as f32;
+
+    let mut overflowing_panels = Vec::new();
+    for (i, panel) in transcript.panels.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let panel_x = MARGIN + column * (PANEL_WIDTH + MARGIN);
+        let panel_y = MARGIN + row * (PANEL_HEIGHT + MARGIN);
+
+        let mut wrapped_lines = Vec::new();
+        for (line_index, line) in panel.iter().enumerate() {
+            if line_index > 0 {
+                wrapped_lines.push(String::new());
+            }
+            wrapped_lines.extend(wrap_line(line, font, scale, max_text_width));
+        }
+
+        let text_height = wrapped_lines.len() as f32 * line_height;
+        let overflows = text_height > max_text_height;
+        if overflows {
+            overflowing_panels.push(i);
+        }
+        draw_border(
+            &mut image,
+            panel_x,
+            panel_y,
+            if overflows { OVERFLOW_BORDER } else { BORDER },
+        );
+
+        let text_x = (panel_x + PADDING) as f32;
+        let mut text_y = (panel_y + PADDING) as f32;
+        for line in &wrapped_lines {
+            draw_text(&mut image, font, scale, text_x, text_y, line);
+            text_y += line_height;
+        }
+    }
+
+    Preview {
+        image,
+        overflowing_panels,
+    }
+}
+
+/// Breaks only at spaces, so a single long word may still be too wide
+fn wrap_line(line: &str, font: &FontVec, scale: PxScale, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if !current.is_empty() && text_width(&candidate, font, scale) > max_width {
+            lines.push(current);
+            current = word.to_string();
+        } else {
+            current = candidate;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn text_width(text: &str, font: &FontVec, scale: PxScale) -> f32 {
+    let scaled_font = font.as_scaled(scale);
+    let mut width = 0.0;
+    let mut previous = None;
+    for char in text.chars() {
+        let glyph_id = scaled_font.glyph_id(char);
+        if let Some(previous) = previous {
+            width += scaled_font.kern(previous, glyph_id);
+        }
+        width += scaled_font.h_advance(glyph_id);
+        previous = Some(glyph_id);
+    }
+    width
+}
+
+/// `y` is the top of the line
+fn draw_text(image: &mut RgbaImage, font: &FontVec, scale: PxScale, x: f32, y: f32, text: &str) {
+    let scaled_font = font.as_scaled(scale);
+    let baseline = y + scaled_font.ascent();
+    let mut caret = x;
+    let mut previous = None;
+    for char in text.chars() {
+        let glyph_id = scaled_font.glyph_id(char);
+        if let Some(previous) = previous {
+            caret += scaled_font.kern(previous, glyph_id);
+        }
+        let glyph = glyph_id.with_scale_and_position(scale, point(caret, baseline));
+        caret += scaled_font.h_advance(glyph_id);
+        previous = Some(glyph_id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|glyph_x, glyph_y, coverage| {
+            let pixel_x = bounds.min.x as i32 + glyph_x as i32;
+            let pixel_y = bounds.min.y as i32 + glyph_y as i32;
+            if pixel_x < 0
+                || pixel_y < 0
+                || pixel_x >= image.width() as i32
+                || pixel_y >= image.height() as i32
+            {
+                return;
+            }
+            let pixel = image.get_pixel_mut(pixel_x as u32, pixel_y as u32);
+            *pixel = blend(*pixel, TEXT, coverage);
+        });
+    }
+}
+
+fn draw_border(image: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
+    for offset_y in 0..PANEL_HEIGHT {
+        for offset_x in 0..PANEL_WIDTH {
+            let is_border = offset_x < BORDER_WIDTH
+                || offset_y < BORDER_WIDTH
+                || offset_x >= PANEL_WIDTH - BORDER_WIDTH
+                || offset_y >= PANEL_HEIGHT - BORDER_WIDTH;
+            if is_border {
+                image.put_pixel(x + offset_x, y + offset_y, color);
+            }
+        }
+    }
+}
+
+fn blend(background: Rgba<u8>, foreground: Rgba<u8>, coverage: f32) -> Rgba<u8> {
+    let coverage = coverage.clamp(0.0, 1.0);
+    let Rgba(background) = background;
+    let Rgba(foreground) = foreground;
+    let mix = |a: u8, b: u8| (a as f32 * (1.0 - coverage) + b as f32 * coverage).round() as u8;
+    Rgba([
+        mix(background[0], foreground[0]),
+        mix(background[1], foreground[1]),
+        mix(background[2], foreground[2]),
+        255,
+    ])
+}
@@ -0,0 +1,224 @@
+use crate::constants::*;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+
+/// Line of props file, as `effort <seconds>`
+const EFFORT_KEY: &str = "effort";
+/// Line of props file, as `author <contribution> <name>`, for each contributor
+const AUTHOR_KEY: &str = "author";
+/// Line of props file, as `approval <state>`
+const APPROVAL_KEY: &str = "approval";
+/// Line of props file, as `approval-comment <text>`, from most recent review
+const APPROVAL_COMMENT_KEY: &str = "approval-comment";
+
+/// Whether post is ready to upload. Posts from before approval was recorded have no state, and
+/// are treated as approved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Approval {
+    /// Transcript is unfinished, eg. still has machine translation, or was rejected in review
+    Draft,
+    NeedsReview,
+    Approved,
+}
+
+impl Approval {
+    const ALL: [Self; 3] = [Self::Draft, Self::NeedsReview, Self::Approved];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::NeedsReview => "needs-review",
+            Self::Approved => "approved",
+        }
+    }
+}
+
+impl fmt::Display for Approval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Part of a post made by an author
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Contribution {
+    Transcript,
+    Svg,
+}
+
+impl Contribution {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Transcript => "transcript",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+impl fmt::Display for Contribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Author {
+    pub contribution: Contribution,
+    pub name: String,
+}
+
+/// Total time spent editing transcripts of post, if any was recorded
+pub fn read_effort(post_path: &Path) -> Result<Option<Duration>> {
+    let path = post_path.join(post_file::PROPS);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading props file")?;
+    Ok(contents
+        .lines()
+        .find_map(parse_effort_line)
+        .map(Duration::from_secs))
+}
+
+/// Adds to total editing time, keeping other lines of props file
+pub fn add_effort(post_path: &Path, elapsed: Duration) -> Result<()> {
+    let path = post_path.join(post_file::PROPS);
+    let contents = if path.exists() {
+        fs::read_to_string(&path).with_context(|| "Reading props file")?
+    } else {
+        String::new()
+    };
+
+    let mut total = elapsed.as_secs();
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        match parse_effort_line(line) {
+            Some(seconds) => total += seconds,
+            None => lines.push(line.to_string()),
+        }
+    }
+    lines.push(format!("{} {}", EFFORT_KEY, total));
+
+    fs::write(&path, lines.join("\n") + "\n").with_context(|| "Writing props file")
+}
+
+/// Contributors of post, in order of first contribution
+pub fn read_authors(post_path: &Path) -> Result<Vec<Author>> {
+    let path = post_path.join(post_file::PROPS);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading props file")?;
+    Ok(contents.lines().filter_map(parse_author_line).collect())
+}
+
+/// Does nothing if author already contributed the same part, keeping other lines of props file
+pub fn add_author(post_path: &Path, contribution: Contribution, name: &str) -> Result<()> {
+    let author = Author {
+        contribution,
+        name: name.trim().to_string(),
+    };
+    if read_authors(post_path)?.contains(&author) {
+        return Ok(());
+    }
+    let path = post_path.join(post_file::PROPS);
+    let mut contents = if path.exists() {
+        fs::read_to_string(&path).with_context(|| "Reading props file")?
+    } else {
+        String::new()
+    };
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents += &format!("{} {} {}\n", AUTHOR_KEY, author.contribution, author.name);
+    fs::write(&path, contents).with_context(|| "Writing props file")
+}
+
+fn parse_author_line(line: &str) -> Option<Author> {
+    let mut parts = line.trim().splitn(3, ' ');
+    if parts.next()? != AUTHOR_KEY {
+        return None;
+    }
+    let contribution = match parts.next()? {
+        "transcript" => Contribution::Transcript,
+        "svg" => Contribution::Svg,
+        _ => return None,
+    };
+    let name = parts.next()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some(Author {
+        contribution,
+        name: name.to_string(),
+    })
+}
+
+/// `None` if no approval was recorded
+pub fn read_approval(post_path: &Path) -> Result<Option<Approval>> {
+    Ok(read_value(post_path, APPROVAL_KEY)?.and_then(|value| {
+        Approval::ALL
+            .into_iter()
+            .find(|approval| approval.as_str() == value)
+    }))
+}
+
+pub fn read_approval_comment(post_path: &Path) -> Result<Option<String>> {
+    read_value(post_path, APPROVAL_COMMENT_KEY)
+}
+
+/// Replaces any previous state and comment, keeping other lines of props file
+pub fn set_approval(post_path: &Path, approval: Approval, comment: Option<&str>) -> Result<()> {
+    let path = post_path.join(post_file::PROPS);
+    let contents = if path.exists() {
+        fs::read_to_string(&path).with_context(|| "Reading props file")?
+    } else {
+        String::new()
+    };
+
+    let mut lines: Vec<String> = contents
+        .lines()
+        .filter(|line| {
+            let key = line.trim().split(' ').next().unwrap_or_default();
+            key != APPROVAL_KEY && key != APPROVAL_COMMENT_KEY
+        })
+        .map(String::from)
+        .collect();
+    lines.push(format!("{} {}", APPROVAL_KEY, approval));
+    if let Some(comment) = comment.map(str::trim).filter(|comment| !comment.is_empty()) {
+        // Newlines would split the line
+        lines.push(format!(
+            "{} {}",
+            APPROVAL_COMMENT_KEY,
+            comment.replace('\n', " ")
+        ));
+    }
+
+    fs::write(&path, lines.join("\n") + "\n").with_context(|| "Writing props file")
+}
+
+/// Value of first line with key
+fn read_value(post_path: &Path, key: &str) -> Result<Option<String>> {
+    let path = post_path.join(post_file::PROPS);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading props file")?;
+    Ok(contents.lines().find_map(|line| {
+        let (line_key, value) = line.trim().split_once(' ')?;
+        (line_key == key).then(|| value.trim().to_string())
+    }))
+}
+
+fn parse_effort_line(line: &str) -> Option<u64> {
+    let (key, value) = line.trim().split_once(' ')?;
+    if key != EFFORT_KEY {
+        return None;
+    }
+    value.trim().parse().ok()
+}
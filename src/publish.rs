@@ -0,0 +1,113 @@
+use crate::commands::{self, FormField};
+use crate::config::{PostLayout, PublishConfig, Variant};
+use crate::file;
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum PublishTarget {
+    /// Discord webhook
+    Discord,
+    /// Mastodon status with image attachments
+    Mastodon,
+    /// Generic HTTP POST, with `caption` field and `image` files
+    Http,
+}
+
+impl PublishTarget {
+    /// As written in uploads log
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Discord => "discord",
+            Self::Mastodon => "mastodon",
+            Self::Http => "http",
+        }
+    }
+}
+
+pub fn publish(
+    config: &PublishConfig,
+    layout: &PostLayout,
+    variant: &Variant,
+    target: PublishTarget,
+    post_path: &Path,
+    caption: String,
+) -> Result<()> {
+    let images = [
+        post_path.join(&layout.duplicate),
+        post_path.join(&variant.image),
+    ];
+
+    match target {
+        PublishTarget::Discord => {
+            let discord = config
+                .discord
+                .as_ref()
+                .with_context(|| "Missing `[publish.discord]` config")?;
+            let mut fields = vec![FormField::text("content", caption)];
+            for (i, image) in images.iter().enumerate() {
+                fields.push(FormField::file(format!("files[{}]", i), image));
+            }
+            commands::http_post_form(&discord.webhook_url, &[], &fields)?;
+        }
+
+        PublishTarget::Mastodon => {
+            let mastodon = config
+                .mastodon
+                .as_ref()
+                .with_context(|| "Missing `[publish.mastodon]` config")?;
+            let authorization = format!("Authorization: Bearer {}", mastodon.access_token);
+            let base_url = mastodon.instance_url.trim_end_matches('/');
+            let transcript = read_optional(&post_path.join(&variant.transcript))?;
+
+            let mut fields = vec![FormField::text("status", caption)];
+            for image in &images {
+                let response = commands::http_post_form(
+                    &format!("{}/api/v2/media", base_url),
+                    &[authorization.as_str()],
+                    &[
+                        FormField::file("file", image),
+                        FormField::text("description", transcript.clone()),
+                    ],
+                )?;
+                let media: MastodonMedia = serde_json::from_slice(&response)
+                    .with_context(|| "Parsing Mastodon media response")?;
+                fields.push(FormField::text("media_ids[]", media.id));
+            }
+            commands::http_post_form(
+                &format!("{}/api/v1/statuses", base_url),
+                &[authorization.as_str()],
+                &fields,
+            )?;
+        }
+
+        PublishTarget::Http => {
+            let http = config
+                .http
+                .as_ref()
+                .with_context(|| "Missing `[publish.http]` config")?;
+            let mut fields = vec![FormField::text("caption", caption)];
+            for image in &images {
+                fields.push(FormField::file("image", image));
+            }
+            commands::http_post_form(&http.url, &[], &fields)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct MastodonMedia {
+    id: String,
+}
+
+fn read_optional(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    file::read_text(path)
+}
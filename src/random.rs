@@ -1,5 +1,7 @@
 use std::cell::RefCell;
 
+use rand::Rng as _;
+
 type Rng = rand::rngs::ThreadRng;
 
 thread_local! {
@@ -23,3 +25,15 @@ where
         func(rng)
     })
 }
+
+/// Uniformly random item, using reservoir sampling so items are never collected
+pub fn sample<T>(items: impl IntoIterator<Item = T>) -> Option<T> {
+    let mut chosen = None;
+    for (index, item) in items.into_iter().enumerate() {
+        // Replaces chosen item with probability `1 / (index + 1)`
+        if with_rng(|rng| rng.gen_range(0..=index)) == 0 {
+            chosen = Some(item);
+        }
+    }
+    chosen
+}
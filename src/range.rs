@@ -1,6 +1,7 @@
+use std::fmt;
 use std::str::FromStr;
 
-use chrono::{Datelike as _, NaiveDate};
+use chrono::{Datelike as _, NaiveDate, Weekday};
 
 /// Always inclusive
 #[derive(Clone, Copy, Debug)]
@@ -9,6 +10,34 @@ pub struct DateRange {
     to: MonthDay,
 }
 
+/// Always inclusive. Unlike `DateRange`, includes year.
+#[derive(Clone, Copy, Debug)]
+pub struct FullDateRange {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+/// A single month of a specific year
+#[derive(Clone, Copy, Debug)]
+pub struct YearMonth {
+    year: i32,
+    month: u32,
+}
+
+/// A whole year, or a single month of one
+#[derive(Clone, Copy, Debug)]
+pub enum YearOrMonth {
+    Year(i32),
+    Month(YearMonth),
+}
+
+/// Set of weekdays which a date may fall on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeekdayFilter {
+    /// Bit `n` is set for `n` days from Monday
+    mask: u8,
+}
+
 // Invariant: date.year is always constant: `MonthDay::YEAR`
 #[derive(Clone, Copy, Debug)]
 struct MonthDay {
@@ -23,12 +52,123 @@ impl DateRange {
         }
     }
 
+    /// Only the month and day of `date`, in any year
+    pub fn anniversary_of(date: NaiveDate) -> Self {
+        let day = MonthDay::from(date);
+        Self { from: day, to: day }
+    }
+
     pub fn contains(&self, date: NaiveDate) -> bool {
         let date = MonthDay::from(date);
         date >= self.from && date <= self.to
     }
 }
 
+impl FullDateRange {
+    pub fn all() -> Self {
+        Self {
+            from: NaiveDate::MIN,
+            to: NaiveDate::MAX,
+        }
+    }
+
+    pub fn year(year: i32) -> Option<Self> {
+        Some(Self {
+            from: NaiveDate::from_ymd_opt(year, 1, 1)?,
+            to: NaiveDate::from_ymd_opt(year, 12, 31)?,
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.from && date <= self.to
+    }
+
+    /// Accepts `YYYY-MM-DD` or `YYYY`, where a year is its first or last day, depending on `is_end`
+    fn parse_bound(string: &str, is_end: bool) -> Option<NaiveDate> {
+        if let Ok(date) = NaiveDate::parse_from_str(string, "%Y-%m-%d") {
+            return Some(date);
+        }
+        let year: i32 = string.parse().ok()?;
+        if is_end {
+            NaiveDate::from_ymd_opt(year, 12, 31)
+        } else {
+            NaiveDate::from_ymd_opt(year, 1, 1)
+        }
+    }
+}
+
+impl WeekdayFilter {
+    const ALL_MASK: u8 = 0b111_1111;
+
+    pub fn all() -> Self {
+        Self {
+            mask: Self::ALL_MASK,
+        }
+    }
+
+    /// Empty iterator gives all weekdays
+    pub fn from_weekdays(weekdays: impl IntoIterator<Item = Weekday>) -> Self {
+        let mask = weekdays
+            .into_iter()
+            .fold(0, |mask, weekday| mask | Self::bit(weekday));
+        if mask == 0 {
+            return Self::all();
+        }
+        Self { mask }
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.mask == Self::ALL_MASK
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.mask & Self::bit(date.weekday()) != 0
+    }
+
+    fn bit(weekday: Weekday) -> u8 {
+        1 << weekday.num_days_from_monday()
+    }
+}
+
+impl YearMonth {
+    pub fn of(date: NaiveDate) -> Self {
+        Self {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date.year() == self.year && date.month() == self.month
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("month should be valid")
+    }
+}
+
+impl YearOrMonth {
+    pub fn year(&self) -> i32 {
+        match self {
+            Self::Year(year) => *year,
+            Self::Month(month) => month.year,
+        }
+    }
+
+    pub fn months(&self) -> Vec<YearMonth> {
+        match *self {
+            Self::Year(year) => (1..=12).map(|month| YearMonth { year, month }).collect(),
+            Self::Month(month) => vec![month],
+        }
+    }
+}
+
+impl fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
 impl MonthDay {
     const YEAR: i32 = 0;
 
@@ -107,3 +247,55 @@ impl TryFrom<&str> for MonthDay {
         Self::from_ymd_opt(month, day).ok_or(())
     }
 }
+
+impl FromStr for YearMonth {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid month: '{}'. Should be in YYYY-MM format", string);
+
+        let (year, month) = string.split_once('-').ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+
+        if NaiveDate::from_ymd_opt(year, month, 1).is_none() {
+            return Err(invalid());
+        }
+        Ok(Self { year, month })
+    }
+}
+
+impl FromStr for YearOrMonth {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.contains('-') {
+            return Ok(Self::Month(string.parse()?));
+        }
+        let invalid = || format!("Invalid year: '{}'. Should be in YYYY format", string);
+        let year: i32 = string.parse().map_err(|_| invalid())?;
+        if NaiveDate::from_ymd_opt(year, 1, 1).is_none() {
+            return Err(invalid());
+        }
+        Ok(Self::Year(year))
+    }
+}
+
+impl FromStr for FullDateRange {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (from, to) = string.split_once("..").unwrap_or((string, string));
+
+        let from = Self::parse_bound(from, false)
+            .ok_or_else(|| format!("Invalid start date: '{}'", from))?;
+        let to =
+            Self::parse_bound(to, true).ok_or_else(|| format!("Invalid end date: '{}'", to))?;
+
+        if from > to {
+            return Err("End date must be after start date".to_string());
+        }
+
+        Ok(Self { from, to })
+    }
+}
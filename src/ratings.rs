@@ -0,0 +1,39 @@
+use crate::location::Location;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+
+pub const MAX_RATING: u8 = 5;
+
+/// Line of ratings file, as `<date> <rating>`. Skips malformed lines.
+pub fn load(location: &Location) -> Result<BTreeMap<NaiveDate, u8>> {
+    let path = location.ratings_file();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading ratings file")?;
+    let ratings = contents
+        .lines()
+        .filter_map(|line| {
+            let (date, rating) = line.trim().split_once(' ')?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            let rating = rating.trim().parse().ok()?;
+            Some((date, rating))
+        })
+        .collect();
+    Ok(ratings)
+}
+
+/// Replaces any existing rating of date
+pub fn set(location: &Location, date: NaiveDate, rating: u8) -> Result<()> {
+    let mut ratings = load(location)?;
+    ratings.insert(date, rating);
+    let contents: String = ratings
+        .iter()
+        .map(|(date, rating)| format!("{} {}\n", date, rating))
+        .collect();
+    fs::write(location.ratings_file(), contents).with_context(|| "Writing ratings file")
+}
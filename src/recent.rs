@@ -0,0 +1,88 @@
+use crate::location::Location;
+
+use std::fs;
+use std::io::Write as _;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context as _, Result};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Arguments of current invocation, recorded with every displayed date
+static COMMAND_LINE: OnceLock<String> = OnceLock::new();
+
+/// Line of recent dates file, as `<date>\t<time>\t<command line>`.
+/// Older files only have dates, so other fields may be missing.
+#[derive(Debug)]
+pub struct RecentEntry {
+    pub date: NaiveDate,
+    pub time: Option<NaiveDateTime>,
+    /// Arguments given to `garfutils`, such as `show --sunday --new`
+    pub command_line: Option<String>,
+}
+
+pub fn init_command_line(args: impl IntoIterator<Item = String>) {
+    let command_line = args.into_iter().collect::<Vec<_>>().join(" ");
+    // Tabs separate fields
+    let _ = COMMAND_LINE.set(command_line.replace('\t', " "));
+}
+
+pub fn append(location: &Location, date: NaiveDate) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(location.recent_file())
+        .with_context(|| "Opening recent dates file")?;
+    writeln!(
+        file,
+        "{}\t{}\t{}",
+        date,
+        Local::now().format(TIME_FORMAT),
+        COMMAND_LINE.get().map(String::as_str).unwrap_or_default()
+    )
+    .with_context(|| "Writing to recent dates file")
+}
+
+/// Oldest first. Skips malformed lines.
+pub fn load(location: &Location) -> Result<Vec<RecentEntry>> {
+    let path = location.recent_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading recent dates file")?;
+    Ok(contents.lines().filter_map(parse_line).collect())
+}
+
+/// `1` is most recent
+pub fn nth_most_recent(location: &Location, n: usize) -> Result<RecentEntry> {
+    let mut entries = load(location)?;
+    if entries.is_empty() {
+        bail!("No comics have been shown yet");
+    }
+    if n == 0 || n > entries.len() {
+        bail!(
+            "Only {} comic(s) have been shown, so there is no comic {} back",
+            entries.len(),
+            n
+        );
+    }
+    Ok(entries.swap_remove(entries.len() - n))
+}
+
+fn parse_line(line: &str) -> Option<RecentEntry> {
+    let mut fields = line.trim_end().split('\t');
+    let date = NaiveDate::parse_from_str(fields.next()?.trim(), "%Y-%m-%d").ok()?;
+    let time = fields
+        .next()
+        .and_then(|time| NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok());
+    let command_line = fields
+        .next()
+        .filter(|command_line| !command_line.is_empty())
+        .map(String::from);
+    Some(RecentEntry {
+        date,
+        time,
+        command_line,
+    })
+}
@@ -0,0 +1,104 @@
+use crate::range::YearMonth;
+
+use std::fmt::Write as _;
+
+use chrono::NaiveDate;
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Paths are relative to report directory
+#[derive(Debug)]
+pub struct ReportEntry {
+    pub id: String,
+    pub title: String,
+    pub date: NaiveDate,
+    pub image_path: String,
+    pub thumbnail_path: String,
+}
+
+impl ReportFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Markdown => "report.md",
+            Self::Html => "report.html",
+        }
+    }
+
+    pub fn render(&self, month: YearMonth, entries: &[ReportEntry]) -> String {
+        match self {
+            Self::Markdown => render_markdown(month, entries),
+            Self::Html => render_html(month, entries),
+        }
+    }
+}
+
+fn render_markdown(month: YearMonth, entries: &[ReportEntry]) -> String {
+    let mut output = String::new();
+    writeln!(output, "# Posts completed in {}\n", month).expect("write to string should not fail");
+    if entries.is_empty() {
+        output += "No posts completed this month.\n";
+    }
+    for entry in entries {
+        writeln!(
+            output,
+            "## {} — {}\n\n\
+            [![{}]({})]({})\n\n\
+            Original comic: {}\n",
+            entry.id, entry.title, entry.title, entry.thumbnail_path, entry.image_path, entry.date,
+        )
+        .expect("write to string should not fail");
+    }
+    output
+}
+
+fn render_html(month: YearMonth, entries: &[ReportEntry]) -> String {
+    let mut output = String::new();
+    writeln!(
+        output,
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head><meta charset=\"utf-8\"><title>Posts completed in {month}</title></head>\n\
+        <body>\n\
+        <h1>Posts completed in {month}</h1>",
+    )
+    .expect("write to string should not fail");
+    if entries.is_empty() {
+        output += "<p>No posts completed this month.</p>\n";
+    }
+    for entry in entries {
+        let title = escape_html(&entry.title);
+        writeln!(
+            output,
+            "<h2>{} — {}</h2>\n\
+            <a href=\"{}\"><img src=\"{}\" alt=\"{}\"></a>\n\
+            <p>Original comic: {}</p>",
+            escape_html(&entry.id),
+            title,
+            escape_html(&entry.image_path),
+            escape_html(&entry.thumbnail_path),
+            title,
+            entry.date,
+        )
+        .expect("write to string should not fail");
+    }
+    output += "</body>\n</html>\n";
+    output
+}
+
+pub fn escape_html(string: &str) -> String {
+    let mut output = String::with_capacity(string.len());
+    for ch in string.chars() {
+        match ch {
+            '&' => output += "&amp;",
+            '<' => output += "&lt;",
+            '>' => output += "&gt;",
+            '"' => output += "&quot;",
+            _ => output.push(ch),
+        }
+    }
+    output
+}
@@ -0,0 +1,123 @@
+use crate::constants::*;
+use crate::location::{Location, Stage};
+use crate::names;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context as _, Result};
+use chrono::{Datelike as _, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Planned publishing dates of posts which have not been uploaded yet
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    /// Post id -> publish date
+    #[serde(default)]
+    posts: BTreeMap<String, NaiveDate>,
+}
+
+impl Schedule {
+    pub fn load(location: &Location) -> Result<Self> {
+        let path = location.schedule_file();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).with_context(|| "Reading schedule file")?;
+        toml::from_str(&contents).with_context(|| "Parsing schedule file")
+    }
+
+    pub fn save(&self, location: &Location) -> Result<()> {
+        let contents = toml::to_string(self).with_context(|| "Serializing schedule")?;
+        fs::write(location.schedule_file(), contents).with_context(|| "Writing schedule file")
+    }
+
+    pub fn set(&mut self, id: &str, date: NaiveDate) {
+        self.posts.insert(id.to_string(), date);
+    }
+
+    /// Returns `true` if post was scheduled
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.posts.remove(id).is_some()
+    }
+
+    pub fn get(&self, id: &str) -> Option<NaiveDate> {
+        self.posts.get(id).copied()
+    }
+
+    /// Sorted by publish date, then id
+    pub fn entries(&self) -> Vec<(NaiveDate, &str)> {
+        let mut entries: Vec<_> = self
+            .posts
+            .iter()
+            .map(|(id, date)| (*date, id.as_str()))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    /// Posts scheduled for `date` or earlier
+    pub fn due(&self, date: NaiveDate) -> Vec<(NaiveDate, &str)> {
+        let mut entries = self.entries();
+        entries.retain(|(publish_date, _)| *publish_date <= date);
+        entries
+    }
+}
+
+/// How `next --upload` chooses between posts which are ready to upload
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UploadPolicy {
+    /// Lowest post id, ie. finished earliest
+    #[default]
+    Oldest,
+    /// Earliest scheduled date, then lowest post id
+    Scheduled,
+    /// Comic weekday following that of the most recently uploaded post, then lowest post id
+    Weekdays,
+}
+
+/// Completed post to upload next, of those which are transcribed but have never been uploaded
+pub fn next_upload(location: &Location, policy: UploadPolicy) -> Result<Option<String>> {
+    let posts_dir = location.posts_dir();
+    let mut candidates = Vec::new();
+    let mut latest_upload: Option<(NaiveDate, NaiveDate)> = None;
+    for (id, date) in names::get_stage_post_dates(location, Stage::Completed)? {
+        let post_path = posts_dir.join(&id);
+        let uploaded_path = post_path.join(post_file::UPLOADED);
+        if uploaded_path.exists() {
+            let uploaded =
+                fs::read_to_string(&uploaded_path).with_context(|| "Reading upload date file")?;
+            let Ok(uploaded) = NaiveDate::parse_from_str(uploaded.trim(), "%Y-%m-%d") else {
+                continue;
+            };
+            if latest_upload.is_none_or(|(latest, _)| uploaded >= latest) {
+                latest_upload = Some((uploaded, date));
+            }
+        } else if post_path.join(post_file::TRANSCRIPT).exists() {
+            candidates.push((id, date));
+        }
+    }
+    // Numeric ids first, in order
+    candidates.sort_by_key(|(id, _)| (id.parse::<u32>().unwrap_or(u32::MAX), id.clone()));
+
+    let chosen = match policy {
+        UploadPolicy::Oldest => candidates.first(),
+        UploadPolicy::Scheduled => {
+            let schedule = Schedule::load(location)?;
+            candidates
+                .iter()
+                .min_by_key(|(id, _)| schedule.get(id).unwrap_or(NaiveDate::MAX))
+        }
+        UploadPolicy::Weekdays => {
+            let next_weekday = latest_upload
+                .map(|(_, date)| date.weekday().succ().num_days_from_monday())
+                .unwrap_or(0);
+            candidates.iter().min_by_key(|(_, date)| {
+                (date.weekday().num_days_from_monday() + 7 - next_weekday) % 7
+            })
+        }
+    };
+    Ok(chosen.map(|(id, _)| id.clone()))
+}
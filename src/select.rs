@@ -0,0 +1,53 @@
+use crate::ui::Prompter;
+
+/// Items after this many are only shown once filtered
+const MAX_SHOWN: usize = 20;
+
+/// Lists items as a numbered menu, and returns index of chosen item.
+/// A number chooses that item, other text filters items by fuzzy match (characters in order), and
+/// an empty line chooses the first item shown. `None` if cancelled with `q`.
+pub fn choose(prompter: &dyn Prompter, prompt: &str, items: &[String]) -> Option<usize> {
+    let mut query = String::new();
+    loop {
+        let shown: Vec<usize> = (0..items.len())
+            .filter(|index| fuzzy_matches(&items[*index], &query))
+            .collect();
+        if shown.is_empty() {
+            println!("Nothing matches `{}`.", query);
+            query.clear();
+            continue;
+        }
+        for (number, index) in shown.iter().take(MAX_SHOWN).enumerate() {
+            println!("{:>3}) {}", number + 1, items[*index]);
+        }
+        if shown.len() > MAX_SHOWN {
+            println!("     (and {} more)", shown.len() - MAX_SHOWN);
+        }
+
+        let answer = prompter.ask(&format!("{} [number, filter, or q]:", prompt));
+        if answer == "q" {
+            return None;
+        }
+        if answer.is_empty() {
+            return Some(shown[0]);
+        }
+        if let Ok(number) = answer.parse::<usize>() {
+            if (1..=shown.len().min(MAX_SHOWN)).contains(&number) {
+                return Some(shown[number - 1]);
+            }
+            println!("Invalid number.");
+            continue;
+        }
+        query = answer;
+    }
+}
+
+/// Case-insensitive
+fn fuzzy_matches(item: &str, query: &str) -> bool {
+    let mut item_chars = item.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|ch| !ch.is_whitespace())
+        .all(|query_char| item_chars.any(|item_char| item_char == query_char))
+}
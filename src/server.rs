@@ -0,0 +1,321 @@
+use crate::constants::*;
+use crate::error::{Error, ErrorKind};
+use crate::file;
+use crate::jobs::{self, Job};
+use crate::location::{Location, Stage};
+use crate::names;
+use crate::special;
+use crate::submissions;
+
+use std::fs;
+use std::io::Read as _;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest accepted request body, for small JSON objects
+const MAX_BODY_SIZE: u64 = 4096;
+const MAX_TRANSCRIPT_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Serialize)]
+struct PostSummary {
+    id: String,
+    stage: &'static str,
+    date: String,
+    title: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PostDetails {
+    #[serde(flatten)]
+    summary: PostSummary,
+    special: Option<String>,
+    props: Vec<String>,
+    transcript: Option<String>,
+    /// File names which can be fetched from `/posts/<id>/images/<file>`
+    images: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClaimRequest {
+    /// Who is transcribing the post, shown by `pull-queue`
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SubmissionReply<'a> {
+    id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(status: u16, value: &impl Serialize) -> Result<Self> {
+        Ok(Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).with_context(|| "Serializing response")?,
+        })
+    }
+}
+
+/// Serves a JSON API over the archive, until the process is killed. There is no authentication,
+/// so only bind to a public address on a trusted network.
+///
+/// - `GET /posts`: id, stage, date, and title of every post
+/// - `GET /posts/<id>`: metadata and transcript of a post
+/// - `GET /posts/<id>/images/<file>`: image of a post, as listed in its metadata
+/// - `GET /jobs`: queued jobs, in order
+/// - `POST /jobs`: queue a job, eg. `{"kind": "make", "date": "2024-01-31"}` or
+///   `{"kind": "transcribe", "id": "abcd:2024-01-31"}`, to run later with `jobs run`
+/// - `GET /claims`: posts being transcribed by someone else
+/// - `POST /claims`: claim an untranscribed post to transcribe, eg. `{"name": "Alex"}`
+/// - `PUT /posts/<id>/transcript`: submit transcript of a claimed post, as plain text, to be moved
+///   into the post with `pull-queue`
+pub fn serve(location: &Location, host: &str, port: u16) -> Result<()> {
+    let server = Server::http((host, port))
+        .map_err(|error| anyhow!(error))
+        .with_context(|| format!("Binding to {}:{}", host, port))?;
+    println!("Listening on http://{}:{}", host, port);
+
+    for mut request in server.incoming_requests() {
+        let reply = handle(location, &mut request).unwrap_or_else(|error| error_reply(&error));
+        println!("{} {} -> {}", request.method(), request.url(), reply.status);
+        let response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(
+                Header::from_bytes("Content-Type", reply.content_type)
+                    .expect("header should be valid"),
+            );
+        // Client may have disconnected, which should not stop the server
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+fn handle(location: &Location, request: &mut Request) -> Result<Reply> {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode_segment)
+        .collect::<Option<Vec<String>>>()
+        .with_context(|| Error::usage("Malformed URL"))?;
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    // Cloned, since body is read while matching
+    let method = request.method().clone();
+    match (&method, segments.as_slice()) {
+        (Method::Get, ["posts"]) => Reply::json(200, &list_posts(location)?),
+        (Method::Get, ["posts", id]) => Reply::json(200, &post_details(location, id)?),
+        (Method::Get, ["posts", id, "images", file_name]) => post_image(location, id, file_name),
+        (Method::Get, ["jobs"]) => Reply::json(200, &jobs::load(location)?),
+        (Method::Post, ["jobs"]) => {
+            let job: Job = serde_json::from_slice(&read_body(request, MAX_BODY_SIZE)?)
+                .map_err(|error| Error::usage(format!("Invalid job: {}", error)))?;
+            queue_job(location, &job)?;
+            Reply::json(201, &job)
+        }
+        (Method::Get, ["claims"]) => Reply::json(200, &submissions::load_claims(location)?),
+        (Method::Post, ["claims"]) => {
+            let body: ClaimRequest = serde_json::from_slice(&read_body(request, MAX_BODY_SIZE)?)
+                .map_err(|error| Error::usage(format!("Invalid claim: {}", error)))?;
+            if body.name.trim().is_empty() {
+                bail!(Error::usage("Name of claimant must not be empty"));
+            }
+            let id = submissions::claim(location, body.name.trim())?
+                .with_context(|| Error::missing_post("No untranscribed post is available"))?;
+            let post_path = location.posts_dir().join(&id);
+            Reply::json(201, &read_summary(Stage::Completed, id, &post_path)?)
+        }
+        (Method::Put, ["posts", id, "transcript"]) => {
+            if submissions::find_claim(location, id)?.is_none() {
+                bail!(Error::usage(
+                    "Post must be claimed before submitting a transcript"
+                ));
+            }
+            let transcript = String::from_utf8(read_body(request, MAX_TRANSCRIPT_SIZE)?)
+                .map_err(|_| Error::usage("Transcript is not valid UTF-8"))?;
+            submissions::submit(location, id, &transcript)?;
+            Reply::json(201, &SubmissionReply { id })
+        }
+        _ => bail!(Error::missing_post("No such endpoint")),
+    }
+}
+
+/// Fails if body is larger than `max_size`, instead of truncating it
+fn read_body(request: &mut Request, max_size: u64) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(max_size + 1)
+        .read_to_end(&mut body)
+        .with_context(|| "Reading request body")?;
+    if body.len() as u64 > max_size {
+        bail!(Error::usage("Request body is too large"));
+    }
+    Ok(body)
+}
+
+fn list_posts(location: &Location) -> Result<Vec<PostSummary>> {
+    let mut posts = Vec::new();
+    for stage in Stage::ALL {
+        let entries = file::sort_dir_entries(
+            file::read_dir(location.stage_dir(stage))?
+                .flatten()
+                .collect(),
+        );
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                let id = entry.file_name().to_string_lossy().to_string();
+                posts.push(read_summary(stage, id, &path)?);
+            }
+        }
+    }
+    Ok(posts)
+}
+
+fn post_details(location: &Location, id: &str) -> Result<PostDetails> {
+    let (stage, post_path) = names::find_post(location, id)?;
+
+    let special = special::read(&post_path)
+        .with_context(|| "Reading special file")?
+        .map(|special| special.kind.to_string());
+    let props_path = post_path.join(post_file::PROPS);
+    let props = if props_path.exists() {
+        fs::read_to_string(&props_path)
+            .with_context(|| "Reading props file")?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let transcript_path = post_path.join(post_file::TRANSCRIPT);
+    let transcript = if transcript_path.exists() {
+        Some(file::read_text(&transcript_path).with_context(|| "Reading transcript file")?)
+    } else {
+        None
+    };
+    let images = image_file_names(location)
+        .into_iter()
+        .filter(|file_name| post_path.join(file_name).is_file())
+        .collect();
+
+    Ok(PostDetails {
+        summary: read_summary(stage, id.to_string(), &post_path)?,
+        special,
+        props,
+        transcript,
+        images,
+    })
+}
+
+/// Only images of layout can be fetched, so no other file can be read
+fn post_image(location: &Location, id: &str, file_name: &str) -> Result<Reply> {
+    let (_, post_path) = names::find_post(location, id)?;
+    if !image_file_names(location)
+        .iter()
+        .any(|image| image == file_name)
+    {
+        bail!(Error::missing_post("Not an image of the post layout"));
+    }
+    let path = post_path.join(file_name);
+    if !path.is_file() {
+        bail!(Error::missing_post("Post does not have this image"));
+    }
+    Ok(Reply {
+        status: 200,
+        content_type: "image/png",
+        body: fs::read(&path).with_context(|| "Reading image file")?,
+    })
+}
+
+/// Checks that job could run now, so mistakes are reported to the client instead of later
+fn queue_job(location: &Location, job: &Job) -> Result<()> {
+    match job {
+        Job::Make { date } => {
+            if !location.source_file(*date).exists() {
+                bail!(Error::usage("Not the date of an existing comic"));
+            }
+        }
+        Job::Transcribe { id } => {
+            if !is_plain_name(id) || !location.posts_dir().join(id).is_dir() {
+                bail!(Error::missing_post("No completed post exists with that id"));
+            }
+        }
+    }
+    jobs::append(location, job)
+}
+
+fn read_summary(stage: Stage, id: String, post_path: &Path) -> Result<PostSummary> {
+    Ok(PostSummary {
+        id,
+        stage: stage.dir_name(),
+        date: file::read_first_line(&post_path.join(post_file::DATE))?,
+        title: file::read_first_line(&post_path.join(post_file::TITLE))?,
+    })
+}
+
+fn image_file_names(location: &Location) -> Vec<String> {
+    let layout = location.layout();
+    layout
+        .all_variants()
+        .into_iter()
+        .map(|variant| variant.image)
+        .chain([layout.duplicate.clone()])
+        .collect()
+}
+
+/// Status is chosen by kind of outermost error
+fn error_reply(error: &anyhow::Error) -> Reply {
+    let status = match error.downcast_ref::<Error>().map(Error::kind) {
+        Some(ErrorKind::Usage | ErrorKind::Validation) => 400,
+        Some(ErrorKind::MissingPost) => 404,
+        _ => 500,
+    };
+    let body = ErrorBody {
+        error: format!("{:#}", error),
+    };
+    Reply::json(status, &body).expect("error body should serialize")
+}
+
+/// Percent-decoding, since clients may encode `:` of post ids.
+/// `None` if malformed, or if it could refer to another directory.
+fn decode_segment(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded)
+        .ok()
+        .filter(|decoded| is_plain_name(decoded))
+}
+
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
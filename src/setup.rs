@@ -0,0 +1,235 @@
+use crate::actions;
+use crate::commands::{self, WindowManager};
+use crate::config::Config;
+use crate::location::Location;
+use crate::output;
+use crate::{ask, ask_yes_no};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _, Result};
+
+/// Only offered for the default location, when it doesn't exist yet
+pub fn offer_wizard(default_base_dir: &Path) -> Result<Option<PathBuf>> {
+    use std::io::{self, IsTerminal as _};
+    if default_base_dir.exists() || !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    println!(
+        "No data directory found at {}.",
+        default_base_dir.to_string_lossy()
+    );
+    if !ask_yes_no("Run first-time setup?") {
+        return Ok(None);
+    }
+    run_wizard(default_base_dir).map(Some)
+}
+
+/// Interactively creates a valid location, or completes an existing one, suggesting
+/// `suggested_dir`. Returns base directory of location.
+pub fn run_wizard(suggested_dir: &Path) -> Result<PathBuf> {
+    let answer = ask(&format!(
+        "Data directory [{}]:",
+        suggested_dir.to_string_lossy()
+    ));
+    let base_dir = if answer.is_empty() {
+        suggested_dir.to_path_buf()
+    } else {
+        PathBuf::from(answer)
+    };
+    let default_base_dir = Location::default_base_dir()?;
+    let default_base_dir = default_base_dir.as_path();
+
+    let location = Location::from_unchecked(base_dir.clone());
+    location.create_dirs()?;
+
+    let import_path = setup_source_dir(&location)?;
+    setup_watermarks_file(&location)?;
+    setup_icon_file(&location)?;
+    setup_config_file(&location)?;
+
+    // So the new location is found without `--location`
+    if base_dir != default_base_dir && !default_base_dir.exists() {
+        if let Some(parent) = default_base_dir.parent() {
+            fs::create_dir_all(parent).with_context(|| "Creating parent of default location")?;
+        }
+        symlink_dir(&base_dir, default_base_dir)
+            .with_context(|| "Linking default location to data directory")?;
+        println!(
+            "Linked {} to {}",
+            default_base_dir.to_string_lossy(),
+            base_dir.to_string_lossy()
+        );
+    }
+
+    let location =
+        Location::from(Some(base_dir.clone())).with_context(|| "Checking new location is valid")?;
+    if let Some(import_path) = import_path {
+        actions::import(&location, &import_path).with_context(|| "Importing source archive")?;
+    }
+    check_programs(&location);
+
+    output::success("Setup complete.");
+    Ok(base_dir)
+}
+
+/// A directory is linked as source directory, but a zip archive must be imported once the location
+/// is valid, so its path is returned
+fn setup_source_dir(location: &Location) -> Result<Option<PathBuf>> {
+    let source_dir = location.source_dir();
+    if source_dir.exists() {
+        return Ok(None);
+    }
+    let answer = ask(
+        "Path to existing source directory or zip archive (leave empty to create an empty one):",
+    );
+    if answer.is_empty() {
+        fs::create_dir(&source_dir).with_context(|| "Creating source directory")?;
+        return Ok(None);
+    }
+    let archive_path = PathBuf::from(answer);
+    if archive_path.is_file() {
+        fs::create_dir(&source_dir).with_context(|| "Creating source directory")?;
+        return Ok(Some(archive_path));
+    }
+    if !archive_path.is_dir() {
+        bail!("Source archive does not exist");
+    }
+    symlink_dir(&archive_path, &source_dir).with_context(|| "Linking source archive")?;
+    Ok(None)
+}
+
+fn setup_watermarks_file(location: &Location) -> Result<()> {
+    let watermarks_file = location.watermarks_file();
+    if watermarks_file.exists() {
+        return Ok(());
+    }
+    println!(
+        "More watermarks can be added later, one per line, to {:?}",
+        watermarks_file
+    );
+    // Empty at end of input, which would otherwise ask forever
+    let watermark = ask("Watermark text (empty to skip):");
+    if watermark.is_empty() {
+        println!("Skipped. Run `setup` again to add a watermark, before making posts.");
+        return Ok(());
+    }
+    fs::write(&watermarks_file, watermark + "\n").with_context(|| "Writing watermarks file")
+}
+
+fn setup_icon_file(location: &Location) -> Result<()> {
+    let icon_file = location.icon_file();
+    if icon_file.exists() {
+        return Ok(());
+    }
+    loop {
+        let answer = ask("Path to icon image (empty to skip):");
+        if answer.is_empty() {
+            println!("Skipped. Run `setup` again to add an icon, before making posts.");
+            return Ok(());
+        }
+        let path = PathBuf::from(answer);
+        if image::open(&path).is_err() {
+            println!("Not a valid image file.");
+            continue;
+        }
+        fs::copy(&path, &icon_file).with_context(|| "Copying icon image")?;
+        return Ok(());
+    }
+}
+
+/// Existing config file is kept as-is, since writing it would remove comments. Only settings which
+/// differ from their default are written, so other defaults can still change in later versions.
+fn setup_config_file(location: &Location) -> Result<()> {
+    let config_file = location.config_file();
+    if config_file.exists() {
+        println!(
+            "Keeping existing {}. Edit it to change settings.",
+            config_file.to_string_lossy()
+        );
+        return Ok(());
+    }
+    let defaults = Config::default();
+    let mut viewer = toml::Table::new();
+    let mut editor = toml::Table::new();
+    let answer = ask(&format!("Image viewer [{}]:", defaults.viewer.program));
+    if !answer.is_empty() && answer != defaults.viewer.program {
+        viewer.insert("program".to_string(), answer.into());
+    }
+    let answer = ask(&format!("Text editor [{}]:", defaults.editor.program));
+    if !answer.is_empty() && answer != defaults.editor.program {
+        editor.insert("program".to_string(), answer.into());
+    }
+    let window_manager = ask_window_manager(defaults.viewer.window_manager);
+    if window_manager != defaults.viewer.window_manager {
+        viewer.insert(
+            "window_manager".to_string(),
+            toml::Value::try_from(window_manager).with_context(|| "Serializing window manager")?,
+        );
+    }
+
+    let mut config = toml::Table::new();
+    for (name, table) in [("viewer", viewer), ("editor", editor)] {
+        if !table.is_empty() {
+            config.insert(name.to_string(), table.into());
+        }
+    }
+    fs::write(&config_file, config.to_string()).with_context(|| "Writing config file")?;
+    println!("Wrote {}", config_file.to_string_lossy());
+    Ok(())
+}
+
+fn ask_window_manager(current: WindowManager) -> WindowManager {
+    // Suggest Hyprland if running in it, even if not yet configured
+    let suggested = if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        WindowManager::Hyprland
+    } else {
+        current
+    };
+    let name = |window_manager| match window_manager {
+        WindowManager::Hyprland => "hyprland",
+        WindowManager::None => "none",
+    };
+    loop {
+        let answer = ask(&format!(
+            "Window manager (hyprland/none) [{}]:",
+            name(suggested)
+        ));
+        match answer.to_lowercase().as_str() {
+            "" => return suggested,
+            "hyprland" => return WindowManager::Hyprland,
+            "none" => return WindowManager::None,
+            _ => println!("Unknown window manager."),
+        }
+    }
+}
+
+/// Only warns, as not every command needs every program
+fn check_programs(location: &Location) {
+    let config = location.config();
+    let mut programs = vec![
+        config.viewer.program.as_str(),
+        config.editor.program.as_str(),
+    ];
+    if config.viewer.window_manager == WindowManager::Hyprland {
+        programs.push("hyprctl");
+    }
+    #[cfg(target_os = "linux")]
+    programs.push("wl-copy");
+    for program in programs {
+        if !commands::is_program_installed(program) {
+            output::warning(format!("`{}` was not found in `$PATH`", program));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(original, link)
+}
@@ -0,0 +1,39 @@
+use std::path::Path;
+
+use anyhow::{bail, Context as _, Result};
+use image::{imageops, GenericImageView as _, Rgba, RgbaImage};
+
+/// Maximum size of each image in sheet, which keeps its aspect ratio
+const TILE_WIDTH: u32 = 600;
+const TILE_HEIGHT: u32 = 300;
+/// Gap between tiles, and around edges of sheet
+const MARGIN: u32 = 20;
+const BACKGROUND: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// Grid of images in given order, filling each row left to right
+pub fn compose_sheet(paths: &[impl AsRef<Path>], columns: u32) -> Result<RgbaImage> {
+    if paths.is_empty() {
+        bail!("No images to compose");
+    }
+    let columns = columns.clamp(1, paths.len() as u32);
+    let rows = (paths.len() as u32).div_ceil(columns);
+
+    let width = columns * (TILE_WIDTH + MARGIN) + MARGIN;
+    let height = rows * (TILE_HEIGHT + MARGIN) + MARGIN;
+    let mut sheet = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+    for (i, path) in paths.iter().enumerate() {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .with_context(|| format!("Opening image `{}`", path.display()))?
+            .thumbnail(TILE_WIDTH, TILE_HEIGHT);
+
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        // Centered within tile
+        let x = MARGIN + column * (TILE_WIDTH + MARGIN) + (TILE_WIDTH - image.width()) / 2;
+        let y = MARGIN + row * (TILE_HEIGHT + MARGIN) + (TILE_HEIGHT - image.height()) / 2;
+        imageops::overlay(&mut sheet, &image.to_rgba8(), x as i64, y as i64);
+    }
+
+    Ok(sheet)
+}
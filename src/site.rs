@@ -0,0 +1,155 @@
+use crate::feed::{self, FeedEntry};
+use crate::report::escape_html;
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Datelike as _, Local, NaiveDate};
+
+/// Posts included in feed, most recently completed first
+const FEED_LENGTH: usize = 20;
+
+pub const IMAGES_DIR: &str = "images";
+pub const THUMBNAILS_DIR: &str = "thumbnails";
+pub const POSTS_DIR: &str = "posts";
+pub const FEED_FILE: &str = "feed.xml";
+
+#[derive(Debug)]
+pub struct SitePost {
+    pub id: String,
+    pub title: String,
+    pub date: NaiveDate,
+    /// Transcript rendered as alt text, if post has one
+    pub alt_text: Option<String>,
+    /// Modification time of post image
+    pub completed: DateTime<Local>,
+}
+
+/// Every file except images, as paths relative to site directory
+pub fn render(title: &str, base_url: Option<&str>, posts: &[SitePost]) -> Vec<(String, String)> {
+    let mut years: BTreeMap<i32, Vec<&SitePost>> = BTreeMap::new();
+    for post in posts {
+        years.entry(post.date.year()).or_default().push(post);
+    }
+
+    let mut files = vec![
+        ("index.html".to_string(), render_index(title, &years)),
+        (FEED_FILE.to_string(), render_feed(title, base_url, posts)),
+    ];
+    for (year, posts) in &years {
+        files.push((
+            format!("{}/index.html", year),
+            render_year(title, *year, posts),
+        ));
+    }
+    for post in posts {
+        files.push((
+            format!("{}/{}.html", POSTS_DIR, post.id),
+            render_post(title, post),
+        ));
+    }
+    files
+}
+
+fn render_index(title: &str, years: &BTreeMap<i32, Vec<&SitePost>>) -> String {
+    let mut output = page_start(title, title, "");
+    writeln!(output, "<p><a href=\"{}\">Feed</a></p>\n<ul>", FEED_FILE)
+        .expect("write to string should not fail");
+    for (year, posts) in years.iter().rev() {
+        writeln!(
+            output,
+            "<li><a href=\"{year}/index.html\">{year}</a> ({} post(s))</li>",
+            posts.len()
+        )
+        .expect("write to string should not fail");
+    }
+    output += "</ul>\n";
+    output + PAGE_END
+}
+
+/// Thumbnails of every post, by date of original comic
+fn render_year(title: &str, year: i32, posts: &[&SitePost]) -> String {
+    let mut posts = posts.to_vec();
+    posts.sort_by_key(|post| post.date);
+    let mut output = page_start(title, &format!("{} — {}", title, year), "../");
+    for post in posts {
+        writeln!(
+            output,
+            "<a href=\"../{posts_dir}/{id}.html\"><img src=\"../{thumbnails_dir}/{id}.png\" \
+            alt=\"{title}\" title=\"{date} — {title}\"></a>",
+            posts_dir = POSTS_DIR,
+            thumbnails_dir = THUMBNAILS_DIR,
+            id = escape_html(&post.id),
+            title = escape_html(&post.title),
+            date = post.date,
+        )
+        .expect("write to string should not fail");
+    }
+    output + PAGE_END
+}
+
+fn render_post(title: &str, post: &SitePost) -> String {
+    let mut output = page_start(title, &post.title, "../");
+    writeln!(
+        output,
+        "<img src=\"../{}/{}.png\" alt=\"{}\">\n\
+        <p>Original comic: {}</p>\n\
+        <p><a href=\"../{}/index.html\">{}</a></p>",
+        IMAGES_DIR,
+        escape_html(&post.id),
+        escape_html(post.alt_text.as_deref().unwrap_or(&post.title)),
+        post.date,
+        post.date.year(),
+        post.date.year(),
+    )
+    .expect("write to string should not fail");
+    output + PAGE_END
+}
+
+/// Links are relative to feed, unless `base_url` is set
+fn render_feed(title: &str, base_url: Option<&str>, posts: &[SitePost]) -> String {
+    let base_url = base_url.map(|url| url.trim_end_matches('/').to_string() + "/");
+    let base_url = base_url.as_deref().unwrap_or_default();
+    let mut posts: Vec<&SitePost> = posts.iter().collect();
+    posts.sort_by_key(|post| std::cmp::Reverse(post.completed));
+    posts.truncate(FEED_LENGTH);
+
+    let entries: Vec<FeedEntry> = posts
+        .into_iter()
+        .map(|post| FeedEntry {
+            title: post.title.clone(),
+            id: format!("{}{}/{}.html", base_url, POSTS_DIR, post.id),
+            link: Some(format!("{}{}/{}.html", base_url, POSTS_DIR, post.id)),
+            updated: post.completed,
+            summary: post.alt_text.clone().unwrap_or_else(|| post.title.clone()),
+            image_url: Some(format!("{}{}/{}.png", base_url, IMAGES_DIR, post.id)),
+        })
+        .collect();
+    feed::render_atom(
+        title,
+        &format!("{}{}", base_url, FEED_FILE),
+        Some(&format!("{}index.html", base_url)),
+        &entries,
+    )
+}
+
+/// `root` is relative path from page to site directory
+fn page_start(site_title: &str, heading: &str, root: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+        <html>\n\
+        <head><meta charset=\"utf-8\"><title>{}</title>\
+        <link rel=\"alternate\" type=\"application/atom+xml\" href=\"{}{}\"></head>\n\
+        <body>\n\
+        <p><a href=\"{}index.html\">{}</a></p>\n\
+        <h1>{}</h1>\n",
+        escape_html(heading),
+        root,
+        FEED_FILE,
+        root,
+        escape_html(site_title),
+        escape_html(heading),
+    )
+}
+
+const PAGE_END: &str = "</body>\n</html>\n";
@@ -0,0 +1,58 @@
+use crate::location::Location;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
+
+/// Comics which should never be posted, with an optional reason.
+/// Line of skip list, as `<date>` or `<date> <reason>`. Skips malformed lines.
+pub fn load(location: &Location) -> Result<BTreeMap<NaiveDate, String>> {
+    let path = location.skips_file();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading skip list")?;
+    let skips = contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (date, reason) = line.split_once(' ').unwrap_or((line, ""));
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            Some((date, reason.trim().to_string()))
+        })
+        .collect();
+    Ok(skips)
+}
+
+/// Replaces reason, if date is already skipped
+pub fn add(location: &Location, date: NaiveDate, reason: &str) -> Result<()> {
+    let mut skips = load(location)?;
+    skips.insert(date, reason.trim().to_string());
+    save(location, &skips)
+}
+
+/// Returns `false` if date was not skipped
+pub fn remove(location: &Location, date: NaiveDate) -> Result<bool> {
+    let mut skips = load(location)?;
+    if skips.remove(&date).is_none() {
+        return Ok(false);
+    }
+    save(location, &skips)?;
+    Ok(true)
+}
+
+fn save(location: &Location, skips: &BTreeMap<NaiveDate, String>) -> Result<()> {
+    let contents: String = skips
+        .iter()
+        .map(|(date, reason)| {
+            if reason.is_empty() {
+                format!("{}\n", date)
+            } else {
+                format!("{} {}\n", date, reason)
+            }
+        })
+        .collect();
+    fs::write(location.skips_file(), contents).with_context(|| "Writing skip list")
+}
@@ -0,0 +1,119 @@
+use crate::file;
+use crate::location::Location;
+use crate::random;
+use crate::timings::{self, Phase};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::{Context as _, Result};
+use chrono::{Datelike as _, NaiveDate};
+use rand::seq::SliceRandom as _;
+
+/// Sorted dates of every source comic, from cache file if it is up to date
+///
+/// Adding or removing a comic changes the modification time of its directory, so cache is stale
+/// if source directory, or any year directory of a cached date, was modified after it.
+/// Equal times are treated as stale, for filesystems with coarse timestamps.
+pub fn load(location: &Location) -> Result<Vec<NaiveDate>> {
+    timings::time(Phase::Scan, || load_untimed(location))
+}
+
+fn load_untimed(location: &Location) -> Result<Vec<NaiveDate>> {
+    if let Some(dates) = read_fresh_cache(location)? {
+        return Ok(dates);
+    }
+    let cache_path = location.source_dates_file();
+    let dates = scan(location).with_context(|| "Reading source directory")?;
+    let contents: String = dates.iter().map(|date| format!("{}\n", date)).collect();
+    fs::write(&cache_path, contents).with_context(|| "Writing source dates cache")?;
+    Ok(dates)
+}
+
+/// Uses cache if it is up to date. Otherwise samples source directory directly, which is cheaper
+/// than scanning and sorting it to rebuild the cache.
+pub fn choose_random(
+    location: &Location,
+    mut predicate: impl FnMut(&NaiveDate) -> bool,
+) -> Result<Option<NaiveDate>> {
+    if let Some(dates) = read_fresh_cache(location)? {
+        return Ok(random::sample(dates.into_iter().filter(predicate)));
+    }
+    let entry = timings::time(Phase::Scan, || {
+        file::sample_entry(location.source_dir(), |entry| {
+            matches!(file::get_date_from_path(entry.path()), Ok(Some(date)) if predicate(&date))
+        })
+    })
+    .with_context(|| "Reading source directory")?;
+    Ok(entry.and_then(|entry| file::get_date_from_path(entry.path()).ok().flatten()))
+}
+
+/// Distinct dates in random order, up to `count`
+pub fn choose_random_many(
+    location: &Location,
+    predicate: impl FnMut(&NaiveDate) -> bool,
+    count: usize,
+) -> Result<Vec<NaiveDate>> {
+    // Sorted first, so order only depends on rng
+    let mut dates: Vec<NaiveDate> = load(location)?.into_iter().filter(predicate).collect();
+    random::with_rng(|rng| dates.shuffle(rng));
+    dates.truncate(count);
+    Ok(dates)
+}
+
+/// `None` if cache does not exist, is malformed, or is stale
+fn read_fresh_cache(location: &Location) -> Result<Option<Vec<NaiveDate>>> {
+    let cache_path = location.source_dates_file();
+    match read_cache(&cache_path)? {
+        Some(dates) if !is_stale(location, &cache_path, &dates)? => Ok(Some(dates)),
+        _ => Ok(None),
+    }
+}
+
+/// `None` if cache does not exist or is malformed
+fn read_cache(path: &Path) -> Result<Option<Vec<NaiveDate>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading source dates cache")?;
+    let dates: Option<Vec<NaiveDate>> = contents
+        .lines()
+        .map(|line| NaiveDate::parse_from_str(line, "%Y-%m-%d").ok())
+        .collect();
+    Ok(dates)
+}
+
+fn is_stale(location: &Location, cache_path: &Path, dates: &[NaiveDate]) -> Result<bool> {
+    let cache_modified = modified_time(cache_path)?;
+    if modified_time(&location.source_dir())? >= cache_modified {
+        return Ok(true);
+    }
+    let years: BTreeSet<i32> = dates.iter().map(|date| date.year()).collect();
+    for year in years {
+        let year_dir = location.source_year_dir(year);
+        if year_dir.is_dir() && modified_time(&year_dir)? >= cache_modified {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn modified_time(path: &Path) -> Result<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("Reading modification time of {:?}", path))
+}
+
+fn scan(location: &Location) -> Result<Vec<NaiveDate>> {
+    let mut dates = Vec::new();
+    for entry in file::read_source_dir(location.source_dir())? {
+        if let Ok(Some(date)) = file::get_date_from_path(entry.path()) {
+            dates.push(date);
+        }
+    }
+    dates.sort();
+    dates.dedup();
+    Ok(dates)
+}
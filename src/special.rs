@@ -0,0 +1,56 @@
+use crate::constants::*;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+/// Why a post is special, eg. published for a holiday
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SpecialKind {
+    Anniversary,
+    Holiday,
+    #[default]
+    Custom,
+}
+
+impl fmt::Display for SpecialKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Anniversary => "anniversary",
+            Self::Holiday => "holiday",
+            Self::Custom => "custom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Contents of `special` file of a post, as TOML
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Special {
+    #[serde(rename = "type")]
+    pub kind: SpecialKind,
+    /// Eg. name of holiday
+    #[serde(default)]
+    pub description: String,
+}
+
+/// `None` if post is not special
+pub fn read(post_path: &Path) -> Result<Option<Special>> {
+    let path = post_path.join(post_file::SPECIAL);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading special file")?;
+    let special = toml::from_str(&contents).with_context(|| "Parsing special file")?;
+    Ok(Some(special))
+}
+
+pub fn write(post_path: &Path, special: &Special) -> Result<()> {
+    let contents = toml::to_string(special).with_context(|| "Serializing special file")?;
+    fs::write(post_path.join(post_file::SPECIAL), contents).with_context(|| "Writing special file")
+}
@@ -0,0 +1,122 @@
+use crate::constants::*;
+use crate::file;
+use crate::location::Location;
+use crate::names;
+
+use std::fs;
+use std::io::Write as _;
+
+use anyhow::{Context as _, Result};
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Line of claims file, as `<time>\t<id>\t<name>`. A claimed post is not given to anyone else,
+/// until its submitted transcript is accepted or rejected with `review`.
+#[derive(Debug, Serialize)]
+pub struct Claim {
+    pub time: NaiveDateTime,
+    pub id: String,
+    pub name: String,
+}
+
+/// Sorted by time of claim. Skips malformed lines.
+pub fn load_claims(location: &Location) -> Result<Vec<Claim>> {
+    let path = location.claims_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading claims file")?;
+    let mut claims: Vec<Claim> = contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let time = NaiveDateTime::parse_from_str(parts.next()?, TIME_FORMAT).ok()?;
+            let id = parts.next()?.to_string();
+            let name = parts.next()?.to_string();
+            Some(Claim { time, id, name })
+        })
+        .collect();
+    claims.sort_by_key(|claim| claim.time);
+    Ok(claims)
+}
+
+pub fn find_claim(location: &Location, id: &str) -> Result<Option<Claim>> {
+    Ok(load_claims(location)?
+        .into_iter()
+        .find(|claim| claim.id == id))
+}
+
+/// Claims first untranscribed post which is not claimed, and has no transcript waiting for review
+pub fn claim(location: &Location, name: &str) -> Result<Option<String>> {
+    let claims = load_claims(location)?;
+    let Some(id) = names::find_untranscribed_posts(location, None)?
+        .into_iter()
+        .find(|id| {
+            !claims.iter().any(|claim| &claim.id == id)
+                && !location
+                    .posts_dir()
+                    .join(id)
+                    .join(post_file::PENDING_TRANSCRIPT)
+                    .exists()
+        })
+    else {
+        return Ok(None);
+    };
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(location.claims_file())
+        .with_context(|| "Opening claims file")?;
+    writeln!(
+        file,
+        "{}\t{}\t{}",
+        Local::now().naive_local().format(TIME_FORMAT),
+        id,
+        // Tabs would split the line
+        name.replace(['\t', '\n'], " ")
+    )
+    .with_context(|| "Writing to claims file")?;
+    Ok(Some(id))
+}
+
+pub fn remove_claim(location: &Location, id: &str) -> Result<()> {
+    let contents: String = load_claims(location)?
+        .iter()
+        .filter(|claim| claim.id != id)
+        .map(|claim| {
+            format!(
+                "{}\t{}\t{}\n",
+                claim.time.format(TIME_FORMAT),
+                claim.id,
+                claim.name
+            )
+        })
+        .collect();
+    fs::write(location.claims_file(), contents).with_context(|| "Writing claims file")
+}
+
+/// Kept outside of post directory, so it can be written without locking the location.
+/// Replaces any previous submission for the post.
+pub fn submit(location: &Location, id: &str, transcript: &str) -> Result<()> {
+    let path = location.submission_file(id);
+    fs::create_dir_all(path.parent().expect("submission file should have parent"))
+        .with_context(|| "Creating submissions directory")?;
+    fs::write(&path, transcript).with_context(|| "Writing submission file")
+}
+
+/// Ids of posts with a submitted transcript, in order of id
+pub fn list_submissions(location: &Location) -> Result<Vec<String>> {
+    let dir = location.submissions_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = file::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
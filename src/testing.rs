@@ -0,0 +1,234 @@
+//! Fixtures for building fake locations in integration tests
+
+use crate::clipboard::ClipboardBackend;
+use crate::commands::SystemCommands;
+use crate::constants::*;
+use crate::location::{Location, Stage};
+use crate::ui::Prompter;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use anyhow::{Context as _, Result};
+use chrono::{Datelike as _, NaiveDate};
+use image::{Rgba, RgbaImage};
+
+/// Solid image with a similar aspect ratio to a real comic
+pub fn create_comic_image(date: NaiveDate) -> RgbaImage {
+    let (width, height) = if date.weekday() == chrono::Weekday::Sun {
+        (600, 400)
+    } else {
+        (600, 200)
+    };
+    RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]))
+}
+
+pub fn write_source_comic(location: &Location, date: NaiveDate) -> Result<PathBuf> {
+    let path = location.source_file(date);
+    create_comic_image(date)
+        .save(&path)
+        .with_context(|| "Saving source comic")?;
+    Ok(path)
+}
+
+/// Every date in range, inclusive
+pub fn write_source_archive(location: &Location, from: NaiveDate, to: NaiveDate) -> Result<()> {
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        write_source_comic(location, date)?;
+    }
+    Ok(())
+}
+
+/// Post with a date, title, and both images, but no transcript
+pub fn write_post(location: &Location, stage: Stage, id: &str, date: NaiveDate) -> Result<PathBuf> {
+    let path = location.stage_dir(stage).join(id);
+    fs::create_dir_all(&path).with_context(|| "Creating post directory")?;
+    fs::write(path.join(post_file::DATE), date.to_string()).with_context(|| "Writing date file")?;
+    fs::write(path.join(post_file::TITLE), "Test").with_context(|| "Writing title file")?;
+    let layout = location.layout();
+    for file_name in [&layout.initial, &layout.duplicate] {
+        create_comic_image(date)
+            .save(path.join(file_name))
+            .with_context(|| "Saving post image")?;
+    }
+    Ok(path)
+}
+
+pub fn write_transcript(location: &Location, id: &str, contents: &str) -> Result<()> {
+    let path = location.posts_dir().join(id).join(post_file::TRANSCRIPT);
+    fs::write(path, contents).with_context(|| "Writing transcript file")
+}
+
+/// Ephemeral location, whose whole directory is removed when dropped, even if the test panics
+pub struct TempLocation {
+    location: Location,
+}
+
+impl TempLocation {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            location: Location::create_ephemeral()?,
+        })
+    }
+}
+
+impl Deref for TempLocation {
+    type Target = Location;
+    fn deref(&self) -> &Self::Target {
+        &self.location
+    }
+}
+
+impl DerefMut for TempLocation {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.location
+    }
+}
+
+impl Drop for TempLocation {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.location.base_dir());
+    }
+}
+
+/// External program which would have been run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SystemCall {
+    SpawnImageViewer { name: String, paths: Vec<PathBuf> },
+    KillProcess { name: String },
+    SetupImageViewerWindow { name: String, paths: Vec<PathBuf> },
+    OpenEditor { path: PathBuf },
+    SpawnDetached { program: String, path: PathBuf },
+    ToggleUploadDestination,
+    UploadFile { path: PathBuf },
+}
+
+/// Shared with the `RecordingSystem` after it is moved into a `Location`
+pub type CallLog = Rc<RefCell<Vec<SystemCall>>>;
+
+/// Records calls instead of running anything.
+/// Editor can optionally overwrite the file it opens, as if the user had edited it.
+#[derive(Default)]
+pub struct RecordingSystem {
+    calls: CallLog,
+    editor_contents: Option<String>,
+}
+
+impl RecordingSystem {
+    pub fn with_editor_contents(mut self, contents: impl Into<String>) -> Self {
+        self.editor_contents = Some(contents.into());
+        self
+    }
+
+    pub fn calls(&self) -> CallLog {
+        Rc::clone(&self.calls)
+    }
+
+    fn record(&self, call: SystemCall) {
+        self.calls.borrow_mut().push(call);
+    }
+}
+
+impl SystemCommands for RecordingSystem {
+    fn spawn_image_viewer(
+        &self,
+        _program: &str,
+        paths: &[PathBuf],
+        name: &str,
+        _fullscreen: bool,
+        _slideshow_delay: Option<u64>,
+    ) -> Result<()> {
+        self.record(SystemCall::SpawnImageViewer {
+            name: name.to_string(),
+            paths: paths.to_vec(),
+        });
+        Ok(())
+    }
+    fn kill_process_name(&self, name: &str) -> Result<()> {
+        self.record(SystemCall::KillProcess {
+            name: name.to_string(),
+        });
+        Ok(())
+    }
+    fn setup_image_viewer_window(
+        &self,
+        _program: &str,
+        paths: &[PathBuf],
+        window_name: &str,
+    ) -> Result<()> {
+        self.record(SystemCall::SetupImageViewerWindow {
+            name: window_name.to_string(),
+            paths: paths.to_vec(),
+        });
+        Ok(())
+    }
+    fn open_editor(&self, _program: &str, path: &Path) -> Result<()> {
+        self.record(SystemCall::OpenEditor {
+            path: path.to_path_buf(),
+        });
+        if let Some(contents) = &self.editor_contents {
+            fs::write(path, contents).with_context(|| "Writing edited file")?;
+        }
+        Ok(())
+    }
+    fn spawn_detached(&self, program: &str, path: &Path) -> Result<()> {
+        self.record(SystemCall::SpawnDetached {
+            program: program.to_string(),
+            path: path.to_path_buf(),
+        });
+        Ok(())
+    }
+    fn toggle_upload_destination(&self) -> Result<()> {
+        self.record(SystemCall::ToggleUploadDestination);
+        Ok(())
+    }
+    fn upload_file(
+        &self,
+        _prompter: &dyn Prompter,
+        _clipboard: ClipboardBackend,
+        path: &Path,
+    ) -> Result<()> {
+        self.record(SystemCall::UploadFile {
+            path: path.to_path_buf(),
+        });
+        Ok(())
+    }
+    /// Nothing is opened, so cleanup tasks (which cannot use this system) must not kill anything
+    fn has_gui(&self) -> bool {
+        false
+    }
+}
+
+/// Answers prompts in order, recording each prompt. Panics if a prompt has no answer left.
+#[derive(Default)]
+pub struct ScriptedPrompter {
+    answers: RefCell<VecDeque<String>>,
+    prompts: Rc<RefCell<Vec<String>>>,
+}
+
+impl ScriptedPrompter {
+    pub fn new(answers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            answers: RefCell::new(answers.into_iter().map(Into::into).collect()),
+            prompts: Rc::default(),
+        }
+    }
+
+    pub fn prompts(&self) -> Rc<RefCell<Vec<String>>> {
+        Rc::clone(&self.prompts)
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn ask(&self, prompt: &str) -> String {
+        self.prompts.borrow_mut().push(prompt.to_string());
+        self.answers
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("no answer scripted for prompt `{}`", prompt))
+    }
+}
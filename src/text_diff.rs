@@ -0,0 +1,110 @@
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Line-based diff in unified format, with `context` unchanged lines around each change.
+/// Returns empty string if texts have the same lines.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_name: &str,
+    new_name: &str,
+    context: usize,
+) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut output = String::new();
+    writeln!(output, "--- {}\n+++ {}", old_name, new_name)
+        .expect("write to string should not fail");
+
+    // Group changes whose contexts would overlap into the same hunk
+    let mut group_start = 0;
+    for i in 1..=changes.len() {
+        if i < changes.len() && changes[i] - changes[i - 1] <= context * 2 {
+            continue;
+        }
+        let start = changes[group_start].saturating_sub(context);
+        let end = (changes[i - 1] + context + 1).min(ops.len());
+        write_hunk(&mut output, &ops, start, end);
+        group_start = i;
+    }
+
+    output
+}
+
+fn write_hunk(output: &mut String, ops: &[Op], start: usize, end: usize) {
+    let count_old = |ops: &[Op]| ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+    let count_new = |ops: &[Op]| ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+
+    let old_len = count_old(&ops[start..end]);
+    let new_len = count_new(&ops[start..end]);
+    // Empty ranges refer to the line before, by convention
+    let old_start = count_old(&ops[..start]) + usize::from(old_len > 0);
+    let new_start = count_new(&ops[..start]) + usize::from(new_len > 0);
+
+    writeln!(
+        output,
+        "@@ -{},{} +{},{} @@",
+        old_start, old_len, new_start, new_len
+    )
+    .expect("write to string should not fail");
+    for op in &ops[start..end] {
+        let (prefix, line) = match op {
+            Op::Equal(line) => (' ', line),
+            Op::Delete(line) => ('-', line),
+            Op::Insert(line) => ('+', line),
+        };
+        writeln!(output, "{}{}", prefix, line).expect("write to string should not fail");
+    }
+}
+
+/// Longest common subsequence, preferring deletions before insertions
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let (n, m) = (old.len(), new.len());
+
+    // `lengths[i][j]` is LCS length of `old[i..]` and `new[j..]`
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Equal(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            ops.push(Op::Delete(old[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(new[j]));
+            j += 1;
+        }
+    }
+    ops
+}
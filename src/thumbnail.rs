@@ -0,0 +1,126 @@
+use crate::commands;
+use crate::constants::*;
+use crate::file;
+use crate::location::Location;
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::hash::{DefaultHasher, Hasher as _};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+
+const THUMBNAIL_SIZE: u32 = 400;
+const INDEX_FILE: &str = "index";
+
+/// Thumbnails of post images, cached in the thumbnails directory.
+/// Invalidated when the content hash of the post image changes.
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    /// Post id -> hash of image the thumbnail was generated from
+    index: HashMap<String, u64>,
+}
+
+impl ThumbnailCache {
+    pub fn open(location: &Location) -> Result<Self> {
+        let dir = location.thumbnails_dir();
+        fs::create_dir_all(&dir).with_context(|| "Creating thumbnails directory")?;
+
+        let mut index = HashMap::new();
+        let index_path = dir.join(INDEX_FILE);
+        if index_path.exists() {
+            let contents =
+                fs::read_to_string(&index_path).with_context(|| "Reading thumbnail index")?;
+            for line in contents.lines() {
+                // Malformed lines are regenerated and overwritten
+                let Some((id, hash)) = line.rsplit_once(' ') else {
+                    continue;
+                };
+                let Ok(hash) = u64::from_str_radix(hash, 16) else {
+                    continue;
+                };
+                index.insert(id.to_string(), hash);
+            }
+        }
+
+        Ok(Self { dir, index })
+    }
+
+    /// Generates thumbnail if missing or outdated
+    pub fn get(&mut self, location: &Location, id: &str) -> Result<PathBuf> {
+        let image_path = location
+            .posts_dir()
+            .join(id)
+            .join(&location.layout().initial);
+        self.refresh(id, &image_path)?;
+        Ok(self.thumbnail_path(id))
+    }
+
+    /// Regenerates all missing or outdated thumbnails, sleeping for `throttle` after each one.
+    /// Returns the number of thumbnails generated.
+    pub fn refresh_all(&mut self, location: &Location, throttle: Duration) -> Result<usize> {
+        let mut generated_count = 0;
+        let entries =
+            file::sort_dir_entries(file::read_dir(location.posts_dir())?.flatten().collect());
+        for entry in entries {
+            let image_path = entry.path().join(&location.layout().initial);
+            if !image_path.exists() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            if self.refresh(&id, &image_path)? {
+                println!("Generated thumbnail for {}", id);
+                generated_count += 1;
+                commands::sleep(throttle.as_millis() as u64);
+            }
+        }
+        Ok(generated_count)
+    }
+
+    /// Returns `Ok(true)` if thumbnail was (re)generated
+    fn refresh(&mut self, id: &str, image_path: &Path) -> Result<bool> {
+        let hash = hash_file(image_path).with_context(|| "Hashing post image")?;
+        let thumbnail_path = self.thumbnail_path(id);
+        if self.index.get(id) == Some(&hash) && thumbnail_path.exists() {
+            return Ok(false);
+        }
+
+        image::open(image_path)
+            .with_context(|| "Opening post image")?
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .save(&thumbnail_path)
+            .with_context(|| "Saving thumbnail image")?;
+
+        self.index.insert(id.to_string(), hash);
+        // Saved after every thumbnail, so progress is kept if interrupted
+        self.save_index()?;
+        Ok(true)
+    }
+
+    fn thumbnail_path(&self, id: &str) -> PathBuf {
+        let mut path = self.dir.join(id);
+        path.set_extension(SOURCE_FORMAT);
+        path
+    }
+
+    fn save_index(&self) -> Result<()> {
+        let mut ids: Vec<_> = self.index.keys().collect();
+        ids.sort();
+        let mut contents = String::new();
+        for id in ids {
+            writeln!(contents, "{} {:016x}", id, self.index[id])
+                .expect("write to string should not fail");
+        }
+        fs::write(self.dir.join(INDEX_FILE), contents).with_context(|| "Writing thumbnail index")
+    }
+}
+
+/// Not stable across Rust versions, which only causes thumbnails to be regenerated
+fn hash_file(path: &Path) -> Result<u64> {
+    let bytes = fs::read(path)?;
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&bytes);
+    Ok(hasher.finish())
+}
@@ -0,0 +1,181 @@
+use crate::location::Location;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use chrono::Local;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static START: OnceLock<Instant> = OnceLock::new();
+static TOTALS: Mutex<BTreeMap<Phase, (Duration, usize)>> = Mutex::new(BTreeMap::new());
+
+/// Instrumented part of a command. Phases should not be nested, so they do not overlap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Reading source or post directories
+    Scan,
+    /// Reading and decoding images
+    Decode,
+    /// Generating post images from comics
+    Convert,
+    /// Encoding, optimizing, and saving images
+    Encode,
+    /// Running other programs
+    External,
+}
+
+/// Timings of one logged run of a command
+#[derive(Debug)]
+pub struct Run {
+    pub command: String,
+    pub total: Duration,
+    pub phases: BTreeMap<Phase, Duration>,
+}
+
+impl Phase {
+    pub const ALL: [Self; 5] = [
+        Self::Scan,
+        Self::Decode,
+        Self::Convert,
+        Self::Encode,
+        Self::External,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Scan => "scan",
+            Self::Decode => "decode",
+            Self::Convert => "convert",
+            Self::Encode => "encode",
+            Self::External => "external",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phase| phase.name() == name)
+    }
+}
+
+pub fn enable() {
+    START.get_or_init(Instant::now);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Adds duration of `f` to `phase`, if timings are enabled
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    let mut totals = TOTALS.lock().expect("timings should not be poisoned");
+    let (duration, count) = totals.entry(phase).or_default();
+    *duration += elapsed;
+    *count += 1;
+    result
+}
+
+/// Prints breakdown of phases, and appends it to timings log. Does nothing if timings are not
+/// enabled.
+pub fn finish(location: &Location, command: &str) -> Result<()> {
+    let Some(start) = START.get() else {
+        return Ok(());
+    };
+    let total = start.elapsed();
+    let totals = TOTALS
+        .lock()
+        .expect("timings should not be poisoned")
+        .clone();
+
+    println!("Timings:");
+    for (phase, (duration, count)) in &totals {
+        println!(
+            "\t{:<8}\t{:>9.1} ms\t{:>5.1}%\t({} call(s))",
+            phase.name(),
+            as_millis(*duration),
+            percent(*duration, total),
+            count
+        );
+    }
+    let measured: Duration = totals.values().map(|(duration, _)| *duration).sum();
+    let other = total.saturating_sub(measured);
+    println!(
+        "\t{:<8}\t{:>9.1} ms\t{:>5.1}%",
+        "other",
+        as_millis(other),
+        percent(other, total)
+    );
+    println!("\t{:<8}\t{:>9.1} ms", "total", as_millis(total));
+
+    let mut line = format!(
+        "{} {} total={:.1}",
+        Local::now().format("%Y-%m-%dT%H:%M:%S"),
+        command,
+        as_millis(total)
+    );
+    for (phase, (duration, _)) in &totals {
+        line += &format!(" {}={:.1}", phase.name(), as_millis(*duration));
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(location.timings_file())
+        .with_context(|| "Opening timings log")?;
+    writeln!(file, "{}", line).with_context(|| "Writing to timings log")?;
+    Ok(())
+}
+
+/// Line of timings log, as `<time> <command> total=<ms> [<phase>=<ms>...]`. Skips malformed
+/// lines.
+pub fn read_log(location: &Location) -> Result<Vec<Run>> {
+    let path = location.timings_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading timings log")?;
+    let runs = contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let command = fields.next()?.to_string();
+            let (key, total) = fields.next()?.split_once('=')?;
+            if key != "total" {
+                return None;
+            }
+            let total = from_millis(total)?;
+            let mut phases = BTreeMap::new();
+            for field in fields {
+                let (name, duration) = field.split_once('=')?;
+                phases.insert(Phase::from_name(name)?, from_millis(duration)?);
+            }
+            Some(Run {
+                command,
+                total,
+                phases,
+            })
+        })
+        .collect();
+    Ok(runs)
+}
+
+pub fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+pub fn percent(part: Duration, total: Duration) -> f64 {
+    if total.is_zero() {
+        return 0.0;
+    }
+    part.as_secs_f64() / total.as_secs_f64() * 100.0
+}
+
+fn from_millis(millis: &str) -> Option<Duration> {
+    let millis: f64 = millis.parse().ok()?;
+    Duration::try_from_secs_f64(millis / 1000.0).ok()
+}
@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+
+use chrono::NaiveDate;
+
+/// Separates panels in a transcript file
+const PANEL_SEPARATOR: &str = "---";
+/// Prefix of lines inserted by `transcribe --draft`, which should be edited by the translator
+pub const MACHINE_TRANSLATION_MARKER: &str = "[MT] ";
+/// Prefix of lines which are notes for the translator, such as recognized English text. Unlike
+/// `#`, it cannot be the start of dialogue.
+pub const NOTE_MARKER: &str = "[#] ";
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum TranscriptFormat {
+    /// Single paragraph of prose, for image descriptions
+    AltText,
+    Markdown,
+    Bbcode,
+    Plain,
+}
+
+/// Lines of each panel, without blank lines
+#[derive(Debug)]
+pub struct Transcript {
+    pub panels: Vec<Vec<String>>,
+}
+
+impl Transcript {
+    /// Panels are separated by `---` lines, and blank lines and notes are ignored
+    pub fn parse(contents: &str) -> Self {
+        let mut panels = vec![Vec::new()];
+        for line in contents.lines() {
+            let line = line.trim();
+            if line == PANEL_SEPARATOR {
+                panels.push(Vec::new());
+            } else if !line.is_empty() && !line.starts_with(NOTE_MARKER.trim_end()) {
+                panels
+                    .last_mut()
+                    .expect("panels is never empty")
+                    .push(line.to_string());
+            }
+        }
+        Self { panels }
+    }
+}
+
+/// Removes note lines, keeping everything else as-is
+pub fn strip_notes(contents: &str) -> String {
+    let mut output = String::new();
+    for line in contents.lines() {
+        if !line.trim_start().starts_with(NOTE_MARKER.trim_end()) {
+            output += line;
+            output += "\n";
+        }
+    }
+    output
+}
+
+impl TranscriptFormat {
+    pub fn render(&self, title: &str, date: NaiveDate, transcript: &Transcript) -> String {
+        match self {
+            Self::AltText => render_alt_text(title, date, transcript),
+            Self::Markdown => render_markdown(title, date, transcript),
+            Self::Bbcode => render_bbcode(title, date, transcript),
+            Self::Plain => render_plain(title, date, transcript),
+        }
+    }
+}
+
+fn render_alt_text(title: &str, date: NaiveDate, transcript: &Transcript) -> String {
+    let mut output = format!("{} ({}).", title, date);
+    for (i, panel) in transcript.panels.iter().enumerate() {
+        write!(output, " Panel {}:", i + 1).expect("write to string should not fail");
+        if panel.is_empty() {
+            output += " (no text).";
+            continue;
+        }
+        for line in panel {
+            output += " ";
+            output += line;
+            if !line.ends_with(['.', '!', '?', '…']) {
+                output += ".";
+            }
+        }
+    }
+    output += "\n";
+    output
+}
+
+fn render_markdown(title: &str, date: NaiveDate, transcript: &Transcript) -> String {
+    let mut output = String::new();
+    writeln!(output, "**{}** ({})", title, date).expect("write to string should not fail");
+    for panel in &transcript.panels {
+        writeln!(output).expect("write to string should not fail");
+        if panel.is_empty() {
+            writeln!(output, "> *...*").expect("write to string should not fail");
+            continue;
+        }
+        for line in panel {
+            // Two trailing spaces for a line break within the quote
+            writeln!(output, "> {}  ", line).expect("write to string should not fail");
+        }
+    }
+    output
+}
+
+fn render_bbcode(title: &str, date: NaiveDate, transcript: &Transcript) -> String {
+    let mut output = String::new();
+    writeln!(output, "[b]{}[/b] ({})", title, date).expect("write to string should not fail");
+    for panel in &transcript.panels {
+        writeln!(output, "[quote]").expect("write to string should not fail");
+        for line in panel {
+            writeln!(output, "{}", line).expect("write to string should not fail");
+        }
+        writeln!(output, "[/quote]").expect("write to string should not fail");
+    }
+    output
+}
+
+fn render_plain(title: &str, date: NaiveDate, transcript: &Transcript) -> String {
+    let mut output = String::new();
+    writeln!(output, "{}", title).expect("write to string should not fail");
+    writeln!(output, "{}", date).expect("write to string should not fail");
+    for panel in &transcript.panels {
+        writeln!(output).expect("write to string should not fail");
+        for line in panel {
+            writeln!(output, "{}", line).expect("write to string should not fail");
+        }
+    }
+    output
+}
@@ -0,0 +1,129 @@
+use crate::file;
+use crate::location::{Location, Stage};
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context as _, Result};
+use chrono::{Local, NaiveDateTime};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Post directory in trash, named `<id>.<stage>.<timestamp>`
+#[derive(Debug)]
+pub struct TrashEntry {
+    pub id: String,
+    pub stage: Stage,
+    pub deleted_at: NaiveDateTime,
+    pub path: PathBuf,
+}
+
+/// Moves post directory into trash. Returns name of trash entry.
+pub fn move_to_trash(location: &Location, stage: Stage, id: &str) -> Result<String> {
+    let post_path = location.stage_dir(stage).join(id);
+    if !post_path.is_dir() {
+        bail!("No post with that id in `{}` directory", stage.dir_name());
+    }
+
+    let trash_dir = location.trash_dir();
+    fs::create_dir_all(&trash_dir).with_context(|| "Creating trash directory")?;
+
+    let timestamp = Local::now().naive_local().format(TIMESTAMP_FORMAT);
+    let name = format!("{}.{}.{}", id, stage.dir_name(), timestamp);
+    let trash_path = trash_dir.join(&name);
+    if trash_path.exists() {
+        bail!("Post was already moved to trash at this time");
+    }
+
+    file::rename_or_copy(&post_path, &trash_path)
+        .with_context(|| "Moving post to trash directory")?;
+    Ok(name)
+}
+
+/// Sorted by deletion time, oldest first. Skips entries with malformed names.
+pub fn list_entries(location: &Location) -> Result<Vec<TrashEntry>> {
+    let trash_dir = location.trash_dir();
+    if !trash_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in file::read_dir(&trash_dir)? {
+        let path = entry?.path();
+        let Some(entry) = TrashEntry::from_path(path) else {
+            continue;
+        };
+        entries.push(entry);
+    }
+    entries.sort_by_key(|entry| entry.deleted_at);
+    Ok(entries)
+}
+
+/// `name` may be the full trash entry name, or a post id, in which case the most recently deleted
+/// entry is restored
+pub fn restore(location: &Location, name: &str) -> Result<TrashEntry> {
+    let entry = list_entries(location)?
+        .into_iter()
+        .rev()
+        .find(|entry| entry.file_name() == name || entry.id == name)
+        .with_context(|| "No post in trash with that name or id")?;
+
+    let restored_path = location.stage_dir(entry.stage).join(&entry.id);
+    if restored_path.exists() {
+        bail!(
+            "A post with that id already exists in `{}` directory",
+            entry.stage.dir_name()
+        );
+    }
+    file::rename_or_copy(&entry.path, &restored_path)
+        .with_context(|| "Moving post out of trash")?;
+    Ok(entry)
+}
+
+/// Returns number of entries removed
+pub fn empty(location: &Location) -> Result<usize> {
+    let entries = list_entries(location)?;
+    for entry in &entries {
+        fs::remove_dir_all(&entry.path)
+            .with_context(|| format!("Removing trash entry {:?}", entry.path))?;
+    }
+    Ok(entries.len())
+}
+
+impl TrashEntry {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        let mut parts = name.rsplitn(3, '.');
+        let deleted_at = NaiveDateTime::parse_from_str(parts.next()?, TIMESTAMP_FORMAT).ok()?;
+        let stage = Stage::from_dir_name(parts.next()?)?;
+        let id = parts.next()?.to_string();
+        Some(Self {
+            id,
+            stage,
+            deleted_at,
+            path,
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Ends with the entry name, which `trash restore` accepts
+impl fmt::Display for TrashEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}",
+            self.deleted_at.format("%Y-%m-%d %H:%M:%S"),
+            self.stage.dir_name(),
+            self.id,
+            self.file_name(),
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config::PromptsConfig;
+use crate::error::Error;
+use crate::location::Location;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Answer of a prompt, set per prompt with `prompts` in config
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PromptPolicy {
+    #[default]
+    Ask,
+    Yes,
+    No,
+}
+
+/// Prompts which can be answered by config
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptId {
+    ResumeTranscript,
+    SaveTranscript,
+    MoveOldPost,
+    EmptyTrash,
+    AcceptTranscript,
+    FinishRevisedPost,
+}
+
+impl PromptId {
+    fn policy(self, config: &PromptsConfig) -> PromptPolicy {
+        match self {
+            Self::ResumeTranscript => config.resume_transcript,
+            Self::SaveTranscript => config.save_transcript,
+            Self::MoveOldPost => config.move_old_post,
+            Self::EmptyTrash => config.empty_trash,
+            Self::AcceptTranscript => config.accept_transcript,
+            Self::FinishRevisedPost => config.finish_revised_post,
+        }
+    }
+}
+
+/// Reads answers to prompts, so they can be replaced (eg. with scripted answers in tests)
+pub trait Prompter {
+    /// Returns trimmed line of input
+    fn ask(&self, prompt: &str) -> String;
+
+    /// Returns `true` only if the answer is `y` or `yes` (case-insensitive)
+    fn ask_yes_no(&self, prompt: &str) -> bool {
+        let answer = self.ask(&format!("{} [y/N]", prompt));
+        matches!(answer.to_lowercase().as_str(), "y" | "yes")
+    }
+
+    /// Like `ask_yes_no`, but an empty answer is `true`
+    fn confirm(&self, prompt: &str) -> bool {
+        let answer = self.ask(&format!("{} [Y/n]", prompt));
+        !matches!(answer.to_lowercase().as_str(), "n" | "no")
+    }
+}
+
+/// Reads from stdin
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn ask(&self, prompt: &str) -> String {
+        crate::ask(prompt)
+    }
+}
+
+/// Only asks if policy of prompt is `ask`. Defaults to no.
+///
+/// Fails if answer is no and `prompts.abort_on_no` is set, so command never continues without
+/// the step which was declined.
+pub fn ask_yes_no(location: &Location, id: PromptId, prompt: &str) -> Result<bool> {
+    let answer = match id.policy(&location.config().prompts) {
+        PromptPolicy::Ask => location.prompter().ask_yes_no(prompt),
+        policy => answer_from_config(prompt, policy),
+    };
+    check_declined(location, prompt, answer)
+}
+
+/// Like `ask_yes_no`, but defaults to yes
+pub fn confirm(location: &Location, id: PromptId, prompt: &str) -> Result<bool> {
+    let answer = match id.policy(&location.config().prompts) {
+        PromptPolicy::Ask => location.prompter().confirm(prompt),
+        policy => answer_from_config(prompt, policy),
+    };
+    check_declined(location, prompt, answer)
+}
+
+fn check_declined(location: &Location, prompt: &str, answer: bool) -> Result<bool> {
+    if !answer && location.config().prompts.abort_on_no {
+        bail!(Error::declined(format!("Declined prompt: {}", prompt)));
+    }
+    Ok(answer)
+}
+
+fn answer_from_config(prompt: &str, policy: PromptPolicy) -> bool {
+    let answer = policy == PromptPolicy::Yes;
+    println!(
+        "{} {} (from config)",
+        prompt,
+        if answer { "yes" } else { "no" }
+    );
+    answer
+}
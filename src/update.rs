@@ -0,0 +1,175 @@
+use crate::commands;
+use crate::error::Error;
+use crate::output;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context as _, Result};
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/dxrcy/garfutils/releases/latest";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Lines of release notes to print
+const CHANGELOG_LINES: usize = 12;
+
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    tag_name: String,
+    #[serde(default)]
+    body: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Latest release, if it is newer than this build
+pub fn check() -> Result<Option<Release>> {
+    let response = commands::http_get(LATEST_RELEASE_URL, &["Accept: application/vnd.github+json"])
+        .with_context(|| "Requesting latest release")?;
+    let release: Release =
+        serde_json::from_slice(&response).with_context(|| "Parsing release response")?;
+    let Some(latest) = parse_version(&release.tag_name) else {
+        bail!(Error::external_tool(format!(
+            "Latest release has malformed version `{}`",
+            release.tag_name
+        )));
+    };
+    let current = parse_version(CURRENT_VERSION).expect("package version should be valid");
+    Ok((latest > current).then_some(release))
+}
+
+/// Prints whether a newer release exists, with the start of its release notes
+pub fn print_check() -> Result<Option<Release>> {
+    println!("Current version: {}", CURRENT_VERSION);
+    let Some(release) = check()? else {
+        println!("Already up to date.");
+        return Ok(None);
+    };
+    println!("New version available: {}", output::id(&release.tag_name));
+    let mut lines = release.body.lines().filter(|line| !line.trim().is_empty());
+    for line in lines.by_ref().take(CHANGELOG_LINES) {
+        println!("\t{}", line.trim_end());
+    }
+    let remaining = lines.count();
+    if remaining > 0 {
+        println!("\t(... {} more line(s))", remaining);
+    }
+    Ok(Some(release))
+}
+
+/// Replaces the running executable with the asset of release for this platform. The asset must
+/// match the checksum published with it, as `<asset>.sha256` (in the format of `sha256sum`).
+pub fn install(release: &Release) -> Result<()> {
+    let name = asset_name();
+    let asset = find_asset(release, &name).with_context(|| {
+        Error::external_tool(format!(
+            "Release has no binary for {}-{} (expected `{}`)",
+            env::consts::OS,
+            env::consts::ARCH,
+            name
+        ))
+    })?;
+    let checksum_name = format!("{}.sha256", name);
+    let checksum_asset = find_asset(release, &checksum_name)
+        .with_context(|| Error::external_tool(format!("Release has no checksum for `{}`", name)))?;
+
+    let checksum = commands::http_get(&checksum_asset.browser_download_url, &[])
+        .with_context(|| format!("Downloading `{}`", checksum_name))?;
+    let Some(expected) = parse_checksum(&checksum) else {
+        bail!(Error::external_tool(format!(
+            "Malformed checksum in `{}`",
+            checksum_name
+        )));
+    };
+    let bytes = commands::http_get(&asset.browser_download_url, &[])
+        .with_context(|| format!("Downloading `{}`", asset.name))?;
+    if format!("{:x}", Sha256::digest(&bytes)) != expected {
+        bail!(Error::validation(format!(
+            "Downloaded `{}` does not match its checksum",
+            asset.name
+        )));
+    }
+
+    let current_exe = env::current_exe().with_context(|| "Finding current executable")?;
+    // Same directory as executable, so it can be renamed over it
+    let download_path = current_exe.with_extension("download");
+    fs::write(&download_path, bytes).with_context(|| "Writing downloaded executable")?;
+    set_executable(&download_path).with_context(|| "Setting permissions of new executable")?;
+    replace_executable(&download_path, &current_exe)
+        .with_context(|| "Replacing current executable")?;
+    output::success(format!("Updated to {}", release.tag_name));
+    Ok(())
+}
+
+/// Eg. `garfutils-linux-x86_64`, or `garfutils-windows-x86_64.exe`
+fn asset_name() -> String {
+    format!(
+        "garfutils-{}-{}{}",
+        env::consts::OS,
+        env::consts::ARCH,
+        env::consts::EXE_SUFFIX
+    )
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Option<&'a Asset> {
+    release.assets.iter().find(|asset| asset.name == name)
+}
+
+/// First field of the file, which may be followed by the file name
+fn parse_checksum(contents: &[u8]) -> Option<String> {
+    let checksum = std::str::from_utf8(contents)
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .to_lowercase();
+    let is_valid = checksum.len() == 64 && checksum.chars().all(|char| char.is_ascii_hexdigit());
+    is_valid.then_some(checksum)
+}
+
+/// Accepts an optional `v` prefix, and ignores any pre-release or build suffix
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn replace_executable(new_path: &Path, current_path: &Path) -> std::io::Result<()> {
+    fs::rename(new_path, current_path)
+}
+
+/// Running executable cannot be replaced on Windows, but it can be moved aside. It is removed by
+/// the next update.
+#[cfg(windows)]
+fn replace_executable(new_path: &Path, current_path: &Path) -> std::io::Result<()> {
+    let old_path = current_path.with_extension("old");
+    if old_path.exists() {
+        fs::remove_file(&old_path)?;
+    }
+    fs::rename(current_path, &old_path)?;
+    if let Err(error) = fs::rename(new_path, current_path) {
+        let _ = fs::rename(&old_path, current_path);
+        return Err(error);
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use crate::location::Location;
+
+use std::fs;
+use std::io::Write as _;
+
+use anyhow::{Context as _, Result};
+use chrono::{Local, NaiveDateTime};
+
+/// Target recorded when images are pasted, instead of published to a configured target
+pub const PASTE_TARGET: &str = "paste";
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Line of uploads log, as `<time>\t<id>\t<target>`, then `\t<language>` for a variant
+#[derive(Debug)]
+pub struct UploadRecord {
+    pub time: NaiveDateTime,
+    pub id: String,
+    pub target: String,
+    /// `None` for the primary translation
+    pub language: Option<String>,
+}
+
+/// Sorted by time of upload. Skips malformed lines.
+pub fn load(location: &Location) -> Result<Vec<UploadRecord>> {
+    let path = location.uploads_file();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| "Reading uploads log")?;
+    let mut records: Vec<UploadRecord> = contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let time = NaiveDateTime::parse_from_str(parts.next()?, TIME_FORMAT).ok()?;
+            let id = parts.next()?.to_string();
+            let target = parts.next()?.to_string();
+            let language = parts.next().map(str::to_string);
+            Some(UploadRecord {
+                time,
+                id,
+                target,
+                language,
+            })
+        })
+        .collect();
+    records.sort_by_key(|record| record.time);
+    Ok(records)
+}
+
+pub fn append(location: &Location, id: &str, target: &str, language: Option<&str>) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(location.uploads_file())
+        .with_context(|| "Opening uploads log")?;
+    let mut line = format!(
+        "{}\t{}\t{}",
+        Local::now().naive_local().format(TIME_FORMAT),
+        id,
+        target
+    );
+    if let Some(language) = language {
+        line = line + "\t" + language;
+    }
+    writeln!(file, "{}", line).with_context(|| "Writing to uploads log")
+}
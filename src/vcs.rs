@@ -0,0 +1,128 @@
+use crate::constants::*;
+use crate::error::Error;
+use crate::location::{Location, Stage};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use anyhow::{bail, Context as _, Result};
+
+/// Whether changes should be committed, as configured or detected from location. Location must
+/// be the root of the repository, so a location nested in another repository (eg. dotfiles) is
+/// not committed to.
+pub fn is_enabled(location: &Location) -> bool {
+    match location.config().vcs.git {
+        Some(enabled) => enabled,
+        None => is_repository(location.base_dir()),
+    }
+}
+
+/// Commits any changes to post directories, with message `<action> <id>:<date>`.
+/// Date is read from the post directory, and omitted if it cannot be read.
+/// Does nothing if git integration is disabled, or nothing changed.
+/// Only post directories are committed, even if other changes were already staged.
+pub fn commit_post(location: &Location, action: &str, id: &str, post_path: &Path) -> Result<()> {
+    if !is_enabled(location) {
+        return Ok(());
+    }
+
+    let mut paths: Vec<PathBuf> = Stage::ALL
+        .into_iter()
+        .map(|stage| location.stage_dir(stage))
+        .collect();
+    paths.push(location.trash_dir());
+    paths.retain(|path| path.exists());
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let mut command = git(location);
+    command.args(["add", "--all", "--"]).args(&paths);
+    run(command).with_context(|| "Staging changes")?;
+
+    // Changed files, rather than directories, since a pathspec which matches no tracked file
+    // (eg. empty trash directory) would fail the commit
+    let mut command = git(location);
+    command
+        .args(["diff", "--cached", "--name-only", "--relative", "-z", "--"])
+        .args(&paths);
+    let output = run(command).with_context(|| "Listing staged changes")?;
+    let changed: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let message = match fs::read_to_string(post_path.join(post_file::DATE)) {
+        Ok(date) => format!("{} {}:{}", action, id, date.trim()),
+        Err(_) => format!("{} {}", action, id),
+    };
+    let mut command = git(location);
+    command
+        .args(["commit", "--quiet", "--message", &message, "--"])
+        .args(&changed);
+    run(command).with_context(|| "Committing changes")?;
+    Ok(())
+}
+
+/// Prints commits which changed post, in any stage
+pub fn print_log(location: &Location, id: &str) -> Result<()> {
+    if !is_enabled(location) {
+        bail!(Error::usage("Location is not a git repository"));
+    }
+    let mut command = git(location);
+    command
+        .args(["log", "--date=short", "--format=%h %ad %s", "--"])
+        .args(Stage::ALL.map(|stage| location.stage_dir(stage).join(id)))
+        .arg(location.trash_dir().join(id));
+    let output = run(command).with_context(|| "Reading commit history")?;
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    Ok(())
+}
+
+/// Whether path is the top-level directory of a work tree
+fn is_repository(path: &Path) -> bool {
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--show-toplevel"])
+        .stderr(Stdio::null())
+        .output()
+    else {
+        return false;
+    };
+    if !output.status.success() {
+        return false;
+    }
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim_end());
+    // Either may be given through a symlink
+    match (fs::canonicalize(toplevel), fs::canonicalize(path)) {
+        (Ok(toplevel), Ok(path)) => toplevel == path,
+        _ => false,
+    }
+}
+
+fn git(location: &Location) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(location.base_dir());
+    command
+}
+
+fn output(mut command: Command) -> Result<Output> {
+    command
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| Error::external_tool("Spawning `git`"))
+}
+
+fn run(command: Command) -> Result<Output> {
+    let output = output(command)?;
+    if !output.status.success() {
+        bail!(Error::external_tool("`git` did not exit successfully"));
+    }
+    Ok(output)
+}
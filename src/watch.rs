@@ -0,0 +1,66 @@
+use crate::config::Variant;
+use crate::location::Location;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use ::notify::{EventKind, RecursiveMode, Watcher as _};
+use anyhow::{Context as _, Result};
+
+/// Calls `on_change` with post id and variant, whenever an SVG file of a completed post is created
+/// or modified. Runs until watcher fails.
+pub fn watch_svg_files(
+    location: &Location,
+    mut on_change: impl FnMut(&str, &Variant),
+) -> Result<()> {
+    let posts_dir = location.posts_dir();
+    let variants = location.layout().all_variants();
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        ::notify::recommended_watcher(sender).with_context(|| "Creating file watcher")?;
+    watcher
+        .watch(&posts_dir, RecursiveMode::Recursive)
+        .with_context(|| "Watching posts directory")?;
+
+    // Editors may write a file several times when saving
+    let mut last_modified: HashMap<PathBuf, SystemTime> = HashMap::new();
+    for event in receiver {
+        let event = event.with_context(|| "Receiving file event")?;
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            continue;
+        }
+        for path in event.paths {
+            let Some((id, variant)) = match_svg_file(&posts_dir, &variants, &path) else {
+                continue;
+            };
+            let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if last_modified.get(&path) == Some(&modified) {
+                continue;
+            }
+            last_modified.insert(path.clone(), modified);
+            on_change(&id, variant);
+        }
+    }
+    Ok(())
+}
+
+/// Only files directly inside a post directory
+fn match_svg_file<'a>(
+    posts_dir: &Path,
+    variants: &'a [Variant],
+    path: &Path,
+) -> Option<(String, &'a Variant)> {
+    let post_path = path.parent()?;
+    if post_path.parent()? != posts_dir {
+        return None;
+    }
+    let file_name = path.file_name()?.to_str()?;
+    let variant = variants.iter().find(|variant| variant.svg == file_name)?;
+    let id = post_path.file_name()?.to_string_lossy().to_string();
+    Some((id, variant))
+}
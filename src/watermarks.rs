@@ -0,0 +1,92 @@
+use crate::location::Location;
+use crate::random;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context as _, Result};
+use chrono::{Datelike as _, NaiveDate, Weekday};
+use rand::Rng as _;
+
+const CONTEXT_PREFIX: &str = "watermarks.";
+const SPECIAL_CONTEXT: &str = "special";
+
+/// Attributes of a post which select its list of watermarks
+#[derive(Clone, Copy, Debug)]
+pub struct WatermarkContext {
+    pub date: NaiveDate,
+    pub special: bool,
+}
+
+/// Default `watermarks` list, and lists for each context, such as `watermarks.sunday` or
+/// `watermarks.special`
+#[derive(Debug)]
+pub struct WatermarkSet {
+    default: Vec<String>,
+    contexts: HashMap<String, Vec<String>>,
+}
+
+impl WatermarkSet {
+    pub fn load(location: &Location) -> Result<Self> {
+        let default = read_list(&location.watermarks_file())?;
+        let mut contexts = HashMap::new();
+        for entry in fs::read_dir(location.base_dir()).with_context(|| "Reading location")? {
+            let entry = entry.with_context(|| "Reading location entry")?;
+            let file_name = entry.file_name();
+            let Some(context) = file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix(CONTEXT_PREFIX))
+            else {
+                continue;
+            };
+            contexts.insert(context.to_string(), read_list(&entry.path())?);
+        }
+        Ok(Self { default, contexts })
+    }
+
+    /// List of `special` context for special posts, then of weekday (eg. `sunday`), then default
+    /// list. Empty lists are skipped.
+    pub fn list(&self, context: WatermarkContext) -> &[String] {
+        let special = context.special.then_some(SPECIAL_CONTEXT);
+        let weekday = weekday_name(context.date.weekday());
+        special
+            .into_iter()
+            .chain([weekday])
+            .filter_map(|name| self.contexts.get(name))
+            .find(|list| !list.is_empty())
+            .unwrap_or(&self.default)
+    }
+
+    pub fn choose_random(&self, context: WatermarkContext) -> Result<String> {
+        let list = self.list(context);
+        if list.is_empty() {
+            bail!("Watermarks file is empty");
+        }
+        let index = random::with_rng(|rng| rng.gen_range(0..list.len()));
+        Ok(list[index].clone())
+    }
+}
+
+/// Blank lines are ignored
+fn read_list(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Reading watermarks file {:?}", path.file_name()))?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
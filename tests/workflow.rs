@@ -0,0 +1,711 @@
+use std::fs;
+
+use chrono::NaiveDate;
+
+use garfutils::testing::{RecordingSystem, ScriptedPrompter, SystemCall, TempLocation};
+use garfutils::{actions, names, testing, ErrorKind, PipelineStage, PromptPolicy, Stage};
+
+fn date(string: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(string, "%Y-%m-%d").unwrap()
+}
+
+fn error_kind(error: &anyhow::Error) -> Option<ErrorKind> {
+    error
+        .downcast_ref::<garfutils::Error>()
+        .map(|error| error.kind())
+}
+
+fn setup() -> TempLocation {
+    garfutils::init_rng();
+    TempLocation::new().unwrap()
+}
+
+#[test]
+fn make_creates_generated_post() {
+    let location = setup();
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+
+    actions::make(
+        &location,
+        date("2004-07-11"),
+        "abcd:2004-07-11",
+        false,
+        None,
+    )
+    .unwrap();
+
+    let post_path = location.generated_dir().join("abcd:2004-07-11");
+    assert!(post_path.join("esperanto.png").is_file());
+    assert!(post_path.join("english.png").is_file());
+    assert_eq!(
+        fs::read_to_string(post_path.join("date")).unwrap(),
+        "2004-07-11"
+    );
+}
+
+#[test]
+fn make_refuses_missing_comic() {
+    let location = setup();
+
+    let error = actions::make(
+        &location,
+        date("2004-07-11"),
+        "abcd:2004-07-11",
+        false,
+        None,
+    )
+    .unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Usage));
+}
+
+#[test]
+fn make_refuses_date_of_generated_post() {
+    let mut location = setup();
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+    location.set_prompter(ScriptedPrompter::new(["c"]));
+
+    let result = actions::make(
+        &location,
+        date("2004-07-11"),
+        "efgh:2004-07-11",
+        false,
+        None,
+    );
+
+    assert!(result.is_err());
+    assert!(!location.generated_dir().join("efgh:2004-07-11").exists());
+}
+
+#[test]
+fn make_replaces_generated_post_by_moving_it_to_trash() {
+    let mut location = setup();
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+    location.set_prompter(ScriptedPrompter::new(["r"]));
+
+    actions::make(
+        &location,
+        date("2004-07-11"),
+        "efgh:2004-07-11",
+        false,
+        None,
+    )
+    .unwrap();
+
+    assert!(location.generated_dir().join("efgh:2004-07-11").is_dir());
+    assert!(!location.generated_dir().join("abcd:2004-07-11").exists());
+    assert_eq!(fs::read_dir(location.trash_dir()).unwrap().count(), 1);
+}
+
+#[test]
+fn make_between_skips_existing_posts() {
+    let location = setup();
+    testing::write_source_archive(&location, date("2004-07-01"), date("2004-07-05")).unwrap();
+    testing::write_post(&location, Stage::Completed, "0", date("2004-07-03")).unwrap();
+
+    actions::make_between(&location, "2004-07-01..2004-07-05".parse().unwrap()).unwrap();
+
+    let generated = fs::read_dir(location.generated_dir()).unwrap().count();
+    assert_eq!(generated, 4);
+}
+
+#[test]
+fn make_between_reads_migrated_source() {
+    let location = setup();
+    testing::write_source_archive(&location, date("2004-12-30"), date("2005-01-02")).unwrap();
+
+    actions::migrate_source(&location).unwrap();
+    actions::make_between(&location, "2004-12-30..2005-01-02".parse().unwrap()).unwrap();
+
+    assert!(location.source_dir().join("2005/2005-01-01.png").is_file());
+    let generated = fs::read_dir(location.generated_dir()).unwrap().count();
+    assert_eq!(generated, 4);
+}
+
+#[test]
+fn sample_entry_is_uniform() {
+    const SAMPLES: usize = 4000;
+    let location = setup();
+    testing::write_source_archive(&location, date("2004-07-01"), date("2004-07-04")).unwrap();
+    actions::migrate_source(&location).unwrap();
+    testing::write_source_comic(&location, date("2005-07-01")).unwrap();
+
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..SAMPLES {
+        let entry = garfutils::sample_entry(location.source_dir(), |_| true)
+            .unwrap()
+            .unwrap();
+        *counts.entry(entry.file_name()).or_insert(0) += 1;
+    }
+
+    // Expected 800 each, with standard deviation of about 25
+    assert_eq!(counts.len(), 5);
+    for count in counts.values() {
+        assert!((650..950).contains(count), "count {} is not uniform", count);
+    }
+}
+
+#[test]
+fn sample_entry_respects_predicate() {
+    let location = setup();
+    testing::write_source_archive(&location, date("2004-07-01"), date("2004-07-04")).unwrap();
+
+    for _ in 0..100 {
+        let entry = garfutils::sample_entry(location.source_dir(), |entry| {
+            entry.file_name() != "2004-07-02.png"
+        })
+        .unwrap()
+        .unwrap();
+        assert_ne!(entry.file_name(), "2004-07-02.png");
+    }
+    let none = garfutils::sample_entry(location.source_dir(), |_| false).unwrap();
+    assert!(none.is_none());
+}
+
+#[test]
+fn finish_moves_post_with_next_id() {
+    let location = setup();
+    testing::write_post(&location, Stage::Completed, "0", date("2004-07-10")).unwrap();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+
+    actions::finish(&location, "abcd:2004-07-11", None).unwrap();
+
+    assert!(!location.generated_dir().join("abcd:2004-07-11").exists());
+    assert!(location.posts_dir().join("1").is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn finish_refuses_duplicate_linked_to_exported_image() {
+    let location = setup();
+    let post_path = testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+    let layout = location.layout();
+    fs::remove_file(post_path.join(&layout.duplicate)).unwrap();
+    fs::hard_link(
+        post_path.join(&layout.initial),
+        post_path.join(&layout.duplicate),
+    )
+    .unwrap();
+    fs::write(post_path.join(&layout.svg), "<svg/>").unwrap();
+
+    let error = actions::finish(&location, "abcd:2004-07-11", None).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Validation));
+    assert!(post_path.is_dir());
+}
+
+#[test]
+fn finish_refuses_id_outside_posts_directory() {
+    let location = setup();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+
+    let error =
+        actions::finish(&location, "abcd:2004-07-11", Some("../escaped".to_string())).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Usage));
+    assert!(location.generated_dir().join("abcd:2004-07-11").is_dir());
+}
+
+#[test]
+fn finish_refuses_missing_post() {
+    let location = setup();
+
+    let error = actions::finish(&location, "abcd:2004-07-11", None).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::MissingPost));
+}
+
+#[test]
+fn upload_refuses_untranscribed_post() {
+    let location = setup();
+    testing::write_post(&location, Stage::Completed, "0", date("2004-07-11")).unwrap();
+
+    let error = actions::upload(&location, "0", None, None, false).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Validation));
+}
+
+#[test]
+fn upload_refuses_unapproved_post() {
+    let location = setup();
+    let post_path =
+        testing::write_post(&location, Stage::Completed, "0", date("2004-07-11")).unwrap();
+    testing::write_transcript(&location, "0", "---\n---").unwrap();
+    fs::write(post_path.join("props"), "approval needs-review\n").unwrap();
+
+    let error = actions::upload(&location, "0", None, None, false).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Validation));
+}
+
+#[test]
+fn resolve_id_by_prefix_and_date() {
+    let location = setup();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abxy:2004-08-01",
+        date("2004-08-01"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        names::resolve_id(&location, "abc").unwrap(),
+        "abcd:2004-07-11"
+    );
+    assert_eq!(
+        names::resolve_id(&location, "2004-08").unwrap(),
+        "abxy:2004-08-01"
+    );
+    let error = names::resolve_id(&location, "ab").unwrap_err();
+    assert_eq!(error_kind(&error), Some(ErrorKind::Usage));
+}
+
+#[test]
+fn rename_moves_post_in_every_directory() {
+    let location = setup();
+    testing::write_post(&location, Stage::Completed, "5", date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Old, "5", date("2004-07-11")).unwrap();
+
+    actions::rename(&location, "5", "6").unwrap();
+
+    assert!(location.posts_dir().join("6").is_dir());
+    assert!(location.old_dir().join("6").is_dir());
+    assert!(!location.posts_dir().join("5").exists());
+}
+
+#[test]
+fn rename_refuses_scheduled_post() {
+    let location = setup();
+    testing::write_post(&location, Stage::Completed, "5", date("2004-07-11")).unwrap();
+    fs::write(location.schedule_file(), "[posts]\n5 = \"2024-01-01\"\n").unwrap();
+
+    let error = actions::rename(&location, "5", "6").unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Usage));
+    assert!(location.posts_dir().join("5").is_dir());
+}
+
+#[test]
+fn duplicate_dates_ignore_revised_posts() {
+    let location = setup();
+    testing::write_post(&location, Stage::Completed, "5", date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Old, "5", date("2004-07-11")).unwrap();
+    testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-12",
+        date("2004-07-12"),
+    )
+    .unwrap();
+    testing::write_post(&location, Stage::Completed, "6", date("2004-07-12")).unwrap();
+
+    let duplicates = names::find_duplicate_dates(&location).unwrap();
+
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].0, date("2004-07-12"));
+}
+
+#[test]
+fn revise_resumes_after_post_was_moved() {
+    let location = setup();
+    testing::write_post(&location, Stage::Old, "3", date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Generated, "3", date("2004-07-11")).unwrap();
+
+    actions::revise(&location, "3").unwrap();
+
+    assert!(location.old_dir().join("3").is_dir());
+    assert!(location.generated_dir().join("3").is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn render_separates_linked_duplicate_image() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let mut location = setup();
+    // Exports a black image, instead of rendering the SVG file
+    let render_path = location.base_dir().join("render.png");
+    let program_path = location.base_dir().join("render");
+    fs::write(
+        &program_path,
+        format!(
+            "#!/bin/sh\n\
+            while [ \"$1\" != --export-filename ]; do shift; done\n\
+            cp '{}' \"$2\"\n",
+            render_path.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&program_path, fs::Permissions::from_mode(0o755)).unwrap();
+    location.config_mut().render.program = program_path.to_string_lossy().to_string();
+
+    let post_path =
+        testing::write_post(&location, Stage::Completed, "3", date("2004-07-12")).unwrap();
+    let layout = location.layout();
+    let initial_path = post_path.join(&layout.initial);
+    let duplicate_path = post_path.join(&layout.duplicate);
+    fs::remove_file(&duplicate_path).unwrap();
+    fs::hard_link(&initial_path, &duplicate_path).unwrap();
+    fs::write(post_path.join(&layout.svg), "<svg/>").unwrap();
+    let (width, height) = image::image_dimensions(&duplicate_path).unwrap();
+    image::RgbaImage::from_pixel(width, height, image::Rgba([0, 0, 0, 255]))
+        .save(&render_path)
+        .unwrap();
+    let duplicate = fs::read(&duplicate_path).unwrap();
+
+    actions::render(&location, "3", None).unwrap();
+
+    assert_eq!(
+        fs::read(&initial_path).unwrap(),
+        fs::read(&render_path).unwrap()
+    );
+    assert_eq!(fs::read(&duplicate_path).unwrap(), duplicate);
+}
+
+#[test]
+fn revise_moves_old_post_and_finishes_remade_post() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default());
+    let prompter = ScriptedPrompter::new([""]);
+    let prompts = prompter.prompts();
+    location.set_prompter(prompter);
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+    testing::write_transcript(&location, "3", "[1]\nhello\n").unwrap();
+
+    actions::remake(&location, "3", false).unwrap();
+    actions::revise(&location, "3").unwrap();
+
+    assert!(prompts
+        .borrow()
+        .iter()
+        .any(|prompt| prompt.starts_with("Move old post to old directory?")));
+    assert!(!location.posts_dir().join("3").exists());
+    assert!(location.old_dir().join("3").is_dir());
+    let generated_path = location.generated_dir().join("3");
+    assert_eq!(
+        fs::read_to_string(generated_path.join("title")).unwrap(),
+        "Test"
+    );
+
+    actions::finish_revised_post(&location, "3").unwrap();
+
+    let post_path = location.posts_dir().join("3");
+    assert!(!generated_path.exists());
+    assert_eq!(
+        fs::read_to_string(post_path.join("transcript")).unwrap(),
+        "[1]\nhello\n"
+    );
+}
+
+#[test]
+fn show_opens_viewer_and_records_date() {
+    let mut location = setup();
+    let system = RecordingSystem::default();
+    let calls = system.calls();
+    location.set_system(system);
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+
+    actions::show(&location, date("2004-07-11")).unwrap();
+
+    assert_eq!(
+        names::get_make_date(&location, None, Some(1)).unwrap(),
+        date("2004-07-11")
+    );
+    assert!(calls.borrow().iter().any(|call| matches!(
+        call,
+        SystemCall::SpawnImageViewer { paths, .. } if paths == &[location.source_file(date("2004-07-11"))]
+    )));
+}
+
+#[test]
+fn transcribe_without_changes_keeps_no_transcript() {
+    let mut location = setup();
+    let system = RecordingSystem::default();
+    let calls = system.calls();
+    location.set_system(system);
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+
+    actions::transcribe(&location, "3", false, None).unwrap();
+
+    assert!(!location.posts_dir().join("3").join("transcript").exists());
+    assert!(!location.temp_transcript_file("3").exists());
+    let calls = calls.borrow();
+    assert!(calls
+        .iter()
+        .any(|call| matches!(call, SystemCall::SetupImageViewerWindow { .. })));
+    assert!(calls
+        .iter()
+        .any(|call| matches!(call, SystemCall::OpenEditor { .. })));
+}
+
+#[test]
+fn transcribe_saves_after_confirming() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default().with_editor_contents("[1]\nhello\n"));
+    let prompter = ScriptedPrompter::new([""]);
+    let prompts = prompter.prompts();
+    location.set_prompter(prompter);
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+
+    actions::transcribe(&location, "3", false, None).unwrap();
+
+    assert_eq!(*prompts.borrow(), ["Save transcript file? [Y/n]"]);
+    let transcript = location.posts_dir().join("3").join("transcript");
+    assert_eq!(fs::read_to_string(transcript).unwrap(), "[1]\nhello\n");
+}
+
+#[test]
+fn transcribe_records_author_from_identity() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default().with_editor_contents("[1]\nhello\n"));
+    location.set_prompter(ScriptedPrompter::new([""]));
+    location.config_mut().identity.name = Some("Alex".to_string());
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+
+    actions::transcribe(&location, "3", false, None).unwrap();
+
+    let props = fs::read_to_string(location.posts_dir().join("3").join("props")).unwrap();
+    assert!(props.lines().any(|line| line == "author transcript Alex"));
+}
+
+#[test]
+fn transcribe_keeps_unsaved_transcript_when_declined_by_config() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default().with_editor_contents("[1]\nhello\n"));
+    // Panics if asked anything
+    location.set_prompter(ScriptedPrompter::default());
+    location.config_mut().prompts.save_transcript = PromptPolicy::No;
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+
+    actions::transcribe(&location, "3", false, None).unwrap();
+
+    assert!(!location.posts_dir().join("3").join("transcript").exists());
+    assert!(location.temp_transcript_file("3").exists());
+}
+
+#[test]
+fn declined_prompt_fails_with_abort_on_no() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default().with_editor_contents("[1]\nhello\n"));
+    location.set_prompter(ScriptedPrompter::new(["n"]));
+    location.config_mut().prompts.abort_on_no = true;
+    testing::write_source_comic(&location, date("2004-07-11")).unwrap();
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+
+    let error = actions::transcribe(&location, "3", false, None).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Declined));
+    assert!(!location.posts_dir().join("3").join("transcript").exists());
+}
+
+#[test]
+fn pulled_submission_is_saved_when_accepted() {
+    let mut location = setup();
+    location.set_system(RecordingSystem::default());
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+    let submission = location.submission_file("3");
+    fs::create_dir_all(submission.parent().unwrap()).unwrap();
+    fs::write(&submission, "[1]\nhello\n").unwrap();
+
+    actions::pull_queue(&location).unwrap();
+
+    let post_path = location.posts_dir().join("3");
+    assert!(!submission.exists());
+    assert!(post_path.join("transcript.pending").exists());
+
+    actions::review(&location, "3", Some(true), Some(true), None).unwrap();
+
+    assert!(!post_path.join("transcript.pending").exists());
+    assert_eq!(
+        fs::read_to_string(post_path.join("transcript")).unwrap(),
+        "[1]\nhello\n"
+    );
+}
+
+#[test]
+fn upload_copies_post_files() {
+    let mut location = setup();
+    let system = RecordingSystem::default();
+    let calls = system.calls();
+    location.set_system(system);
+    testing::write_post(&location, Stage::Completed, "3", date("2004-07-11")).unwrap();
+    testing::write_transcript(&location, "3", "---\n---").unwrap();
+
+    actions::upload(&location, "3", None, None, false).unwrap();
+
+    let uploaded: Vec<_> = calls
+        .borrow()
+        .iter()
+        .filter_map(|call| match call {
+            SystemCall::UploadFile { path } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    let post_path = location.posts_dir().join("3");
+    assert_eq!(uploaded.len(), 4);
+    assert_eq!(uploaded[2], post_path.join("transcript"));
+    assert!(post_path.join("uploaded").is_file());
+}
+
+#[test]
+fn config_refuses_variant_named_like_duplicate() {
+    let location = setup();
+    let config_path = location.base_dir().join("config.toml");
+    fs::write(&config_path, "[layout]\nvariants = [\"english\"]\n").unwrap();
+
+    let error = garfutils::Config::load(&config_path).unwrap_err();
+
+    assert_eq!(error_kind(&error), Some(ErrorKind::Usage));
+}
+
+#[test]
+fn open_without_file_opens_post_directory() {
+    let mut location = setup();
+    let system = RecordingSystem::default();
+    let calls = system.calls();
+    location.set_system(system);
+    let post_path = testing::write_post(
+        &location,
+        Stage::Generated,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+
+    actions::open(&location, "abcd:2004-07-11", None).unwrap();
+
+    assert!(calls
+        .borrow()
+        .iter()
+        .any(|call| matches!(call, SystemCall::SpawnDetached { path, .. } if path == &post_path)));
+}
+
+#[test]
+fn recent_dates_read_old_and_new_format() {
+    let location = setup();
+    fs::write(
+        location.recent_file(),
+        "2004-07-10\n2004-07-11\t2024-01-31T12:00:00\tshow --sunday\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        names::get_make_date(&location, None, Some(1)).unwrap(),
+        date("2004-07-11")
+    );
+    assert_eq!(
+        names::get_make_date(&location, None, Some(2)).unwrap(),
+        date("2004-07-10")
+    );
+    assert!(names::get_make_date(&location, None, Some(3)).is_err());
+}
+
+#[test]
+fn backup_restore_brings_back_deleted_post() {
+    let location = setup();
+    let post_path = testing::write_post(
+        &location,
+        Stage::Completed,
+        "abcd:2004-07-11",
+        date("2004-07-11"),
+    )
+    .unwrap();
+    testing::write_transcript(&location, "abcd:2004-07-11", "---\n---").unwrap();
+
+    actions::backup(&location, None, Some(1)).unwrap();
+    let timestamp = fs::read_dir(location.backups_dir())
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .next()
+        .unwrap();
+    fs::remove_dir_all(&post_path).unwrap();
+
+    actions::backup_restore(&location, None, &timestamp).unwrap();
+    assert!(post_path.join("transcript").is_file());
+}
+
+#[test]
+fn pipeline_allows_only_workflow_transitions() {
+    use PipelineStage::*;
+    const ALL: [PipelineStage; 7] = [
+        Generated,
+        Titled,
+        Finished,
+        Transcribed,
+        Revised,
+        Uploaded,
+        Archived,
+    ];
+    let allowed = [
+        (Generated, Titled),
+        (Generated, Finished),
+        (Titled, Finished),
+        (Revised, Finished),
+        (Finished, Transcribed),
+        (Transcribed, Transcribed),
+        (Uploaded, Transcribed),
+        (Transcribed, Uploaded),
+        (Uploaded, Uploaded),
+        (Finished, Revised),
+        (Transcribed, Revised),
+        (Uploaded, Revised),
+    ];
+
+    for current in ALL {
+        for next in ALL {
+            assert_eq!(
+                current.can_transition_to(next),
+                allowed.contains(&(current, next)),
+                "{} -> {}",
+                current,
+                next
+            );
+        }
+    }
+}